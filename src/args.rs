@@ -1,11 +1,26 @@
 use clap::Parser;
-use mocksmith::MethodsToMockStrategy;
+use mocksmith::{
+    FactoryPointer, HelperAllocation, Language, LineEnding, MethodsToMockStrategy, MockStyle,
+};
 use std::path::PathBuf;
 
 /// Generates mocks for the Google Mock framework (gmock) from C++ header files. If no
 /// header files are provided, stdin is read and mocks are generated from the content.
 #[derive(Parser, Debug)]
-#[command(version, about)]
+#[command(
+    version,
+    about,
+    group(
+        clap::ArgGroup::new("input")
+            .multiple(true)
+            .args(["source_files", "files_from", "stdin_name"])
+    ),
+    group(
+        clap::ArgGroup::new("headers")
+            .multiple(true)
+            .args(["source_files", "files_from"])
+    )
+)]
 pub(crate) struct Arguments {
     /// Directory to add to the include search path. This needs to be set up properly to
     /// find types used in source header files. It is also used to determine the relative path
@@ -13,6 +28,37 @@ pub(crate) struct Arguments {
     #[arg(short = 'I', long)]
     pub(crate) include_dir: Vec<PathBuf>,
 
+    /// Directory to add to the system include search path, like the -isystem compiler
+    /// option, e.g., for third-party libraries. Warnings in headers in the directory are
+    /// suppressed, and the directory is not used to determine the path to use when
+    /// including the source header file.
+    #[arg(long = "isystem", value_name = "DIR")]
+    pub(crate) system_include_dir: Vec<PathBuf>,
+
+    /// Includes the mocked headers with angle brackets rather than quotes.
+    #[arg(long)]
+    pub(crate) include_angle_brackets: bool,
+
+    /// Includes the headers declaring types used in the signatures of mocked methods in
+    /// mock headers, not only the mocked headers. System headers are not included.
+    #[arg(long)]
+    pub(crate) include_type_headers: bool,
+
+    /// Forward declares classes only used by pointer or reference in the signatures of
+    /// mocked methods, rather than including their headers.
+    #[arg(long, requires = "include_type_headers")]
+    pub(crate) forward_declare_types: bool,
+
+    /// A prefix to prepend to the paths of the mocked headers when including them, e.g.,
+    /// mylib to include <mylib/ifoo.h>.
+    #[arg(long, value_name = "PREFIX")]
+    pub(crate) include_prefix: Option<String>,
+
+    /// A prefix to strip from the paths of the mocked headers when including them. It is
+    /// stripped before adding any prefix from --include-prefix.
+    #[arg(long, value_name = "PREFIX")]
+    pub(crate) strip_include_prefix: Option<String>,
+
     /// Selects which methofd to mock in a class. Either all virtual methods
     /// (default), only pure virtual methods or all non-static methods are mocked.
     /// This also affects which classes to mock, since classes with no matching methods
@@ -20,9 +66,36 @@ pub(crate) struct Arguments {
     #[arg(short = 'm', long = "methods", value_parser = ["virtual", "pure", "all"])]
     pub(crate) methods_to_mock: Option<String>,
 
-    /// A regex to filter classes to mock by name.
+    /// Selects the style of the generated code, either Google Mock mocks (default) or
+    /// plain fakes implementing methods with trivial bodies.
+    #[arg(long, value_parser = ["gmock", "fake"])]
+    pub(crate) style: Option<String>,
+
+    /// Selects the mocking framework to generate mocks for, either Google Mock (default)
+    /// or FakeIt. FakeIt mocks fake all methods on construction.
+    #[arg(long, value_parser = ["gmock", "fakeit"], conflicts_with = "style")]
+    pub(crate) framework: Option<String>,
+
+    /// A regex to filter classes to mock by name. May be given several times to mock
+    /// classes matching any of the regexes.
     #[arg(short = 'c', long = "class-filter", value_name = "FILTER")]
-    pub(crate) class_filter: Option<String>,
+    pub(crate) class_filter: Vec<String>,
+
+    /// A regex to exclude classes from mocking by name, applied after --class-filter. May
+    /// be given several times to exclude classes matching any of the regexes.
+    #[arg(long, value_name = "REGEX")]
+    pub(crate) class_exclude: Vec<String>,
+
+    /// A regex to filter methods to mock by name. May be given several times to mock
+    /// methods matching any of the regexes. Methods that are not mocked are inherited from
+    /// the mocked class.
+    #[arg(long, value_name = "REGEX")]
+    pub(crate) method_filter: Vec<String>,
+
+    /// A regex to exclude methods from mocking by name, applied after --method-filter. May
+    /// be given several times to exclude methods matching any of the regexes.
+    #[arg(long, value_name = "REGEX")]
+    pub(crate) method_exclude: Vec<String>,
 
     /// A sed style regex replacement string to convert class names to mock names.
     #[arg(short = 'n', long = "name-mock", value_name = "SED_REPLACEMENT")]
@@ -40,22 +113,110 @@ pub(crate) struct Arguments {
 
     /// If set, all generated mocks are written to the specified file. If neither an output
     /// file nor directory is specified, the mocks are printed to stdout. Input from stdin
-    /// always generates output to stdout.
-    #[arg(short = 'o', long, group = "output", requires = "source_files")]
+    /// generates output to stdout, unless --stdin-name is specified. Use - to print the
+    /// complete file to stdout, with logging to stderr.
+    #[arg(short = 'o', long, group = "output", requires = "input")]
     pub(crate) output_file: Option<PathBuf>,
 
+    /// Adds a region delimited by '// mocksmith:keep(MockFoo)' and '// mocksmith:endkeep'
+    /// lines at the start of each mock, for hand-written members. The content of the
+    /// regions in existing output files is kept when regenerating the mocks.
+    #[arg(long)]
+    pub(crate) keep_regions: bool,
+
+    /// Updates the existing output file by only replacing the lines between the marker
+    /// lines '// mocksmith:begin' and '// mocksmith:end' with the mocks, keeping
+    /// hand-written code outside the markers.
+    #[arg(long, requires = "output_file")]
+    pub(crate) in_place: bool,
+
     /// If set, all generated mocks are written to files in the specified directory.
     /// Files are named after the source class header file. If neither an output
     /// file nor directory is specified, the mocks are printed to stdout. Input from stdin
     /// always generates output to stdout. If the directory does not exist, it is created,
     /// unless --no-create-output-dir is specified.
-    #[arg(short = 'd', long, group = "output", requires = "source_files")]
+    #[arg(short = 'd', long, group = "output", requires = "headers")]
     pub(crate) output_dir: Option<PathBuf>,
 
     /// Don't create the output directory if it does not exist.
     #[arg(long, requires = "output_dir")]
     pub(crate) no_create_output_dir: bool,
 
+    /// Writes a manifest, mocksmith_manifest.json, to the output directory, listing the
+    /// output files, i.e., mock headers, split sources and fixtures, and the mocked
+    /// classes and mock names for each header.
+    #[arg(long, requires = "output_dir", conflicts_with = "output_file")]
+    pub(crate) manifest: bool,
+
+    /// Mirrors the directory structure of the header files under the source root in the
+    /// output directory, e.g., a mock for src/net/isocket.h with source root src is written
+    /// to the subdirectory net of the output directory.
+    #[arg(long, requires_all = ["output_dir", "source_root"])]
+    pub(crate) mirror_tree: bool,
+
+    /// Root directory of the header files, when mirroring the directory structure with
+    /// --mirror-tree.
+    #[arg(long, value_name = "DIR", requires = "mirror_tree")]
+    pub(crate) source_root: Option<PathBuf>,
+
+    /// Prints the files that would be written to the output directory to stdout instead,
+    /// each preceded by a marker line with the file name, e.g., '--- file: MockFoo.h', to
+    /// be split by scripts.
+    #[arg(long, requires = "output_dir")]
+    pub(crate) stdout_multiplex: bool,
+
+    /// Writes a dependency file in Make format to the given path, listing the mocked
+    /// headers and all headers they include as dependencies of the output files. This lets
+    /// build systems, e.g., Make and Ninja, regenerate mocks when any of the headers change.
+    #[arg(long, value_name = "PATH", requires = "output")]
+    pub(crate) depfile: Option<PathBuf>,
+
+    /// A shell command to pipe the content of each generated C++ file through before
+    /// writing it, e.g., a formatter. The path of the file is in the MOCKSMITH_OUTPUT_FILE
+    /// environment variable.
+    #[arg(long, value_name = "CMD", requires = "output")]
+    pub(crate) post_process: Option<String>,
+
+    /// Removes files in the output directory generated by an earlier run but not by this
+    /// run, e.g., mocks of removed classes. Generated files are identified by the manifest
+    /// of the earlier run, see --manifest, so files generated by other runs are kept.
+    #[arg(long, requires = "manifest", conflicts_with = "stdout_multiplex")]
+    pub(crate) prune: bool,
+
+    /// Continues with the other header files when mocks can't be created from a header
+    /// file, e.g., due to parse errors. The mocks created are written, and the failed
+    /// header files are listed before exiting with a failure.
+    #[arg(long, requires = "output_dir")]
+    pub(crate) keep_going: bool,
+
+    /// Prints the time spent parsing, finding the classes to mock, generating code and
+    /// reading and writing files, per header file and in total, to stderr.
+    #[arg(long)]
+    pub(crate) stats: bool,
+
+    /// Fails if no mocks are generated from any of the header files, e.g., due to
+    /// misconfigured filters. With 'each', fails if no mocks are generated from one of the
+    /// header files.
+    #[arg(
+        long,
+        value_name = "MODE",
+        value_parser = ["all", "each"],
+        num_args = 0..=1,
+        default_missing_value = "all"
+    )]
+    pub(crate) fail_on_empty: Option<String>,
+
+    /// Prints unified diffs between the existing output files and the generated content
+    /// instead of writing the files, to review what regenerating mocks would change.
+    #[arg(long, requires = "output", conflicts_with = "stdout_multiplex")]
+    pub(crate) diff: bool,
+
+    /// Checks that the output files are up to date instead of writing them, e.g., in CI.
+    /// The changes are printed like with --diff, and the program exits with code 5 if any
+    /// output file would change.
+    #[arg(long, requires = "output", conflicts_with = "stdout_multiplex")]
+    pub(crate) check: bool,
+
     /// Forces writing output files without checking if the content has changed.
     #[arg(short = 'w', long)]
     pub(crate) always_write: bool,
@@ -67,26 +228,369 @@ pub(crate) struct Arguments {
         "gnu++11", "gnu++14", "gnu++17", "gnu++20", "gnu++23", "gnu++2c"])]
     pub(crate) std: Option<String>,
 
+    /// The language to parse the source header files as. Use objective-c++ for headers
+    /// also containing Objective-C. Only C++ classes are mocked.
+    #[arg(long, value_parser = ["c++", "objective-c++"])]
+    pub(crate) language: Option<String>,
+
     /// Additional arguments to the clang C++ parser.
     #[arg(short = 'a', long = "clang-arg", value_name = "ARG")]
     pub(crate) clang_args: Vec<String>,
 
+    /// The system root directory to parse source headers with, e.g., of a cross-compilation
+    /// toolchain. Detected system include paths of the host are not used.
+    #[arg(long, value_name = "DIR")]
+    pub(crate) sysroot: Option<PathBuf>,
+
+    /// The target triple to parse source headers for, e.g., aarch64-linux-gnu. Detected
+    /// system include paths of the host are not used. Default is the host target.
+    #[arg(long, value_name = "TRIPLE")]
+    pub(crate) target: Option<String>,
+
+    /// Defines a macro when parsing the header files, like the -D compiler option. May be
+    /// given several times.
+    #[arg(short = 'D', long = "define", value_name = "NAME[=VALUE]")]
+    pub(crate) defines: Vec<String>,
+
+    /// Undefines a macro when parsing the header files, like the -U compiler option. Macros
+    /// are undefined after all macros are defined. May be given several times.
+    #[arg(short = 'U', long = "undefine", value_name = "NAME")]
+    pub(crate) undefines: Vec<String>,
+
+    /// Header to include before the content of each header file to mock when parsing, like
+    /// the -include compiler option, e.g., a config.h with macros needed to parse the
+    /// header files. May be given several times.
+    #[arg(long, value_name = "FILE")]
+    pub(crate) force_include: Vec<PathBuf>,
+
+    /// Adds the include paths in the CPATH and CPLUS_INCLUDE_PATH environment variables to
+    /// the include search path, like compilers do.
+    #[arg(long)]
+    pub(crate) use_env_includes: bool,
+
+    /// Detects the system include paths, e.g., of the C++ standard library, from clang++ or
+    /// the INCLUDE environment variable on Windows, and the SDK system root on macOS.
+    /// Helps when the parser doesn't find the standard library by itself.
+    #[arg(long)]
+    pub(crate) detect_system_includes: bool,
+
+    /// Skips mocking methods marked as deprecated.
+    #[arg(long)]
+    pub(crate) skip_deprecated: bool,
+
+    /// Reproduces standard attributes of mocked methods, i.e., [[nodiscard]] and
+    /// [[deprecated]], on overrides forwarding to mocked delegates, e.g., DoBar for bar.
+    #[arg(long)]
+    pub(crate) keep_attributes: bool,
+
+    /// Generates helpers, e.g., DelegateGetToConst(), making non-const overloads call their
+    /// const overloads by default, for methods overloaded only on constness.
+    #[arg(long)]
+    pub(crate) const_overload_helpers: bool,
+
+    /// Mocks methods taking or returning std::unique_ptr by value with delegate methods
+    /// using raw pointers, e.g., DoShare(), called from overriding adapter methods.
+    #[arg(long)]
+    pub(crate) move_only_helpers: bool,
+
+    /// Adds constructors to mocks setting default actions for methods returning
+    /// references, returning references to static dummy objects. Otherwise gmock aborts
+    /// when such methods are called without actions.
+    #[arg(long)]
+    pub(crate) reference_default_actions: bool,
+
+    /// Adds #line directives before mocked methods, so compiler errors in the mocked
+    /// signatures point to the method declarations in the mocked headers. Following code
+    /// is numbered as in the generated files.
+    #[arg(long)]
+    pub(crate) line_directives: bool,
+
+    /// Adds constructors to mocks setting default actions for methods returning default
+    /// constructible and copyable types, returning default values, e.g., 0 or Foo{}.
+    #[arg(long)]
+    pub(crate) value_default_actions: bool,
+
+    /// Adds aliases for NiceMock and StrictMock of each mock, e.g., NiceMockFoo and
+    /// StrictMockFoo for MockFoo.
+    #[arg(long)]
+    pub(crate) mock_aliases: bool,
+
+    /// Adds a factory helper for each mock, e.g., makeMockFoo(MockFoo *& out), returning
+    /// the mock as a std::unique_ptr or std::shared_ptr to the mocked class.
+    #[arg(long, value_name = "POINTER", value_parser = ["unique", "shared"])]
+    pub(crate) factory_helpers: Option<String>,
+
+    /// Adds helpers for each mock, e.g., makeNiceMockFoo() and makeStrictMockFoo(),
+    /// returning NiceMock and StrictMock instances by value (requires C++17) or heap
+    /// allocated in a std::unique_ptr.
+    #[arg(
+        long,
+        value_name = "ALLOCATION",
+        value_parser = ["value", "heap"],
+        num_args = 0..=1,
+        default_missing_value = "value"
+    )]
+    pub(crate) nice_strict_helpers: Option<String>,
+
+    /// Adds a static_assert after each mock checking that the mock is not abstract, i.e.,
+    /// that no pure virtual method is left unmocked.
+    #[arg(long)]
+    pub(crate) assert_not_abstract: bool,
+
+    /// Extracts interfaces from concrete classes. For each class, e.g., Foo, generates an
+    /// interface IFoo with the public methods of the class, an adapter FooAdapter
+    /// forwarding calls to a Foo, and a mock of the interface.
+    #[arg(long)]
+    pub(crate) extract_interface: bool,
+
+    /// Wraps MOCK_METHOD lines longer than the given number of columns.
+    #[arg(long, value_name = "COLUMNS")]
+    pub(crate) column_limit: Option<usize>,
+
+    /// Extra namespace to put mocks in, e.g., testing::mocks, nested inside the
+    /// namespaces of the mocked classes. Mocked classes are referred to with fully
+    /// qualified names from the mocks.
+    #[arg(long, value_name = "NAMESPACE")]
+    pub(crate) mock_namespace: Option<String>,
+
+    /// Mocks methods under the same access specifiers as in the mocked classes, e.g.,
+    /// protected methods under protected. Otherwise all methods are mocked as public.
+    #[arg(long)]
+    pub(crate) preserve_access: bool,
+
+    /// Befriends a test in each mock with FRIEND_TEST, given as TestSuite.TestName, so the
+    /// test can call protected and private mocked methods. May be given multiple times.
+    #[arg(long, value_name = "SUITE.NAME", requires = "preserve_access")]
+    pub(crate) friend_test: Vec<String>,
+
+    /// Rewrites the types of arguments and return values to fully qualified spellings,
+    /// for mocked headers relying on using namespace directives.
+    #[arg(long)]
+    pub(crate) fully_qualify_types: bool,
+
+    /// Sorts mocks by the qualified names of the mocked classes and mocked methods by name
+    /// and signature, to keep generated code stable when headers are reorganized.
+    #[arg(long)]
+    pub(crate) sort: bool,
+
+    /// The style of include guards in mock headers, either #pragma once (default) or
+    /// #ifndef/#define guards named with --include-guard-pattern.
+    #[arg(long, value_parser = ["pragma-once", "ifndef"])]
+    pub(crate) include_guard_style: Option<String>,
+
+    /// The pattern for #ifndef include guard names. {PROJECT} is replaced with the
+    /// project set with --include-guard-project, and {PATH} and {FILE} with the directory
+    /// and the stem of the include path of the mocked header.
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        default_value = "{PATH}_{FILE}_MOCK_H_",
+        requires = "include_guard_style"
+    )]
+    pub(crate) include_guard_pattern: String,
+
+    /// The project name replacing {PROJECT} in include guard patterns.
+    #[arg(long, value_name = "NAME", requires = "include_guard_style")]
+    pub(crate) include_guard_project: Option<String>,
+
+    /// Splits mocks into header and source files, defining the constructors and
+    /// destructors of the mocks in the source files to reduce compile times. The source
+    /// files get the same names as the headers, but with the extension cpp.
+    #[arg(long, requires = "output_dir")]
+    pub(crate) split_source: bool,
+
+    /// The macro used to mock methods, e.g., a project specific wrapper of MOCK_METHOD
+    /// taking the same arguments.
+    #[arg(long, value_name = "NAME", default_value = "MOCK_METHOD")]
+    pub(crate) mock_macro: String,
+
+    /// Declares type aliases in mocks for types containing commas, instead of wrapping
+    /// the types in parentheses.
+    #[arg(long)]
+    pub(crate) comma_type_aliases: bool,
+
+    /// Adds a mocked method Die, called from the destructor, to mocks of classes with
+    /// virtual destructors, to be able to expect destruction.
+    #[arg(long)]
+    pub(crate) mock_destructor: bool,
+
+    /// Writes each mock to a separate file, named after the mock, rather than writing all
+    /// mocks of a header to one file.
+    #[arg(
+        long,
+        requires = "output_dir",
+        conflicts_with = "name_output_file_sed_replacement"
+    )]
+    pub(crate) split_per_class: bool,
+
+    /// Generates a header for each mock header, named like the mock header but with the
+    /// suffix _checks, with static assertions checking that the signatures of the mocked
+    /// methods are unchanged since the mocks were generated.
+    #[arg(long, requires = "output_dir")]
+    pub(crate) signature_checks: bool,
+
+    /// Generates a skeleton of a gtest fixture for each mock, with the mock as a member
+    /// and an empty first test. The fixtures are written to files named after the
+    /// fixtures, e.g., FooTest.cpp, unless the files already exist.
+    #[arg(long, requires = "output_dir")]
+    pub(crate) emit_fixture: bool,
+
+    /// Generates a markdown cheat sheet for each mock, listing an EXPECT_CALL with
+    /// placeholder matchers for each mocked method. The cheat sheets are written to files
+    /// named after the mocks, e.g., MockFoo.md.
+    #[arg(long, requires = "output_dir")]
+    pub(crate) emit_cheat_sheet: bool,
+
+    /// Writes markdown documentation of the mocks of each header to the given directory,
+    /// listing the mocked classes with namespaces, mocked and skipped methods and the
+    /// output files. The files are named after the mock headers, e.g., MockFoo.md.
+    #[arg(long, value_name = "DIR", requires = "output_dir")]
+    pub(crate) emit_docs: Option<PathBuf>,
+
+    /// A template file controlling the layout of the generated code, using Jinja2 syntax.
+    /// The template renders the complete header and may define a macro mock(class)
+    /// rendering each mock and a macro method(method) rendering each mocked method. See the library documentation for available variables. Any
+    /// license header is added before the rendered code.
+    #[arg(long, value_name = "FILE")]
+    pub(crate) template: Option<PathBuf>,
+
+    /// A banner comment replacing the "Automatically generated" comment at the top of
+    /// generated files. {SOURCE_FILES}, {CLASSES} and {VERSION} are replaced with the
+    /// mocked header files, the mocked classes and the Mocksmith version.
+    #[arg(long, value_name = "TEXT", conflicts_with = "banner_file")]
+    pub(crate) banner_text: Option<String>,
+
+    /// A file with a banner comment, see --banner-text.
+    #[arg(long, value_name = "FILE")]
+    pub(crate) banner_file: Option<PathBuf>,
+
+    /// A file with a license header inserted at the top of generated files, before the
+    /// banner. {YEAR} and {FILE_NAME} are replaced with the current year and the mocked
+    /// header files.
+    #[arg(long, value_name = "FILE")]
+    pub(crate) license_file: Option<PathBuf>,
+
+    /// Line endings of generated files.
+    #[arg(long, value_name = "STYLE", value_parser = ["lf", "crlf"], default_value = "lf")]
+    pub(crate) line_endings: String,
+
+    /// Makes sure generated files end with a newline, also when using a template.
+    #[arg(long)]
+    pub(crate) final_newline: bool,
+
+    /// Starts generated files with a UTF-8 byte order mark.
+    #[arg(long)]
+    pub(crate) bom: bool,
+
     /// Adds MSVC compiler pragmas to disable warnings for overriding deprecated methods.
-    /// This option can only be used when producing header files.
+    /// This option can only be used when producing header files. The pragmas are always
+    /// added when any mocked method is marked as deprecated.
     #[arg(long, requires = "output")]
     pub(crate) msvc_allow_deprecated: bool,
 
+    /// Adds GCC diagnostic pragmas, also understood by Clang, to disable warnings for
+    /// overriding deprecated methods. This option can only be used when producing header
+    /// files.
+    #[arg(long, requires = "output")]
+    pub(crate) gcc_allow_deprecated: bool,
+
+    /// Adds Clang and GCC diagnostic pragmas to disable warnings commonly triggered by
+    /// mocks in strict builds, like -Wsuggest-override, -Wweak-vtables and
+    /// -Winconsistent-missing-destructor-override. This option can only be used when
+    /// producing header files.
+    #[arg(long, requires = "output")]
+    pub(crate) suppress_warnings: bool,
+
+    /// Surrounds generated code with NOLINTBEGIN/NOLINTEND comments to suppress
+    /// clang-tidy checks. Without a value all checks are suppressed, otherwise only the
+    /// given comma separated checks, e.g., "modernize-*,cppcoreguidelines-*".
+    #[arg(
+        long,
+        value_name = "CHECKS",
+        num_args = 0..=1,
+        default_missing_value = "",
+        requires = "output"
+    )]
+    pub(crate) nolint: Option<String>,
+
+    /// Adds a comment with a SHA-256 hash of the mocked class declarations to headers,
+    /// to detect stale mocks without parsing the source files.
+    #[arg(long)]
+    pub(crate) source_hash: bool,
+
+    /// Wraps the code of generated files in #if defined(MACRO) ... #endif, to only compile
+    /// mocks when the macro is defined, e.g., UNIT_TEST.
+    #[arg(long, value_name = "MACRO")]
+    pub(crate) guard_macro: Option<String>,
+
+    /// Generates C++20 module interface units exporting the mocks, with the given module
+    /// name, instead of headers. Output files are named with extension .cppm by default.
+    #[arg(
+        long,
+        value_name = "NAME",
+        requires = "output",
+        conflicts_with_all = [
+            "guard_macro",
+            "include_guard_style",
+            "split_source",
+            "emit_fixture",
+            "template",
+            "assert_not_abstract",
+            "forward_declare_types"
+        ]
+    )]
+    pub(crate) module: Option<String>,
+
+    /// Imports gmock as a header unit, import <gmock/gmock.h>, in module interface units
+    /// instead of including it. Requires a build system compiling gmock as a header unit.
+    #[arg(long, requires = "module")]
+    pub(crate) import_gmock: bool,
+
+    /// Predefines lightweight versions of Qt macros, like Q_OBJECT, signals and slots, to
+    /// be able to parse QObject derived classes. Signals and the meta-object methods
+    /// implemented by moc are never mocked.
+    #[arg(long)]
+    pub(crate) qt: bool,
+
+    /// Mocks COM interfaces, e.g., declared with MIDL_INTERFACE and STDMETHOD. COM macros
+    /// are predefined when not parsing for Windows, and the IUnknown methods are mocked
+    /// for interfaces inheriting IUnknown.
+    #[arg(long)]
+    pub(crate) com: bool,
+
     /// Ignores errors from parsing the C++ code. This may lead to unknown types in
     /// arguments being referred to as `int`, and entire methods and classes being
     /// ignored (when the return value of a method is unknown).
     #[arg(long)]
     pub(crate) ignore_errors: bool,
 
+    /// Prints the version of mocksmith, the version of the loaded libclang, where libclang
+    /// is searched for and the arguments passed to Clang, e.g., include paths and C++
+    /// standard, to diagnose environment issues. No mocks are generated.
+    #[arg(long)]
+    pub(crate) version_verbose: bool,
+
     /// Enables verbose output, printing debug information to stdout if writing mocks to
     /// file, otherwise to stderr.
     #[arg(short = 'v', long, group = "logging")]
     pub(crate) verbose: bool,
 
+    /// The format of diagnostics, i.e., parse errors and warnings. With json, a JSON array
+    /// of the diagnostics, with severity, message, file, line, column and source, is written
+    /// to --diagnostics-output when done, and errors are only reported in the array. Use
+    /// --silent to leave out the text log.
+    #[arg(long, value_name = "FORMAT", value_parser = ["text", "json"], default_value = "text")]
+    pub(crate) diagnostics: String,
+
+    /// Where to write JSON diagnostics, stdout, stderr or a file path.
+    #[arg(long, value_name = "DEST", default_value = "stderr")]
+    pub(crate) diagnostics_output: String,
+
+    /// Disables colors in diagnostics, which are otherwise colored when written to a
+    /// terminal unless the NO_COLOR environment variable is set.
+    #[arg(long)]
+    pub(crate) no_color: bool,
+
     /// Disables all log output, other than printing the reason for failure.
     #[arg(short = 's', long, group = "logging")]
     pub(crate) silent: bool,
@@ -95,6 +599,73 @@ pub(crate) struct Arguments {
     #[arg(long, hide = true)]
     pub(crate) parse_function_bodies: bool,
 
+    /// Lists the classes that can be mocked in the header files, with locations and the
+    /// number of methods mocked with each --methods strategy, instead of generating mocks.
+    /// The list is printed as text (default) or JSON.
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_parser = ["text", "json"],
+        num_args = 0..=1,
+        default_missing_value = "text",
+        requires = "headers",
+        conflicts_with = "output"
+    )]
+    pub(crate) list_classes: Option<String>,
+
+    /// Lists the classes found in the header files and asks which of them to mock before
+    /// generating mocks. The selection is read from stdin.
+    #[arg(
+        long,
+        requires = "headers",
+        conflicts_with_all = ["list_classes", "emit_model"]
+    )]
+    pub(crate) interactive: bool,
+
+    /// Prints the classes to mock in the header files as JSON, with their methods as
+    /// parsed, instead of generating mocks. Methods skipped are listed with reasons.
+    #[arg(
+        long,
+        requires = "headers",
+        conflicts_with_all = ["output", "list_classes"]
+    )]
+    pub(crate) emit_model: bool,
+
+    /// A glob pattern for header files to skip, e.g., '*_generated.h' or
+    /// 'third_party/**'. Patterns without a slash match file names, other patterns match
+    /// the paths as given. In patterns, * matches within a path component, ** matches
+    /// across components and ? matches a single character. May be given several times.
+    #[arg(long, value_name = "GLOB", requires = "headers")]
+    pub(crate) exclude: Vec<String>,
+
+    /// A file name for the content read from stdin, used when resolving includes, in parse
+    /// errors and when including the mocked header from a mock header written with
+    /// --output-file. The file doesn't need to exist.
+    #[arg(long, value_name = "PATH", conflicts_with = "headers")]
+    pub(crate) stdin_name: Option<PathBuf>,
+
+    /// A file listing header files to mock, one per line, e.g., from find or git ls-files.
+    /// Use - to read the list from stdin. The listed files are mocked after the header
+    /// files given as arguments.
+    #[arg(long, value_name = "PATH")]
+    pub(crate) files_from: Option<PathBuf>,
+
+    /// Only mocks the header files changed compared to a git ref, e.g., origin/main, or
+    /// including changed headers, to quickly regenerate mocks in pre-commit hooks. Includes
+    /// are found without parsing, searching the include directories.
+    #[arg(
+        long,
+        value_name = "REF",
+        requires = "output_dir",
+        conflicts_with_all = ["prune", "manifest", "depfile"]
+    )]
+    pub(crate) since: Option<String>,
+
+    /// Reads the header files listed with --files-from separated by NUL characters instead
+    /// of lines, e.g., from git ls-files -z or find -print0, to handle any file names.
+    #[arg(long, requires = "files_from")]
+    pub(crate) null: bool,
+
     /// Paths to the header files to mock. If no header files are provided, the
     /// program reads from stdin and generates mocks from the content.
     #[arg(value_name = "HEADER")]
@@ -103,16 +674,16 @@ pub(crate) struct Arguments {
 
 pub(crate) fn arguments() -> Arguments {
     let arguments = Arguments::parse();
-    // For some reason 'requires = "output_dir"' does not seem to work. Perhaps because
-    // it is in a group.
+    // 'requires = "output_dir"' is satisfied by --output-file too, since both are in the
+    // output group, so arguments that can't be used with either are checked here
     if arguments.output_dir.is_none() {
         if arguments.name_output_file_sed_replacement.is_some() {
             eprintln!("The argument --output-dir is required when --name-output-file is used");
-            std::process::exit(2);
+            std::process::exit(crate::exitcode::ARGUMENT_ERROR.into());
         }
         if arguments.no_create_output_dir {
             eprintln!("The argument --output-dir is required when --no-create-output-dir is used");
-            std::process::exit(2);
+            std::process::exit(crate::exitcode::ARGUMENT_ERROR.into());
         }
     }
     arguments
@@ -131,4 +702,59 @@ impl Arguments {
             MethodsToMockStrategy::AllVirtual
         }
     }
+
+    pub(crate) fn style(&self) -> MockStyle {
+        match (self.style.as_deref(), self.framework.as_deref()) {
+            (Some("fake"), _) => MockStyle::Fake,
+            (_, Some("fakeit")) => MockStyle::FakeIt,
+            _ => MockStyle::Gmock,
+        }
+    }
+
+    pub(crate) fn factory_helpers(&self) -> Option<FactoryPointer> {
+        match self.factory_helpers.as_deref() {
+            Some("unique") => Some(FactoryPointer::Unique),
+            Some("shared") => Some(FactoryPointer::Shared),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn language(&self) -> Language {
+        match self.language.as_deref() {
+            Some("objective-c++") => Language::ObjectiveCpp,
+            _ => Language::Cpp,
+        }
+    }
+
+    pub(crate) fn line_ending(&self) -> LineEnding {
+        match self.line_endings.as_str() {
+            "crlf" => LineEnding::Crlf,
+            _ => LineEnding::Lf,
+        }
+    }
+
+    pub(crate) fn nice_strict_helpers(&self) -> Option<HelperAllocation> {
+        match self.nice_strict_helpers.as_deref() {
+            Some("value") => Some(HelperAllocation::Value),
+            Some("heap") => Some(HelperAllocation::Heap),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn nolint_checks(&self) -> Option<Vec<String>> {
+        self.nolint.as_ref().map(|checks| {
+            checks
+                .split(',')
+                .map(|check| check.trim().to_string())
+                .filter(|check| !check.is_empty())
+                .collect()
+        })
+    }
+
+    pub(crate) fn include_guard_pattern(&self) -> Option<String> {
+        match self.include_guard_style.as_deref() {
+            Some("ifndef") => Some(self.include_guard_pattern.clone()),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,158 @@
+use crate::{Mocksmith, MocksmithError, Result, clangwrap::ClangWrap, log};
+
+type ConfigureFun = Box<dyn Fn(Mocksmith) -> Mocksmith + Send + Sync>;
+type LogWriteFun = Box<dyn Fn() -> Box<dyn std::io::Write> + Send + Sync>;
+
+/// Configuration of Mocksmith instances, prepared without using Clang.
+///
+/// Since Clang can only be used from one thread at a time, a `Mocksmith` instance holds
+/// exclusive access to Clang while it exists. A builder accumulates the settings instead,
+/// can be prepared in any thread and reused, and only acquires Clang when building an
+/// instance. Prefer `run()` to hold Clang only while generating mocks.
+///
+/// ```no_run
+/// # use mocksmith::{MethodsToMockStrategy, MocksmithBuilder};
+/// let builder = MocksmithBuilder::new()
+///     .include_path("include")
+///     .methods_to_mock(MethodsToMockStrategy::All)
+///     .configure(|mocksmith| mocksmith.sort_output(true));
+/// let mocks = builder.run(|mocksmith| mocksmith.create_mocks_for_file("include/IFoo.h"));
+/// ```
+#[derive(Default)]
+pub struct MocksmithBuilder {
+    log_write: Option<LogWriteFun>,
+    verbose: bool,
+    configure: Vec<ConfigureFun>,
+}
+
+impl MocksmithBuilder {
+    /// Creates a builder with the default settings and no logging.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a function creating the writer to log to for each instance built. Verbose
+    /// logging adds information about, e.g., Clang and detected system include paths.
+    pub fn log_write(
+        mut self,
+        log_write: impl Fn() -> Box<dyn std::io::Write> + Send + Sync + 'static,
+        verbose: bool,
+    ) -> Self {
+        self.log_write = Some(Box::new(log_write));
+        self.verbose = verbose;
+        self
+    }
+
+    /// Adds a function configuring each instance built, called with the instance after
+    /// the functions added earlier. Any setting of `Mocksmith` can be configured this way.
+    pub fn configure(
+        mut self,
+        configure: impl Fn(Mocksmith) -> Mocksmith + Send + Sync + 'static,
+    ) -> Self {
+        self.configure.push(Box::new(configure));
+        self
+    }
+
+    /// Adds an include path to the list of paths to search for headers, see
+    /// `Mocksmith::include_path()`.
+    pub fn include_path<P>(self, include_path: P) -> Self
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let include_path = include_path.as_ref().to_path_buf();
+        self.configure(move |mocksmith| mocksmith.include_path(&include_path))
+    }
+
+    /// Sets the strategy for which methods to mock, see `Mocksmith::methods_to_mock()`.
+    pub fn methods_to_mock(self, methods: crate::MethodsToMockStrategy) -> Self {
+        self.configure(move |mocksmith| mocksmith.methods_to_mock(methods))
+    }
+
+    /// Sets a function to generate mock names based on class names, see
+    /// `Mocksmith::mock_name_fun()`.
+    pub fn mock_name_fun(self, name_mock: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        let name_mock = std::sync::Arc::new(name_mock);
+        self.configure(move |mocksmith| {
+            let name_mock = name_mock.clone();
+            mocksmith.mock_name_fun(move |class_name| name_mock(class_name))
+        })
+    }
+
+    /// Sets a function to filter which classes to mock, see
+    /// `Mocksmith::class_filter_fun()`.
+    pub fn class_filter_fun(self, filter: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        let filter = std::sync::Arc::new(filter);
+        self.configure(move |mocksmith| {
+            let filter = filter.clone();
+            mocksmith.class_filter_fun(move |class_name| filter(class_name))
+        })
+    }
+
+    /// Sets a function to filter which methods to mock, see
+    /// `Mocksmith::method_filter_fun()`.
+    pub fn method_filter_fun(
+        self,
+        filter: impl Fn(&crate::MethodInfo) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        let filter = std::sync::Arc::new(filter);
+        self.configure(move |mocksmith| {
+            let filter = filter.clone();
+            mocksmith.method_filter_fun(move |method| filter(method))
+        })
+    }
+
+    /// Builds a configured Mocksmith instance.
+    ///
+    /// The function fails if another thread already holds an instance, like
+    /// `Mocksmith::new()`.
+    pub fn build(&self) -> Result<Mocksmith> {
+        Ok(self.configured(Mocksmith::create(ClangWrap::new(self.log())?)?))
+    }
+
+    /// Builds a configured Mocksmith instance, waiting for any other thread holding an
+    /// instance to release its instance, like `Mocksmith::new_when_available()`.
+    pub fn build_when_available(&self) -> Result<Mocksmith> {
+        let mut clangwrap = ClangWrap::blocking_new(self.log());
+        while let Err(MocksmithError::Poisoned) = clangwrap {
+            ClangWrap::clear_poison();
+            clangwrap = ClangWrap::blocking_new(self.log());
+        }
+        Ok(self.configured(Mocksmith::create(clangwrap?)?))
+    }
+
+    /// Builds a configured Mocksmith instance when available, see
+    /// `build_when_available()`, and calls a function with it. Clang is released when the
+    /// function returns, so other threads can use it.
+    pub fn run<T>(&self, f: impl FnOnce(&Mocksmith) -> Result<T>) -> Result<T> {
+        f(&self.build_when_available()?)
+    }
+
+    fn log(&self) -> Option<log::Logger> {
+        self.log_write
+            .as_ref()
+            .map(|log_write| log::Logger::new(log_write(), self.verbose))
+    }
+
+    fn configured(&self, mocksmith: Mocksmith) -> Mocksmith {
+        self.configure
+            .iter()
+            .fold(mocksmith, |mocksmith, configure| configure(mocksmith))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_can_be_prepared_in_other_thread() {
+        let builder = std::thread::spawn(|| {
+            MocksmithBuilder::new()
+                .include_path("include")
+                .class_filter_fun(|class_name| class_name.starts_with('I'))
+        })
+        .join()
+        .unwrap();
+        assert_eq!(builder.configure.len(), 2);
+    }
+}
@@ -1,9 +1,13 @@
+mod system_includes;
+
 use crate::MocksmithError;
 use crate::{log, verbose};
 use capitalize::Capitalize;
 use std::{
+    cell::{Cell, RefCell},
     path::{Path, PathBuf},
     sync::{Mutex, MutexGuard, TryLockError},
+    time::{Duration, Instant},
 };
 
 // Ensure Clang is initialized in only one thread at a time. The clang::Clang struct
@@ -13,6 +17,72 @@ static CLANG_MUTEX: Mutex<()> = Mutex::new(());
 // Dummy file name used when parsing strings
 static DUMMY_FILE: &str = "mocksmith_dummy_input_file.h";
 
+// Name of the header with COM definitions, force included when parsing COM interfaces
+static COM_PREFIX_FILE: &str = "mocksmith_com_prefix.h";
+
+// Lightweight definitions of the COM macros and types needed to parse MIDL generated
+// interfaces without the Windows SDK. On Windows, the SDK headers are expected to be used.
+static COM_PREFIX_CONTENT: &str = r#"#ifndef _WIN32
+#define STDMETHODCALLTYPE
+#define STDMETHOD(method) virtual HRESULT STDMETHODCALLTYPE method
+#define STDMETHOD_(type, method) virtual type STDMETHODCALLTYPE method
+#define PURE = 0
+#define THIS_
+#define THIS void
+#define DECLSPEC_UUID(x) __declspec(uuid(x))
+#define DECLSPEC_NOVTABLE __declspec(novtable)
+#define MIDL_INTERFACE(x) struct DECLSPEC_UUID(x) DECLSPEC_NOVTABLE
+#define interface struct
+typedef long HRESULT;
+typedef unsigned long ULONG;
+typedef unsigned long DWORD;
+typedef struct _GUID {
+  unsigned long Data1;
+  unsigned short Data2;
+  unsigned short Data3;
+  unsigned char Data4[8];
+} GUID;
+typedef GUID IID;
+typedef const IID& REFIID;
+MIDL_INTERFACE("00000000-0000-0000-C000-000000000046") IUnknown {
+public:
+  virtual HRESULT STDMETHODCALLTYPE QueryInterface(REFIID riid, void** ppvObject) = 0;
+  virtual ULONG STDMETHODCALLTYPE AddRef() = 0;
+  virtual ULONG STDMETHODCALLTYPE Release() = 0;
+};
+#endif
+"#;
+
+// Lightweight definitions of Qt macros to be able to parse QObject derived classes without
+// running moc. Real Qt headers redefine most of these, but the access specifier annotation
+// is kept since Qt only defines it if not already defined. The annotation makes it
+// possible to identify signal sections.
+static QT_MACRO_DEFINITIONS: [&str; 20] = [
+    "-DQT_ANNOTATE_ACCESS_SPECIFIER(x)=__attribute__((annotate(#x)))",
+    "-DQ_OBJECT=",
+    "-DQ_GADGET=",
+    "-DQ_INTERFACES(x)=",
+    "-DQ_PROPERTY(...)=",
+    "-DQ_ENUM(x)=",
+    "-DQ_INVOKABLE=",
+    "-DQ_SIGNALS=public QT_ANNOTATE_ACCESS_SPECIFIER(qt_signal)",
+    "-DQ_SLOTS=QT_ANNOTATE_ACCESS_SPECIFIER(qt_slot)",
+    "-Dsignals=Q_SIGNALS",
+    "-Dslots=Q_SLOTS",
+    "-DQ_SIGNAL=",
+    "-DQ_SLOT=",
+    "-DQ_EMIT=",
+    "-Demit=",
+    "-DQ_DECL_OVERRIDE=override",
+    "-DQ_DECL_FINAL=final",
+    "-DQ_DECL_NOEXCEPT=noexcept",
+    "-DQ_DECL_CONSTEXPR=constexpr",
+    "-DQ_DECL_DEPRECATED=[[deprecated]]",
+];
+
+// Function called with reported diagnostics
+pub(crate) type DiagnosticFun = Box<dyn Fn(&crate::Diagnostic)>;
+
 // Struct to wrap the Clang library and a mutex guard to ensure only one thread can use it
 // at a time, at least via this library.
 pub(crate) struct ClangWrap {
@@ -22,8 +92,28 @@ pub(crate) struct ClangWrap {
     _clang_lock: MutexGuard<'static, ()>,
     ignore_errors: bool,
     cpp_standard: Option<String>,
+    language: crate::Language,
     additional_clang_args: Vec<String>,
     parse_function_bodies: bool,
+    qt_macros: bool,
+    com_interfaces: bool,
+    use_env_includes: bool,
+    // Given explicitly, searched before the detected system include paths
+    user_system_include_paths: Vec<PathBuf>,
+    system_include_paths: Vec<PathBuf>,
+    detected_sysroot: Option<PathBuf>,
+    sysroot: Option<PathBuf>,
+    target: Option<String>,
+    force_includes: Vec<PathBuf>,
+    macro_definitions: Vec<String>,
+    undefined_macros: Vec<String>,
+    // Files given by the user that only exist in memory, with their content
+    virtual_files: Vec<(PathBuf, String)>,
+    diagnostic_fun: Option<DiagnosticFun>,
+    collect_diagnostics: bool,
+    diagnostics: RefCell<Vec<crate::Diagnostic>>,
+    // Time spent parsing the last translation unit
+    parse_duration: Cell<Duration>,
 }
 
 impl ClangWrap {
@@ -39,9 +129,9 @@ impl ClangWrap {
         Self::create(clang_lock, log)
     }
 
-    pub(crate) fn blocking_new() -> crate::Result<Self> {
+    pub(crate) fn blocking_new(log: Option<log::Logger>) -> crate::Result<Self> {
         let clang_lock = CLANG_MUTEX.lock().map_err(|_| MocksmithError::Poisoned)?;
-        Self::create(clang_lock, None)
+        Self::create(clang_lock, log)
     }
 
     fn create(
@@ -57,11 +147,68 @@ impl ClangWrap {
             clang,
             ignore_errors: false,
             cpp_standard: None,
+            language: crate::Language::Cpp,
             additional_clang_args: Vec::new(),
             parse_function_bodies: false,
+            qt_macros: false,
+            com_interfaces: false,
+            use_env_includes: false,
+            user_system_include_paths: Vec::new(),
+            system_include_paths: Vec::new(),
+            detected_sysroot: None,
+            sysroot: None,
+            target: None,
+            force_includes: Vec::new(),
+            macro_definitions: Vec::new(),
+            undefined_macros: Vec::new(),
+            virtual_files: Vec::new(),
+            diagnostic_fun: None,
+            collect_diagnostics: false,
+            diagnostics: RefCell::new(Vec::new()),
+            parse_duration: Cell::new(Duration::ZERO),
         })
     }
 
+    pub(crate) fn version(&self) -> String {
+        clang::get_version()
+    }
+
+    pub(crate) fn parse_duration(&self) -> Duration {
+        self.parse_duration.get()
+    }
+
+    pub(crate) fn log(&self) -> &Option<log::Logger> {
+        &self.log
+    }
+
+    pub(crate) fn set_diagnostic_fun(&mut self, fun: DiagnosticFun) {
+        self.diagnostic_fun = Some(fun);
+    }
+
+    // Reports a diagnostic to the diagnostic function, if any
+    pub(crate) fn report(&self, diagnostic: crate::Diagnostic) {
+        if let Some(fun) = &self.diagnostic_fun {
+            fun(&diagnostic);
+        }
+        if self.collect_diagnostics {
+            self.diagnostics.borrow_mut().push(diagnostic);
+        }
+    }
+
+    pub(crate) fn set_collect_diagnostics(&mut self, value: bool) {
+        self.collect_diagnostics = value;
+    }
+
+    pub(crate) fn take_diagnostics(&self) -> Vec<crate::Diagnostic> {
+        self.diagnostics.take()
+    }
+
+    pub(crate) fn set_color_diagnostics(&mut self, value: bool) {
+        if let Some(log) = &mut self.log {
+            log.color = value;
+        }
+    }
+
     pub(crate) fn set_ignore_errors(&mut self, value: bool) {
         self.ignore_errors = value;
     }
@@ -70,6 +217,10 @@ impl ClangWrap {
         self.cpp_standard = standard;
     }
 
+    pub(crate) fn set_language(&mut self, language: crate::Language) {
+        self.language = language;
+    }
+
     pub(crate) fn set_additional_clang_args(&mut self, args: Vec<String>) {
         self.additional_clang_args = args;
     }
@@ -78,15 +229,75 @@ impl ClangWrap {
         self.parse_function_bodies = value;
     }
 
+    pub(crate) fn set_qt_macros(&mut self, value: bool) {
+        self.qt_macros = value;
+    }
+
+    pub(crate) fn set_com_interfaces(&mut self, value: bool) {
+        self.com_interfaces = value;
+    }
+
+    pub(crate) fn set_sysroot(&mut self, sysroot: Option<PathBuf>) {
+        self.sysroot = sysroot;
+    }
+
+    pub(crate) fn set_target(&mut self, target: Option<String>) {
+        self.target = target;
+    }
+
+    pub(crate) fn set_force_includes(&mut self, files: Vec<PathBuf>) {
+        self.force_includes = files;
+    }
+
+    pub(crate) fn set_macro_definitions(&mut self, definitions: Vec<String>) {
+        self.macro_definitions = definitions;
+    }
+
+    pub(crate) fn set_undefined_macros(&mut self, names: Vec<String>) {
+        self.undefined_macros = names;
+    }
+
+    pub(crate) fn add_virtual_file(&mut self, path: PathBuf, content: String) {
+        self.virtual_files.push((path, content));
+    }
+
+    pub(crate) fn add_system_include_paths(&mut self, paths: &[PathBuf]) {
+        self.user_system_include_paths.extend(paths.iter().cloned());
+    }
+
+    pub(crate) fn set_use_env_includes(&mut self, value: bool) {
+        self.use_env_includes = value;
+    }
+
+    pub(crate) fn set_detect_system_includes(&mut self, value: bool) {
+        self.system_include_paths.clear();
+        self.detected_sysroot = None;
+        if value {
+            self.system_include_paths = system_includes::detect();
+            verbose!(
+                &self.log,
+                "Detected system include paths: {:?}",
+                self.system_include_paths
+            );
+            self.detected_sysroot = system_includes::detect_sysroot();
+            if let Some(sysroot) = &self.detected_sysroot {
+                verbose!(&self.log, "Detected system root: {}", sysroot.display());
+            }
+        }
+    }
+
     pub(crate) fn with_tu_from_file<T>(
         &self,
         include_paths: &[PathBuf],
         file: &Path,
         f: impl FnOnce(&clang::TranslationUnit) -> crate::Result<T>,
     ) -> crate::Result<T> {
+        self.check()?;
         let index = clang::Index::new(&self.clang, true, false);
+        let start = Instant::now();
         let tu = index
             .parser(file)
+            .unsaved(&self.unsaved_files())
             .arguments(&self.clang_arguments(include_paths))
             .skip_function_bodies(!self.parse_function_bodies)
             .parse()
@@ -96,48 +307,113 @@ impl ClangWrap {
                 line: 0,
                 column: 0,
             })?;
+        self.parse_duration.set(start.elapsed());
         self.check_diagnostics(&tu)?;
         f(&tu)
     }
 
+    // Parses a string as if it was the content of the given file, or of a dummy file if no
+    // file is given
     pub(crate) fn with_tu_from_string<T>(
         &self,
         include_paths: &[PathBuf],
         content: &str,
+        file: Option<&Path>,
         f: impl FnOnce(&clang::TranslationUnit) -> crate::Result<T>,
     ) -> crate::Result<T> {
+        self.check()?;
         let index = clang::Index::new(&self.clang, true, false);
-        // Use `Unsaved` with dummy file name to be able to parse from a string
-        let unsaved = clang::Unsaved::new(Path::new(DUMMY_FILE), content);
+        // Use `Unsaved` with the file name to be able to parse from a string
+        let file_name = file.unwrap_or(Path::new(DUMMY_FILE));
+        let mut unsaved = self.unsaved_files();
+        unsaved.push(clang::Unsaved::new(file_name, content));
+        let start = Instant::now();
         let tu = index
-            .parser(DUMMY_FILE)
-            .unsaved(&[unsaved])
+            .parser(file_name)
+            .unsaved(&unsaved)
             .arguments(&self.clang_arguments(include_paths))
             .skip_function_bodies(!self.parse_function_bodies)
             .parse()
             .map_err(|e| MocksmithError::ParseError {
                 message: e.to_string(),
-                file: None,
+                file: file.map(Path::to_path_buf),
                 line: 0,
                 column: 0,
             })?;
+        self.parse_duration.set(start.elapsed());
         self.check_diagnostics(&tu)?;
         f(&tu)
     }
 
+    // Checks that the options to forward to Clang are valid
+    fn check(&self) -> crate::Result<()> {
+        if let Some(sysroot) = &self.sysroot
+            && !sysroot.is_dir()
+        {
+            return Err(MocksmithError::InvalidParserOption(format!(
+                "System root is not a directory: {}",
+                sysroot.display()
+            )));
+        }
+        if let Some(target) = &self.target
+            && !is_target_triple(target)
+        {
+            return Err(MocksmithError::InvalidParserOption(format!(
+                "Invalid target triple: {target}"
+            )));
+        }
+        // Function-like macros are defined as NAME(PARAMS)=VALUE
+        if let Some(definition) = self.macro_definitions.iter().find(|definition| {
+            let name = definition.split(['=', '(']).next().unwrap_or_default();
+            !crate::generate::is_identifier(name)
+        }) {
+            return Err(MocksmithError::InvalidParserOption(format!(
+                "Invalid macro definition: {definition}"
+            )));
+        }
+        if let Some(name) = self
+            .undefined_macros
+            .iter()
+            .find(|name| !crate::generate::is_identifier(name))
+        {
+            return Err(MocksmithError::InvalidParserOption(format!(
+                "Invalid macro name: {name}"
+            )));
+        }
+        Ok(())
+    }
+
+    // Formats a diagnostic like compilers do, with colors if enabled for the log
+    fn format_diagnostic(&self, diagnostic: &crate::Diagnostic) -> String {
+        let location = diagnostic
+            .file
+            .as_ref()
+            .map(|file| log::location(file, diagnostic.line.unwrap_or(0), diagnostic.column));
+        log::format_diagnostic(
+            diagnostic.severity,
+            location.as_deref(),
+            &diagnostic.message,
+            self.log.as_ref().is_some_and(|log| log.color),
+        )
+    }
+
     fn check_diagnostics(&self, tu: &clang::TranslationUnit) -> crate::Result<()> {
         let diagnostics = tu.get_diagnostics();
-        if self.ignore_errors {
-            diagnostics
-                .iter()
-                .filter(|diagnostic| {
-                    diagnostic.get_severity() >= clang::diagnostic::Severity::Error
-                })
-                .for_each(|diagnostic| log!(&self.log, "{}", diagnostic));
-        } else {
-            diagnostics
-                .iter()
-                .for_each(|diagnostic| verbose!(&self.log, "{}", diagnostic));
+        for diagnostic in diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.get_severity() != clang::diagnostic::Severity::Ignored)
+        {
+            let diagnostic = to_diagnostic(diagnostic);
+            if self.ignore_errors {
+                if diagnostic.severity == crate::Severity::Error
+                    || diagnostic.severity == crate::Severity::Fatal
+                {
+                    log!(&self.log, "{}", self.format_diagnostic(&diagnostic));
+                }
+            } else {
+                verbose!(&self.log, "{}", self.format_diagnostic(&diagnostic));
+            }
+            self.report(diagnostic);
         }
 
         if !self.ignore_errors {
@@ -166,10 +442,32 @@ impl ClangWrap {
         Ok(())
     }
 
-    fn clang_arguments(&self, include_paths: &[PathBuf]) -> Vec<String> {
+    // Files that only exist in memory, which are added by Mocksmith itself or given by the
+    // user
+    fn unsaved_files(&self) -> Vec<clang::Unsaved> {
+        let mut unsaved = Vec::new();
+        if self.com_interfaces {
+            unsaved.push(clang::Unsaved::new(
+                Path::new(COM_PREFIX_FILE),
+                COM_PREFIX_CONTENT,
+            ));
+        }
+        for (path, content) in &self.virtual_files {
+            unsaved.push(clang::Unsaved::new(path, content));
+            // Clang only finds unsaved files spelled like the paths it looks up, which are
+            // prefixed with the directory, e.g., `./` for the default include path
+            if path.is_relative() && !path.starts_with(".") {
+                unsaved.push(clang::Unsaved::new(Path::new(".").join(path), content));
+            }
+        }
+        unsaved
+    }
+
+    pub(crate) fn clang_arguments(&self, include_paths: &[PathBuf]) -> Vec<String> {
         let mut arguments = vec![
-            // Mocksmith is for generating mocks for C++
-            "--language=c++".to_string(),
+            // Mocksmith is for generating mocks for C++, but headers may also contain
+            // Objective-C when parsed as Objective-C++
+            format!("--language={}", self.language.clang_name()),
             // Default to C++17 standard which should be sufficient for most use cases and
             // fully supported from Clang 5
             format!(
@@ -189,7 +487,144 @@ impl ClangWrap {
                     .map(|path| format!("-I{}", path.display())),
             );
         }
+        if self.use_env_includes {
+            arguments.extend(env_include_arguments());
+        }
+        if let Some(target) = &self.target {
+            arguments.push(format!("--target={target}"));
+        }
+        // The detected system include paths and system root are of the host, so they are
+        // not used when parsing for another system root or target
+        let use_detected = self.sysroot.is_none() && self.target.is_none();
+        if let Some(sysroot) = &self.sysroot {
+            arguments.push(format!("--sysroot={}", sysroot.display()));
+        } else if let Some(sysroot) = &self.detected_sysroot
+            && use_detected
+            && !self
+                .additional_clang_args
+                .iter()
+                .any(|arg| arg.starts_with("-isysroot") || arg.starts_with("--sysroot"))
+        {
+            arguments.push("-isysroot".to_string());
+            arguments.push(sysroot.display().to_string());
+        }
+        arguments.extend(
+            self.user_system_include_paths
+                .iter()
+                .chain(self.system_include_paths.iter().filter(|_| use_detected))
+                .map(|path| format!("-isystem{}", path.display())),
+        );
+        if self.qt_macros {
+            arguments.extend(QT_MACRO_DEFINITIONS.iter().map(|d| d.to_string()));
+            // Real Qt headers redefine the macros, which is expected
+            arguments.push("-Wno-macro-redefined".to_string());
+        }
+        if self.com_interfaces {
+            // Needed for __declspec(uuid(...)) used by MIDL_INTERFACE
+            arguments.push("-fms-extensions".to_string());
+            arguments.push("-include".to_string());
+            arguments.push(COM_PREFIX_FILE.to_string());
+        }
+        for file in &self.force_includes {
+            arguments.push("-include".to_string());
+            arguments.push(file.display().to_string());
+        }
+        arguments.extend(
+            self.macro_definitions
+                .iter()
+                .map(|definition| format!("-D{definition}")),
+        );
+        arguments.extend(self.undefined_macros.iter().map(|name| format!("-U{name}")));
         arguments.extend(self.additional_clang_args.iter().cloned());
         arguments
     }
 }
+
+fn to_diagnostic(diagnostic: &clang::diagnostic::Diagnostic) -> crate::Diagnostic {
+    let severity = match diagnostic.get_severity() {
+        clang::diagnostic::Severity::Ignored | clang::diagnostic::Severity::Note => {
+            crate::Severity::Note
+        }
+        clang::diagnostic::Severity::Warning => crate::Severity::Warning,
+        clang::diagnostic::Severity::Error => crate::Severity::Error,
+        clang::diagnostic::Severity::Fatal => crate::Severity::Fatal,
+    };
+    let location = diagnostic.get_location().get_file_location();
+    let file = location
+        .file
+        .map(|file| file.get_path())
+        // Dummy file means parsing from string, don't report the dummy name
+        .filter(|path| path != Path::new(DUMMY_FILE));
+    let (line, column) = if file.is_some() {
+        (Some(location.line), Some(location.column))
+    } else {
+        (None, None)
+    };
+    crate::Diagnostic {
+        severity,
+        message: diagnostic.get_text(),
+        file,
+        line,
+        column,
+        source: crate::DiagnosticSource::Clang,
+    }
+}
+
+// Whether a string looks like a target triple, i.e., two to four components like
+// arch-vendor-os-environment separated by dashes
+fn is_target_triple(target: &str) -> bool {
+    let components = target.split('-').collect::<Vec<_>>();
+    (2..=4).contains(&components.len())
+        && components.iter().all(|component| {
+            !component.is_empty()
+                && component
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        })
+}
+
+// Include path arguments for the paths in the environment variables used by compilers.
+// Like for GCC and Clang, paths in CPATH are searched as if given with -I and paths in
+// CPLUS_INCLUDE_PATH as system include paths.
+fn env_include_arguments() -> Vec<String> {
+    let paths = |variable: &str| {
+        std::env::var_os(variable)
+            .map(|value| {
+                std::env::split_paths(&value)
+                    .filter(|path| !path.as_os_str().is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+    paths("CPATH")
+        .iter()
+        .map(|path| format!("-I{}", path.display()))
+        .chain(
+            paths("CPLUS_INCLUDE_PATH")
+                .iter()
+                .map(|path| format!("-isystem{}", path.display())),
+        )
+        .collect()
+}
+
+// Files visited by Clang when parsing a file, i.e., the file itself and all files it
+// includes, directly or indirectly. Files only existing in memory are left out.
+pub(crate) fn visited_files(tu: &clang::TranslationUnit, file: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = tu.get_file(file).into_iter().collect::<Vec<_>>();
+    while let Some(file) = pending.pop() {
+        let path = file.get_path();
+        if files.contains(&path) {
+            continue;
+        }
+        files.push(path);
+        pending.extend(
+            file.get_includes()
+                .iter()
+                .rev()
+                .filter_map(|include| include.get_file()),
+        );
+    }
+    files.retain(|path| path.is_file());
+    files
+}
@@ -0,0 +1,96 @@
+// Detection of the system include paths of an installed compiler, for libclang to find the
+// C++ standard library when it does not find it by itself
+use std::path::{Path, PathBuf};
+
+// Lines surrounding the include paths in the verbose output of Clang and GCC
+static SEARCH_LIST_START: &str = "#include <...> search starts here:";
+static SEARCH_LIST_END: &str = "End of search list.";
+
+// Detects system include paths, from the environment of a Visual Studio developer prompt
+// on Windows and otherwise by asking clang++ for its default include paths
+pub(crate) fn detect() -> Vec<PathBuf> {
+    #[cfg(windows)]
+    if let Some(include) = std::env::var_os("INCLUDE") {
+        return std::env::split_paths(&include)
+            .filter(|path| !path.as_os_str().is_empty())
+            .collect();
+    }
+    std::process::Command::new("clang++")
+        .args(["-E", "-x", "c++", "-", "-v"])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map(|output| parse_search_list(&String::from_utf8_lossy(&output.stderr)))
+        .unwrap_or_default()
+}
+
+// Detects the SDK to use as system root on macOS, where the standard headers are in the
+// SDK rather than in /usr/include
+pub(crate) fn detect_sysroot() -> Option<PathBuf> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    std::process::Command::new("xcrun")
+        .arg("--show-sdk-path")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+// Parses the include paths from the verbose output of a compiler. The compiler's own
+// builtin headers are left out since libclang has builtin headers matching its version.
+fn parse_search_list(output: &str) -> Vec<PathBuf> {
+    output
+        .lines()
+        .skip_while(|line| line.trim() != SEARCH_LIST_START)
+        .skip(1)
+        .take_while(|line| line.trim() != SEARCH_LIST_END)
+        .map(|line| line.trim())
+        // Frameworks on macOS are not include directories
+        .filter(|line| !line.ends_with("(framework directory)"))
+        .map(PathBuf::from)
+        .filter(|path| !is_builtin_headers_dir(path))
+        .collect()
+}
+
+// Whether a directory is the builtin headers directory of Clang, e.g.,
+// /usr/lib/clang/18/include
+fn is_builtin_headers_dir(path: &Path) -> bool {
+    path.parent()
+        .and_then(Path::parent)
+        .and_then(Path::file_name)
+        .is_some_and(|name| name == "clang")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn include_paths_are_parsed_from_verbose_compiler_output() {
+        let output = "clang version 18.1.3\n\
+                      ignoring nonexistent directory \"/include\"\n\
+                      #include \"...\" search starts here:\n\
+                      #include <...> search starts here:\n\
+                      \x20/usr/include/c++/13\n\
+                      \x20/usr/lib/llvm-18/lib/clang/18/include\n\
+                      \x20/usr/include\n\
+                      \x20/System/Library/Frameworks (framework directory)\n\
+                      End of search list.\n\
+                      # 1 \"<stdin>\"\n";
+        assert_eq!(
+            parse_search_list(output),
+            vec![
+                PathBuf::from("/usr/include/c++/13"),
+                PathBuf::from("/usr/include")
+            ]
+        );
+    }
+
+    #[test]
+    fn no_include_paths_are_parsed_from_unexpected_output() {
+        assert!(parse_search_list("clang++: command not found\n").is_empty());
+    }
+}
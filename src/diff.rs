@@ -0,0 +1,222 @@
+// Unified diffs between existing output files and generated content, to show what
+// regenerating mocks would change without writing anything
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Number of unchanged lines shown around changes
+const CONTEXT: usize = 3;
+
+// Whether any changes have been printed, to fail when checking that files are up to date
+static CHANGES_PRINTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Edit {
+    Keep(usize, usize),
+    Remove(usize),
+    Add(usize),
+}
+
+// Prints a unified diff between the lines of two texts, if they are not equal
+pub(crate) fn print_diff(old: &str, new: &str, old_name: &str, new_name: &str) {
+    let diff = unified_diff(old, new, old_name, new_name);
+    if !diff.is_empty() {
+        CHANGES_PRINTED.store(true, Ordering::Relaxed);
+        print!("{diff}");
+    }
+}
+
+// Whether any diff has been printed
+pub(crate) fn changes_printed() -> bool {
+    CHANGES_PRINTED.load(Ordering::Relaxed)
+}
+
+// Unified diff between the lines of two texts, empty if they are equal
+pub(crate) fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+    let edits = edits(&old_lines, &new_lines);
+    let changes = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, edit)| !matches!(edit, Edit::Keep(..)))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Group changes close to each other into hunks of edits, with context
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for change in changes {
+        let start = change.saturating_sub(CONTEXT);
+        let end = (change + CONTEXT + 1).min(edits.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut diff = format!("--- {old_name}\n+++ {new_name}\n");
+    for (start, end) in hunks {
+        let hunk = &edits[start..end];
+        // Lines before the hunk, from the first edit or the edits before it
+        let (old_before, new_before) =
+            edits[..start]
+                .iter()
+                .fold((0, 0), |(o, n), edit| match edit {
+                    Edit::Keep(..) => (o + 1, n + 1),
+                    Edit::Remove(_) => (o + 1, n),
+                    Edit::Add(_) => (o, n + 1),
+                });
+        let old_count = hunk
+            .iter()
+            .filter(|edit| !matches!(edit, Edit::Add(_)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|edit| !matches!(edit, Edit::Remove(_)))
+            .count();
+        // Empty ranges start at the line before, like diff -u does
+        let range_start =
+            |before: usize, count: usize| if count == 0 { before } else { before + 1 };
+        diff.push_str(&format!(
+            "@@ -{},{old_count} +{},{new_count} @@\n",
+            range_start(old_before, old_count),
+            range_start(new_before, new_count)
+        ));
+        for edit in hunk {
+            match *edit {
+                Edit::Keep(i, _) => diff.push_str(&format!(" {}\n", old_lines[i])),
+                Edit::Remove(i) => diff.push_str(&format!("-{}\n", old_lines[i])),
+                Edit::Add(j) => diff.push_str(&format!("+{}\n", new_lines[j])),
+            }
+        }
+    }
+    diff
+}
+
+// Edits turning the old lines into the new lines. Common leading and trailing lines are
+// kept as is, since regenerated files mostly have small changes, and the longest common
+// subsequence is only computed for the lines between them.
+fn edits(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let mut edits = (0..prefix).map(|i| Edit::Keep(i, i)).collect::<Vec<_>>();
+    edits.extend(
+        lcs_edits(
+            &old[prefix..old.len() - suffix],
+            &new[prefix..new.len() - suffix],
+        )
+        .into_iter()
+        .map(|edit| match edit {
+            Edit::Keep(i, j) => Edit::Keep(i + prefix, j + prefix),
+            Edit::Remove(i) => Edit::Remove(i + prefix),
+            Edit::Add(j) => Edit::Add(j + prefix),
+        }),
+    );
+    edits.extend((0..suffix).map(|k| Edit::Keep(old.len() - suffix + k, new.len() - suffix + k)));
+    edits
+}
+
+// Edits turning the old lines into the new lines, from the longest common subsequence
+fn lcs_edits(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    // Length of the longest common subsequence of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            edits.push(Edit::Keep(i, j));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            edits.push(Edit::Remove(i));
+            i += 1;
+        } else {
+            edits.push(Edit::Add(j));
+            j += 1;
+        }
+    }
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_texts_have_no_diff() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "old", "new"), "");
+    }
+
+    #[test]
+    fn changed_lines_are_shown_with_context() {
+        let old = (1..=20).map(|i| format!("{i}\n")).collect::<String>();
+        let new = old.replacen("3\n", "three\n", 1) + "21\n";
+        assert_eq!(
+            unified_diff(&old, &new, "a/mock.h", "b/mock.h"),
+            "--- a/mock.h\n\
+             +++ b/mock.h\n\
+             @@ -1,6 +1,6 @@\n \
+             1\n \
+             2\n\
+             -3\n\
+             +three\n \
+             4\n \
+             5\n \
+             6\n\
+             @@ -18,3 +18,4 @@\n \
+             18\n \
+             19\n \
+             20\n\
+             +21\n"
+        );
+    }
+
+    #[test]
+    fn large_files_with_few_changes_are_diffed() {
+        let old = (1..=100_000).map(|i| format!("{i}\n")).collect::<String>();
+        let new = old.replacen("\n50000\n", "\nfifty thousand\n", 1);
+        assert_eq!(
+            unified_diff(&old, &new, "a/mock.h", "b/mock.h"),
+            "--- a/mock.h\n\
+             +++ b/mock.h\n\
+             @@ -49997,7 +49997,7 @@\n \
+             49997\n \
+             49998\n \
+             49999\n\
+             -50000\n\
+             +fifty thousand\n \
+             50001\n \
+             50002\n \
+             50003\n"
+        );
+    }
+
+    #[test]
+    fn new_file_is_shown_as_added_lines() {
+        assert_eq!(
+            unified_diff("", "a\nb\n", "/dev/null", "b/mock.h"),
+            "--- /dev/null\n+++ b/mock.h\n@@ -0,0 +1,2 @@\n+a\n+b\n"
+        );
+    }
+}
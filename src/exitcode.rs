@@ -0,0 +1,90 @@
+// Exit codes telling failures apart, so that scripts can react differently to, e.g., a
+// source header that can't be parsed and mocks that are out of date. Documented in the
+// README.
+
+use mocksmith::MocksmithError;
+
+pub(crate) const SUCCESS: u8 = 0;
+// Any failure not covered by another exit code
+pub(crate) const FAILURE: u8 = 1;
+// Same exit code as for arguments rejected when parsing the command line
+pub(crate) const ARGUMENT_ERROR: u8 = 2;
+pub(crate) const PARSE_ERROR: u8 = 3;
+pub(crate) const NOTHING_TO_MOCK: u8 = 4;
+pub(crate) const STALE: u8 = 5;
+pub(crate) const IO_ERROR: u8 = 6;
+
+// Failures with their own exit codes that are not reported as library errors
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum Failure {
+    #[error("{0}")]
+    NothingToMock(String),
+    #[error("{0}")]
+    Stale(String),
+    // Failure with the exit code of an earlier error, e.g., after keeping going
+    #[error("{message}")]
+    WithExitCode { message: String, exit_code: u8 },
+}
+
+// Exit code for the first cause of an error with a specific exit code
+pub(crate) fn exit_code(error: &anyhow::Error) -> u8 {
+    error
+        .chain()
+        .find_map(|cause| {
+            if let Some(failure) = cause.downcast_ref::<Failure>() {
+                Some(match failure {
+                    Failure::NothingToMock(_) => NOTHING_TO_MOCK,
+                    Failure::Stale(_) => STALE,
+                    Failure::WithExitCode { exit_code, .. } => *exit_code,
+                })
+            } else if let Some(error) = cause.downcast_ref::<MocksmithError>() {
+                mocksmith_exit_code(error)
+            } else if cause.is::<std::io::Error>() {
+                Some(IO_ERROR)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(FAILURE)
+}
+
+fn mocksmith_exit_code(error: &MocksmithError) -> Option<u8> {
+    match error {
+        MocksmithError::InvalidSedReplacement(_)
+        | MocksmithError::InvalidRegex(_)
+        | MocksmithError::IncompatibleOptions(_)
+        | MocksmithError::InvalidIdentifier(_)
+        | MocksmithError::InvalidParserOption(_) => Some(ARGUMENT_ERROR),
+        MocksmithError::ParseError { .. } => Some(PARSE_ERROR),
+        MocksmithError::NothingToMock => Some(NOTHING_TO_MOCK),
+        MocksmithError::InputFileError(_) => Some(IO_ERROR),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn exit_code_is_taken_from_first_cause_with_exit_code() {
+        let parse_error = MocksmithError::ParseError {
+            message: "unknown type name 'Unknown'".to_string(),
+            file: None,
+            line: 1,
+            column: 1,
+        };
+        let error = Err::<(), _>(parse_error).context("Could not create mocks");
+        assert_eq!(exit_code(&error.unwrap_err()), PARSE_ERROR);
+
+        let io_error = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let error = Err::<(), _>(io_error).context("Failed to write mock file");
+        assert_eq!(exit_code(&error.unwrap_err()), IO_ERROR);
+
+        let error = anyhow::Error::new(Failure::Stale("Mocks are out of date".to_string()));
+        assert_eq!(exit_code(&error), STALE);
+
+        assert_eq!(exit_code(&anyhow::anyhow!("Something failed")), FAILURE);
+    }
+}
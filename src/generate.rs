@@ -1,125 +1,1872 @@
+mod banner;
 mod builder;
+mod template;
 
+use crate::FactoryPointer;
+use crate::HelperAllocation;
+use crate::LineEnding;
 use crate::MethodsToMockStrategy;
+use crate::MockStyle;
 use crate::model;
+use sha2::{Digest, Sha256};
+
+pub(crate) use banner::has_default_banner;
+
+// Warnings commonly triggered by mocks in strict builds. Unknown warnings are ignored
+// first, since older Clang versions lack some of the warnings.
+const CLANG_SUPPRESSED_WARNINGS: [&str; 4] = [
+    "-Wunknown-warning-option",
+    "-Wsuggest-override",
+    "-Wweak-vtables",
+    "-Winconsistent-missing-destructor-override",
+];
+const GCC_SUPPRESSED_WARNINGS: [&str; 1] = ["-Wsuggest-override"];
+
+// Code of a mock header, with the ranges of lines, counted from 0, of the include
+// directives and of the code of each mock, unless laid out by a template
+pub(crate) struct HeaderCode {
+    pub(crate) code: String,
+    pub(crate) include_lines: Option<std::ops::Range<usize>>,
+    pub(crate) mock_lines: Vec<Option<std::ops::Range<usize>>>,
+}
 
 // Generator for gmock mocks. Generates mock code for a single class or complete headers
 // for a set of classes.
 pub(crate) struct Generator {
     methods_to_mock: MethodsToMockStrategy,
+    style: MockStyle,
     add_deprecation_pragma: bool,
+    add_gcc_deprecation_pragma: bool,
+    add_warning_suppression_pragma: bool,
+    nolint_checks: Option<Vec<String>>,
+    source_hash_comment: bool,
+    guard_macro: Option<String>,
+    module_name: Option<String>,
+    import_gmock: bool,
     simplified_nested_namespaces: bool,
     indent_str: String,
+    com_interfaces: bool,
+    keep_method_attributes: bool,
+    const_overload_helpers: bool,
+    move_only_helpers: bool,
+    reference_default_actions: bool,
+    value_default_actions: bool,
+    line_directives: bool,
+    signature_checks: bool,
+    mock_macro: String,
+    mock_destructor: bool,
+    comma_type_aliases: bool,
+    keep_regions: bool,
+    mock_aliases: bool,
+    include_guard_pattern: Option<String>,
+    include_guard_project: Option<String>,
+    split_source_file: bool,
+    template: Option<String>,
+    banner: Option<String>,
+    license: Option<String>,
+    line_ending: LineEnding,
+    final_newline: bool,
+    byte_order_mark: bool,
+    include_with_angle_brackets: bool,
+    column_limit: Option<usize>,
+    mock_namespace: Option<String>,
+    preserve_access: bool,
+    // Tests to befriend with FRIEND_TEST, as TestSuite.TestName
+    friend_tests: Vec<String>,
+    emit_fixture: bool,
+    emit_cheat_sheet: bool,
+    emit_documentation: bool,
+    factory_helpers: Option<FactoryPointer>,
+    extract_interface: bool,
+    assert_not_abstract: bool,
+    forward_declare_types: bool,
+    nice_strict_helpers: Option<HelperAllocation>,
 }
 
+// Mocks for the IUnknown methods, needed for COM interfaces since IUnknown is declared
+// outside the mocked header
+// Directive following each mocked declaration mapped to its declaration with a #line
+// directive, rewritten by restore_line_directives() to continue the numbering of the
+// generated code
+const RESTORE_LINE_DIRECTIVE: &str = "#line 1 \"\"";
+
+const IUNKNOWN_MOCK_METHODS: [&str; 3] = [
+    "MOCK_METHOD(HRESULT, QueryInterface, (REFIID riid, void ** ppvObject), \
+     (Calltype(STDMETHODCALLTYPE), override));",
+    "MOCK_METHOD(ULONG, AddRef, (), (Calltype(STDMETHODCALLTYPE), override));",
+    "MOCK_METHOD(ULONG, Release, (), (Calltype(STDMETHODCALLTYPE), override));",
+];
+
+// Fake implementations of the IUnknown methods, for the fake style
+const IUNKNOWN_FAKE_METHODS: [&str; 3] = [
+    "HRESULT STDMETHODCALLTYPE QueryInterface(REFIID, void **) override { return E_NOINTERFACE; }",
+    "ULONG STDMETHODCALLTYPE AddRef() override { return 1; }",
+    "ULONG STDMETHODCALLTYPE Release() override { return 1; }",
+];
+
 impl crate::Mock {
-    fn from(parent: &model::ClassToMock, name: &str, builder: builder::CodeBuilder) -> Self {
+    fn from(
+        parent: &model::ClassToMock,
+        name: &str,
+        code: String,
+        definitions: Option<builder::CodeBuilder>,
+        fixture: Option<builder::CodeBuilder>,
+        signature_checks: Option<builder::CodeBuilder>,
+        forward_declare_types: bool,
+    ) -> Self {
+        let (type_headers, forward_declarations) =
+            type_headers_and_forward_declarations(parent, forward_declare_types);
         Self {
             source_file: None,
             parent_name: parent.name.clone(),
             name: name.to_string(),
+            code,
+            has_deprecated_methods: parent.methods.iter().any(|method| method.is_deprecated),
+            definitions: definitions.map(|definitions| definitions.build()),
+            fixture: fixture.map(|fixture| fixture.build()),
+            signature_checks: signature_checks.map(|checks| checks.build()),
+            cheat_sheet: None,
+            documentation: None,
+            type_headers,
+            forward_declarations,
+            source_hash: sha256_hex(&parent.declaration_text()),
+            dependencies: Vec::new(),
+            template_class: None,
+        }
+    }
+}
+
+impl Generator {
+    pub(crate) fn new(methods_to_mock: MethodsToMockStrategy) -> Self {
+        Self {
+            methods_to_mock,
+            style: MockStyle::Gmock,
+            add_deprecation_pragma: false,
+            add_gcc_deprecation_pragma: false,
+            add_warning_suppression_pragma: false,
+            nolint_checks: None,
+            source_hash_comment: false,
+            guard_macro: None,
+            module_name: None,
+            import_gmock: false,
+            simplified_nested_namespaces: true,
+            indent_str: "  ".to_string(),
+            com_interfaces: false,
+            keep_method_attributes: false,
+            const_overload_helpers: false,
+            move_only_helpers: false,
+            reference_default_actions: false,
+            value_default_actions: false,
+            line_directives: false,
+            signature_checks: false,
+            mock_macro: "MOCK_METHOD".to_string(),
+            mock_destructor: false,
+            comma_type_aliases: false,
+            keep_regions: false,
+            mock_aliases: false,
+            include_guard_pattern: None,
+            include_guard_project: None,
+            split_source_file: false,
+            template: None,
+            banner: None,
+            license: None,
+            line_ending: LineEnding::Lf,
+            final_newline: false,
+            byte_order_mark: false,
+            include_with_angle_brackets: false,
+            column_limit: None,
+            mock_namespace: None,
+            preserve_access: false,
+            friend_tests: Vec::new(),
+            emit_fixture: false,
+            emit_cheat_sheet: false,
+            emit_documentation: false,
+            factory_helpers: None,
+            extract_interface: false,
+            assert_not_abstract: false,
+            forward_declare_types: false,
+            nice_strict_helpers: None,
+        }
+    }
+
+    pub(crate) fn methods_to_mock(&mut self, methods: MethodsToMockStrategy) {
+        self.methods_to_mock = methods;
+    }
+
+    pub(crate) fn style(&mut self, style: MockStyle) {
+        self.style = style;
+    }
+
+    pub(crate) fn add_deprecation_pragma(&mut self, value: bool) {
+        self.add_deprecation_pragma = value;
+    }
+
+    pub(crate) fn add_gcc_deprecation_pragma(&mut self, value: bool) {
+        self.add_gcc_deprecation_pragma = value;
+    }
+
+    pub(crate) fn add_warning_suppression_pragma(&mut self, value: bool) {
+        self.add_warning_suppression_pragma = value;
+    }
+
+    pub(crate) fn nolint_checks(&mut self, checks: Option<Vec<String>>) {
+        self.nolint_checks = checks;
+    }
+
+    pub(crate) fn source_hash_comment(&mut self, value: bool) {
+        self.source_hash_comment = value;
+    }
+
+    pub(crate) fn guard_macro(&mut self, guard_macro: Option<String>) {
+        self.guard_macro = guard_macro;
+    }
+
+    pub(crate) fn module_name(&mut self, module_name: Option<String>) {
+        self.module_name = module_name;
+    }
+
+    pub(crate) fn import_gmock(&mut self, value: bool) {
+        self.import_gmock = value;
+    }
+
+    pub(crate) fn simplified_nested_namespaces(&mut self, value: bool) {
+        self.simplified_nested_namespaces = value;
+    }
+
+    pub(crate) fn indent_str(&mut self, indent_str: String) {
+        self.indent_str = indent_str;
+    }
+
+    pub(crate) fn com_interfaces(&mut self, value: bool) {
+        self.com_interfaces = value;
+    }
+
+    pub(crate) fn keep_method_attributes(&mut self, value: bool) {
+        self.keep_method_attributes = value;
+    }
+
+    pub(crate) fn const_overload_helpers(&mut self, value: bool) {
+        self.const_overload_helpers = value;
+    }
+
+    pub(crate) fn move_only_helpers(&mut self, value: bool) {
+        self.move_only_helpers = value;
+    }
+
+    pub(crate) fn reference_default_actions(&mut self, value: bool) {
+        self.reference_default_actions = value;
+    }
+
+    pub(crate) fn value_default_actions(&mut self, value: bool) {
+        self.value_default_actions = value;
+    }
+
+    pub(crate) fn line_directives(&mut self, value: bool) {
+        self.line_directives = value;
+    }
+
+    pub(crate) fn signature_checks(&mut self, value: bool) {
+        self.signature_checks = value;
+    }
+
+    pub(crate) fn mock_macro(&mut self, mock_macro: String) {
+        self.mock_macro = mock_macro;
+    }
+
+    pub(crate) fn mock_destructor(&mut self, value: bool) {
+        self.mock_destructor = value;
+    }
+
+    pub(crate) fn comma_type_aliases(&mut self, value: bool) {
+        self.comma_type_aliases = value;
+    }
+
+    pub(crate) fn keep_regions(&mut self, value: bool) {
+        self.keep_regions = value;
+    }
+
+    pub(crate) fn mock_aliases(&mut self, value: bool) {
+        self.mock_aliases = value;
+    }
+
+    pub(crate) fn include_guard_pattern(&mut self, pattern: Option<String>) {
+        self.include_guard_pattern = pattern;
+    }
+
+    pub(crate) fn include_guard_project(&mut self, project: Option<String>) {
+        self.include_guard_project = project;
+    }
+
+    pub(crate) fn split_source_file(&mut self, value: bool) {
+        self.split_source_file = value;
+    }
+
+    pub(crate) fn template(&mut self, template: Option<String>) {
+        self.template = template;
+    }
+
+    // Checks that the template can be parsed and that the options can be combined
+    pub(crate) fn check(&self) -> crate::Result<()> {
+        if let Some(template) = &self.template {
+            template::check(template)?;
+        }
+        if !is_identifier(&self.mock_macro) {
+            return Err(crate::MocksmithError::InvalidIdentifier(
+                self.mock_macro.clone(),
+            ));
+        }
+        if let Some(test) = self
+            .friend_tests
+            .iter()
+            .find(|test| friend_test_parts(test).is_none())
+        {
+            return Err(crate::MocksmithError::InvalidIdentifier(test.clone()));
+        }
+        if self.module_name.is_some() {
+            // Preprocessor conditionals can't enclose the module declaration and
+            // static_assert declarations can't be exported
+            if self.guard_macro.is_some() {
+                return Err(crate::MocksmithError::IncompatibleOptions(
+                    "Modules can't be guarded by a macro".to_string(),
+                ));
+            }
+            if self.assert_not_abstract {
+                return Err(crate::MocksmithError::IncompatibleOptions(
+                    "Modules can't assert that mocks are not abstract".to_string(),
+                ));
+            }
+            // Forward declarations would have to be in the module purview, attaching them
+            // to the module, since only preprocessor directives are allowed before it
+            if self.forward_declare_types {
+                return Err(crate::MocksmithError::IncompatibleOptions(
+                    "Modules can't forward declare types".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn banner(&mut self, banner: Option<String>) {
+        self.banner = banner;
+    }
+
+    pub(crate) fn license(&mut self, license: Option<String>) {
+        self.license = license;
+    }
+
+    pub(crate) fn line_ending(&mut self, line_ending: LineEnding) {
+        self.line_ending = line_ending;
+    }
+
+    pub(crate) fn final_newline(&mut self, value: bool) {
+        self.final_newline = value;
+    }
+
+    pub(crate) fn byte_order_mark(&mut self, value: bool) {
+        self.byte_order_mark = value;
+    }
+
+    // Applies the configured final newline, line endings and byte order mark to the
+    // content of a generated file
+    pub(crate) fn file_content(&self, mut code: String) -> String {
+        if self.final_newline && !code.is_empty() && !code.ends_with('\n') {
+            code.push('\n');
+        }
+        if self.line_ending == LineEnding::Crlf {
+            code = code.replace("\r\n", "\n").replace('\n', "\r\n");
+        }
+        if self.byte_order_mark {
+            code.insert(0, '\u{feff}');
+        }
+        code
+    }
+
+    pub(crate) fn include_with_angle_brackets(&mut self, value: bool) {
+        self.include_with_angle_brackets = value;
+    }
+
+    pub(crate) fn column_limit(&mut self, limit: Option<usize>) {
+        self.column_limit = limit;
+    }
+
+    pub(crate) fn mock_namespace(&mut self, namespace: Option<String>) {
+        self.mock_namespace = namespace;
+    }
+
+    pub(crate) fn preserve_access(&mut self, value: bool) {
+        self.preserve_access = value;
+    }
+
+    pub(crate) fn friend_tests(&mut self, tests: Vec<String>) {
+        self.friend_tests = tests;
+    }
+
+    pub(crate) fn emit_fixture(&mut self, value: bool) {
+        self.emit_fixture = value;
+    }
+
+    pub(crate) fn emit_cheat_sheet(&mut self, value: bool) {
+        self.emit_cheat_sheet = value;
+    }
+
+    pub(crate) fn emit_documentation(&mut self, value: bool) {
+        self.emit_documentation = value;
+    }
+
+    pub(crate) fn factory_helpers(&mut self, pointer: Option<FactoryPointer>) {
+        self.factory_helpers = pointer;
+    }
+
+    pub(crate) fn extract_interface(&mut self, value: bool) {
+        self.extract_interface = value;
+    }
+
+    pub(crate) fn assert_not_abstract(&mut self, value: bool) {
+        self.assert_not_abstract = value;
+    }
+
+    pub(crate) fn forward_declare_types(&mut self, value: bool) {
+        self.forward_declare_types = value;
+    }
+
+    pub(crate) fn nice_strict_helpers(&mut self, allocation: Option<HelperAllocation>) {
+        self.nice_strict_helpers = allocation;
+    }
+
+    pub(crate) fn header(
+        &self,
+        source_file_paths: &[String],
+        type_header_paths: &[String],
+        mocks: &[crate::Mock],
+    ) -> crate::Result<HeaderCode> {
+        let mut builder = builder::CodeBuilder::new(self.indent_str.clone());
+        if let Some(template) = &self.template {
+            // The license is always kept, while the template decides where to put the
+            // banner and the type headers
+            self.add_license(&mut builder, source_file_paths);
+            let code = template::render_header(
+                template,
+                source_file_paths,
+                type_header_paths,
+                &self.banner_text(source_file_paths, mocks),
+                mocks,
+            )?;
+            return Ok(HeaderCode {
+                code: builder.build() + &code,
+                include_lines: None,
+                mock_lines: vec![None; mocks.len()],
+            });
+        }
+        self.add_banner(&mut builder, source_file_paths, mocks);
+        if self.source_hash_comment {
+            let hashes = mocks
+                .iter()
+                .map(|mock| mock.source_hash.as_str())
+                .collect::<Vec<_>>();
+            builder.add_line(&format!(
+                "// mocksmith-source-sha256: {}",
+                sha256_hex(&hashes.join("\n"))
+            ));
+        }
+        let include_guard = self
+            .include_guard_pattern
+            .as_ref()
+            .filter(|_| self.module_name.is_none())
+            .map(|pattern| {
+                include_guard_name(
+                    pattern,
+                    self.include_guard_project.as_deref(),
+                    source_file_paths,
+                )
+            });
+        if self.module_name.is_some() {
+            // Includes go in the global module fragment of the module interface unit
+            builder.add_line("module;");
+        } else if let Some(guard) = &include_guard {
+            builder.add_line(&format!("#ifndef {guard}"));
+            builder.add_line(&format!("#define {guard}"));
+        } else {
+            builder.add_line("#pragma once");
+        }
+        builder.add_line("");
+        if let Some(guard_macro) = &self.guard_macro {
+            builder.add_line(&format!("#if defined({guard_macro})"));
+            builder.add_line("");
+        }
+        self.add_nolint(&mut builder, "NOLINTBEGIN");
+        let includes_start = builder.line_count();
+        for path in source_file_paths.iter().chain(type_header_paths) {
+            if self.include_with_angle_brackets {
+                builder.add_line(&format!("#include <{path}>"));
+            } else {
+                builder.add_line(&format!("#include \"{path}\""));
+            }
+        }
+        let import_gmock = self.module_name.is_some() && self.import_gmock;
+        match self.style {
+            MockStyle::Gmock if import_gmock => {}
+            MockStyle::Gmock => builder.add_line("#include <gmock/gmock.h>"),
+            MockStyle::FakeIt => builder.add_line("#include <fakeit.hpp>"),
+            MockStyle::Fake => {}
+        }
+        if (self.factory_helpers.is_some() && self.style != MockStyle::FakeIt)
+            || (self.nice_strict_helpers == Some(HelperAllocation::Heap)
+                && self.style == MockStyle::Gmock)
+        {
+            builder.add_line("#include <memory>");
+        }
+        if self.assert_not_abstract && self.style != MockStyle::FakeIt {
+            builder.add_line("#include <type_traits>");
+        }
+        if !self.friend_tests.is_empty() && self.style != MockStyle::FakeIt {
+            builder.add_line("#include <gtest/gtest_prod.h>");
+        }
+        let include_lines =
+            Some(includes_start..builder.line_count()).filter(|lines| !lines.is_empty());
+        let mut forward_declarations: Vec<&String> = Vec::new();
+        for declaration in mocks.iter().flat_map(|mock| &mock.forward_declarations) {
+            if !forward_declarations.contains(&declaration) {
+                forward_declarations.push(declaration);
+            }
+        }
+        if !forward_declarations.is_empty() {
+            builder.add_line("");
+            forward_declarations
+                .iter()
+                .for_each(|declaration| builder.add_line(declaration));
+        }
+        if let Some(module_name) = &self.module_name {
+            builder.add_line("");
+            builder.add_line(&format!("export module {module_name};"));
+            if import_gmock && self.style == MockStyle::Gmock {
+                builder.add_line("");
+                builder.add_line("import <gmock/gmock.h>;");
+            }
+        }
+
+        let add_deprecation_pragma =
+            self.add_deprecation_pragma || mocks.iter().any(|mock| mock.has_deprecated_methods);
+        if add_deprecation_pragma {
+            builder.add_line("");
+            builder.add_line("#ifdef _MSC_VER");
+            builder.add_line(&format!("#{}pragma warning(push)", self.indent_str));
+            builder.add_line(&format!(
+                "#{}pragma warning(disable : 4996)",
+                self.indent_str
+            ));
+            builder.add_line("#endif");
+        }
+        if self.add_gcc_deprecation_pragma {
+            builder.add_line("");
+            builder.add_line("#ifdef __GNUC__");
+            builder.add_line(&format!("#{}pragma GCC diagnostic push", self.indent_str));
+            builder.add_line(&format!(
+                "#{}pragma GCC diagnostic ignored \"-Wdeprecated-declarations\"",
+                self.indent_str
+            ));
+            builder.add_line("#endif");
+        }
+        if self.add_warning_suppression_pragma {
+            builder.add_line("");
+            builder.add_line("#if defined(__clang__)");
+            builder.add_line(&format!("#{}pragma clang diagnostic push", self.indent_str));
+            for warning in CLANG_SUPPRESSED_WARNINGS {
+                builder.add_line(&format!(
+                    "#{}pragma clang diagnostic ignored \"{warning}\"",
+                    self.indent_str
+                ));
+            }
+            builder.add_line("#elif defined(__GNUC__)");
+            builder.add_line(&format!("#{}pragma GCC diagnostic push", self.indent_str));
+            for warning in GCC_SUPPRESSED_WARNINGS {
+                builder.add_line(&format!(
+                    "#{}pragma GCC diagnostic ignored \"{warning}\"",
+                    self.indent_str
+                ));
+            }
+            builder.add_line("#endif");
+        }
+
+        if self.module_name.is_some() {
+            builder.add_line("");
+            builder.add_line("export {");
+        }
+        let mut mock_lines = Vec::new();
+        for mock in mocks.iter() {
+            builder.add_line("");
+            let start = builder.line_count();
+            for line in mock.code.lines() {
+                builder.add_line(line);
+            }
+            mock_lines.push(Some(start..builder.line_count()));
+        }
+        if self.module_name.is_some() {
+            builder.add_line("}");
+        }
+
+        if self.add_warning_suppression_pragma {
+            builder.add_line("");
+            builder.add_line("#if defined(__clang__)");
+            builder.add_line(&format!("#{}pragma clang diagnostic pop", self.indent_str));
+            builder.add_line("#elif defined(__GNUC__)");
+            builder.add_line(&format!("#{}pragma GCC diagnostic pop", self.indent_str));
+            builder.add_line("#endif");
+        }
+        if self.add_gcc_deprecation_pragma {
+            builder.add_line("");
+            builder.add_line("#ifdef __GNUC__");
+            builder.add_line(&format!("#{}pragma GCC diagnostic pop", self.indent_str));
+            builder.add_line("#endif");
+        }
+        if add_deprecation_pragma {
+            builder.add_line("");
+            builder.add_line("#ifdef _MSC_VER");
+            builder.add_line(&format!("#{}pragma warning(pop)", self.indent_str));
+            builder.add_line("#endif");
+        }
+        if self.nolint_checks.is_some() {
+            builder.add_line("");
+            self.add_nolint(&mut builder, "NOLINTEND");
+        }
+        if let Some(guard_macro) = &self.guard_macro {
+            builder.add_line("");
+            builder.add_line(&format!("#endif // defined({guard_macro})"));
+        }
+        if let Some(guard) = &include_guard {
+            builder.add_line("");
+            builder.add_line(&format!("#endif // {guard}"));
+        }
+
+        Ok(HeaderCode {
             code: builder.build(),
+            include_lines,
+            mock_lines,
+        })
+    }
+
+    pub(crate) fn mock(
+        &self,
+        class: &model::ClassToMock,
+        mock_name: &str,
+    ) -> crate::Result<crate::Mock> {
+        let mut builder = builder::CodeBuilder::new(self.indent_str.clone());
+        // When extracting an interface, the interface and an adapter for the class precede
+        // the mock, which mocks the interface rather than the class
+        let interface = self.extract_interface.then(|| {
+            let interface = extracted_interface(class);
+            self.build_interface(&mut builder, &interface);
+            builder.add_line("");
+            self.build_adapter(&mut builder, class, &interface);
+            builder.add_line("");
+            interface
+        });
+        let mocked = interface.as_ref().unwrap_or(class);
+        let cheat_sheet = (self.emit_cheat_sheet && self.style == MockStyle::Gmock)
+            .then(|| self.cheat_sheet(mocked, mock_name));
+        let documentation = self
+            .emit_documentation
+            .then(|| self.documentation_section(class, mocked, mock_name));
+        let fixture = self.emit_fixture.then(|| {
+            let mut fixture = builder::CodeBuilder::new(self.indent_str.clone());
+            self.build_fixture(&mut fixture, mocked, mock_name);
+            fixture
+        });
+        let signature_checks = self.signature_checks.then(|| {
+            let mut checks = builder::CodeBuilder::new(self.indent_str.clone());
+            self.build_signature_checks(&mut checks, class);
+            checks
+        });
+        if self.style == MockStyle::FakeIt {
+            self.build_fakeit_mock(&mut builder, mocked, mock_name);
+            return Ok(crate::Mock {
+                documentation,
+                ..crate::Mock::from(
+                    class,
+                    mock_name,
+                    builder.build(),
+                    None,
+                    fixture,
+                    signature_checks,
+                    self.forward_declare_types,
+                )
+            });
+        }
+        let extracted_code = builder.build();
+        let method_code = match &self.template {
+            Some(template) => {
+                let methods = mocked
+                    .methods
+                    .iter()
+                    .map(|method| template::method_context(method, self.mock_method(method)))
+                    .collect();
+                template::render_methods(template, methods)?
+            }
+            None => None,
+        };
+        let mut builder = builder::CodeBuilder::new(self.indent_str.clone());
+        self.build_mock(&mut builder, mocked, mock_name, method_code.as_deref());
+        let definitions = self.split_source_file.then(|| {
+            let mut definitions = builder::CodeBuilder::new(self.indent_str.clone());
+            self.build_definitions(&mut definitions, mocked, mock_name);
+            definitions
+        });
+        let template_class = self.template.as_ref().map(|_| {
+            let mock_methods = method_code.unwrap_or_else(|| {
+                mocked
+                    .methods
+                    .iter()
+                    .map(|method| self.mock_method(method))
+                    .collect()
+            });
+            template::class_context(mocked, mock_name, mock_methods)
+        });
+        let code = match (&self.template, &template_class) {
+            (Some(template), Some(context)) => template::render_mock(template, context.clone())?,
+            _ => None,
+        };
+        let code = extracted_code + &code.unwrap_or_else(|| builder.build());
+        // Lines following mocked declarations continue the numbering of a header with
+        // only this mock, named like by default, until the mock is put in a header
+        let code = self.restore_line_directives(code, || {
+            let extension = class
+                .declaration_location
+                .as_ref()
+                .and_then(|(file, _)| file.extension())
+                .map_or("h".into(), |extension| extension.to_string_lossy());
+            format!("{mock_name}.{extension}")
+        });
+        Ok(crate::Mock {
+            cheat_sheet,
+            documentation,
+            template_class,
+            ..crate::Mock::from(
+                class,
+                mock_name,
+                code,
+                definitions,
+                fixture,
+                signature_checks,
+                self.forward_declare_types,
+            )
+        })
+    }
+
+    // Rewrites the #line directives following mocked declarations, which are every second
+    // #line directive, to continue the numbering of the generated file with the given name
+    pub(crate) fn restore_line_directives(
+        &self,
+        code: String,
+        file: impl FnOnce() -> String,
+    ) -> String {
+        if !self.line_directives {
+            return code;
+        }
+        let file = file();
+        let mut mapped = false;
+        code.split_inclusive('\n')
+            .enumerate()
+            .map(|(index, line)| {
+                let directive = line.trim_start();
+                if !directive.starts_with("#line ") {
+                    return line.to_string();
+                }
+                mapped = !mapped;
+                if mapped {
+                    return line.to_string();
+                }
+                let indent = &line[..line.len() - directive.len()];
+                let line_ending = &directive[directive.trim_end_matches(['\r', '\n']).len()..];
+                // The directive sets the number of the next line
+                format!("{indent}#line {} \"{file}\"{line_ending}", index + 2)
+            })
+            .collect()
+    }
+
+    pub(crate) fn source(
+        &self,
+        header_include_path: &str,
+        mocks: &[crate::Mock],
+    ) -> Option<String> {
+        let definitions = mocks
+            .iter()
+            .filter_map(|mock| mock.definitions.as_ref())
+            .collect::<Vec<_>>();
+        if definitions.is_empty() {
+            return None;
+        }
+        let mut builder = builder::CodeBuilder::new(self.indent_str.clone());
+        self.add_banner(&mut builder, &[header_include_path.to_string()], mocks);
+        if let Some(guard_macro) = &self.guard_macro {
+            builder.add_line(&format!("#if defined({guard_macro})"));
+            builder.add_line("");
+        }
+        self.add_nolint(&mut builder, "NOLINTBEGIN");
+        builder.add_line(&format!("#include \"{header_include_path}\""));
+        for code in definitions {
+            builder.add_line("");
+            for line in code.lines() {
+                builder.add_line(line);
+            }
+        }
+        if self.nolint_checks.is_some() {
+            builder.add_line("");
+            self.add_nolint(&mut builder, "NOLINTEND");
+        }
+        if let Some(guard_macro) = &self.guard_macro {
+            builder.add_line("");
+            builder.add_line(&format!("#endif // defined({guard_macro})"));
+        }
+        Some(builder.build())
+    }
+
+    pub(crate) fn fixture(&self, header_include_path: &str, mock: &crate::Mock) -> Option<String> {
+        let fixture = mock.fixture.as_ref()?;
+        let mut builder = builder::CodeBuilder::new(self.indent_str.clone());
+        self.add_banner(
+            &mut builder,
+            &[header_include_path.to_string()],
+            std::slice::from_ref(mock),
+        );
+        builder.add_line(&format!("#include \"{header_include_path}\""));
+        builder.add_line("");
+        builder.add_line("#include <gtest/gtest.h>");
+        builder.add_line("");
+        for line in fixture.lines() {
+            builder.add_line(line);
+        }
+        Some(builder.build())
+    }
+
+    // Generates a header checking that the signatures of the mocked methods are unchanged,
+    // by including the mocked headers. Returns None if signature checks are not generated.
+    pub(crate) fn signature_checks_header(
+        &self,
+        source_file_paths: &[String],
+        mocks: &[crate::Mock],
+    ) -> Option<String> {
+        let checks = mocks
+            .iter()
+            .filter_map(|mock| mock.signature_checks.as_ref())
+            .collect::<Vec<_>>();
+        if checks.is_empty() {
+            return None;
+        }
+        let mut builder = builder::CodeBuilder::new(self.indent_str.clone());
+        self.add_banner(&mut builder, source_file_paths, mocks);
+        builder.add_line("#pragma once");
+        builder.add_line("");
+        for path in source_file_paths {
+            if self.include_with_angle_brackets {
+                builder.add_line(&format!("#include <{path}>"));
+            } else {
+                builder.add_line(&format!("#include \"{path}\""));
+            }
+        }
+        builder.add_line("#include <type_traits>");
+        for code in checks {
+            builder.add_line("");
+            for line in code.lines() {
+                builder.add_line(line);
+            }
+        }
+        Some(builder.build())
+    }
+
+    // Generates markdown documenting the mocks of a header and where they are written.
+    // Returns None if documentation is not emitted.
+    pub(crate) fn documentation(
+        &self,
+        output_file: &std::path::Path,
+        mocks: &[crate::Mock],
+    ) -> Option<String> {
+        let sections = mocks
+            .iter()
+            .filter_map(|mock| mock.documentation.as_ref())
+            .collect::<Vec<_>>();
+        if sections.is_empty() {
+            return None;
+        }
+        let mut source_files = Vec::new();
+        for file in mocks.iter().filter_map(|mock| mock.source_file.as_ref()) {
+            let file = format!("`{}`", file.to_string_lossy());
+            if !source_files.contains(&file) {
+                source_files.push(file);
+            }
+        }
+        let mut builder = builder::CodeBuilder::new(self.indent_str.clone());
+        if source_files.is_empty() {
+            builder.add_line("# Mocks");
+        } else {
+            builder.add_line(&format!("# Mocks of {}", source_files.join(", ")));
+        }
+        builder.add_line("");
+        builder.add_line(&format!(
+            "Mocks are written to `{}`.",
+            output_file.to_string_lossy()
+        ));
+        for section in sections {
+            builder.add_line("");
+            for line in section.lines() {
+                builder.add_line(line);
+            }
+        }
+        Some(builder.build())
+    }
+
+    // Adds a clang-tidy suppression comment, e.g., `// NOLINTBEGIN(checks)`, if enabled
+    fn add_nolint(&self, builder: &mut builder::CodeBuilder, marker: &str) {
+        match self.nolint_checks.as_deref() {
+            Some([]) => builder.add_line(&format!("// {marker}")),
+            Some(checks) => builder.add_line(&format!("// {marker}({})", checks.join(","))),
+            None => {}
+        }
+    }
+
+    fn add_banner(
+        &self,
+        builder: &mut builder::CodeBuilder,
+        source_file_paths: &[String],
+        mocks: &[crate::Mock],
+    ) {
+        self.add_license(builder, source_file_paths);
+        for line in self.banner_text(source_file_paths, mocks).lines() {
+            builder.add_line(line);
+        }
+    }
+
+    fn add_license(&self, builder: &mut builder::CodeBuilder, source_file_paths: &[String]) {
+        if let Some(license) = &self.license {
+            for line in banner::license(license, source_file_paths).lines() {
+                builder.add_line(line);
+            }
+        }
+    }
+
+    fn banner_text(&self, source_file_paths: &[String], mocks: &[crate::Mock]) -> String {
+        let classes = mocks
+            .iter()
+            .map(|mock| mock.parent_name.as_str())
+            .collect::<Vec<_>>();
+        banner::banner(
+            self.banner.as_deref().unwrap_or(banner::DEFAULT_BANNER),
+            source_file_paths,
+            &classes,
+        )
+    }
+
+    // Out-of-line definitions of special members declared in the mock when splitting
+    // mocks into header and source files, following the gmock cookbook advice to speed
+    // up compilation
+    fn build_definitions(
+        &self,
+        builder: &mut builder::CodeBuilder,
+        class: &model::ClassToMock,
+        mock_name: &str,
+    ) {
+        builder.maybe_add_line(&self.namespace_start(&self.mock_namespaces(class)));
+        if self.declares_default_constructor(class) {
+            builder.add_line(&format!("{mock_name}::{mock_name}() {{}}"));
+        }
+        if self.mocks_destructor(class) {
+            builder.add_line(&format!("{mock_name}::~{mock_name}() {{ Die(); }}"));
+        } else {
+            builder.add_line(&format!("{mock_name}::~{mock_name}() {{}}"));
+        }
+        builder.maybe_add_line(&self.namespace_end(&self.mock_namespaces(class)));
+    }
+
+    // Functions creating nice and strict instances of a mock
+    fn build_nice_strict_helpers(
+        &self,
+        builder: &mut builder::CodeBuilder,
+        mock_name: &str,
+        allocation: HelperAllocation,
+    ) {
+        for kind in ["Nice", "Strict"] {
+            let mock_type = format!("::testing::{kind}Mock<{mock_name}>");
+            let (return_type, creation) = match allocation {
+                HelperAllocation::Value => (mock_type.clone(), format!("{mock_type}()")),
+                HelperAllocation::Heap => (
+                    format!("std::unique_ptr<{mock_type}>"),
+                    format!("std::make_unique<{mock_type}>()"),
+                ),
+            };
+            builder.add_line(&format!("inline {return_type} make{kind}{mock_name}()"));
+            builder.add_line("{");
+            builder.push_indent();
+            builder.add_line(&format!("return {creation};"));
+            builder.pop_indent();
+            builder.add_line("}");
+        }
+    }
+
+    // Function creating a mock owned by a smart pointer to the mocked class, also giving a
+    // raw pointer to the mock for setting expectations
+    fn build_factory_helper(
+        &self,
+        builder: &mut builder::CodeBuilder,
+        class: &model::ClassToMock,
+        mock_name: &str,
+        pointer: FactoryPointer,
+    ) {
+        let (smart_pointer, make) = match pointer {
+            FactoryPointer::Unique => ("std::unique_ptr", "std::make_unique"),
+            FactoryPointer::Shared => ("std::shared_ptr", "std::make_shared"),
+        };
+        builder.add_line(&format!(
+            "inline {smart_pointer}<{}> make{mock_name}({mock_name} *& out)",
+            self.base_name(class)
+        ));
+        builder.add_line("{");
+        builder.push_indent();
+        builder.add_line(&format!("auto mock = {make}<{mock_name}>();"));
+        builder.add_line("out = mock.get();");
+        builder.add_line("return mock;");
+        builder.pop_indent();
+        builder.add_line("}");
+    }
+
+    // Pure virtual interface with the methods of a class to extract an interface from
+    fn build_interface(&self, builder: &mut builder::CodeBuilder, interface: &model::ClassToMock) {
+        builder.maybe_add_line(&self.namespace_start(&interface.namespaces));
+        builder.add_line(&format!("class {}", interface.name));
+        builder.add_line("{");
+        builder.add_line("public:");
+        builder.push_indent();
+        builder.add_line(&format!("virtual ~{}() = default;", interface.name));
+        for method in &interface.methods {
+            builder.add_line(&format!(
+                "virtual {} {}({}){} = 0;",
+                method.result_type,
+                method.name,
+                method
+                    .arguments
+                    .iter()
+                    .map(|arg| arg.declaration_with_own_name())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                signature_qualifiers(method)
+            ));
+        }
+        builder.pop_indent();
+        builder.add_line("};");
+        builder.maybe_add_line(&self.namespace_end(&interface.namespaces));
+    }
+
+    // Adapter implementing an extracted interface by forwarding calls to a referenced
+    // object of the class the interface was extracted from
+    fn build_adapter(
+        &self,
+        builder: &mut builder::CodeBuilder,
+        class: &model::ClassToMock,
+        interface: &model::ClassToMock,
+    ) {
+        let adapter_name = format!("{}Adapter", class.name);
+        builder.maybe_add_line(&self.namespace_start(&class.namespaces));
+        builder.add_line(&format!(
+            "class {} : public {}",
+            adapter_name, interface.name
+        ));
+        builder.add_line("{");
+        builder.add_line("public:");
+        builder.push_indent();
+        builder.add_line(&format!(
+            "explicit {}({} & adaptee) : adaptee_(adaptee) {{}}",
+            adapter_name, class.name
+        ));
+        for method in &interface.methods {
+            let names = (0..method.arguments.len())
+                .map(|i| format!("arg{i}"))
+                .collect::<Vec<_>>();
+            let parameters = method
+                .arguments
+                .iter()
+                .zip(&names)
+                .map(|(arg, name)| arg.declaration(name))
+                .collect::<Vec<_>>();
+            let forwarded = method
+                .arguments
+                .iter()
+                .zip(&names)
+                .map(|(arg, name)| {
+                    if arg.type_name.ends_with("&&") {
+                        format!("std::move({name})")
+                    } else {
+                        name.clone()
+                    }
+                })
+                .collect::<Vec<_>>();
+            let adaptee = if method.ref_qualifier.as_deref() == Some("&&") {
+                "std::move(adaptee_)"
+            } else {
+                "adaptee_"
+            };
+            let call = format!("{}.{}({})", adaptee, method.name, forwarded.join(", "));
+            let body = if method.result_type == "void" {
+                format!("{call};")
+            } else {
+                format!("return {call};")
+            };
+            builder.add_line(&format!(
+                "{} {}({}){} override {{ {} }}",
+                method.result_type,
+                method.name,
+                parameters.join(", "),
+                signature_qualifiers(method),
+                body
+            ));
+        }
+        builder.add_line("");
+        builder.pop_indent();
+        builder.add_line("private:");
+        builder.push_indent();
+        builder.add_line(&format!("{} & adaptee_;", class.name));
+        builder.pop_indent();
+        builder.add_line("};");
+        builder.maybe_add_line(&self.namespace_end(&class.namespaces));
+    }
+
+    // Markdown section documenting a mocked class, with the methods that are mocked and the
+    // methods that are skipped. The mocked class may be an interface extracted from the
+    // class.
+    fn documentation_section(
+        &self,
+        class: &model::ClassToMock,
+        mocked: &model::ClassToMock,
+        mock_name: &str,
+    ) -> String {
+        let mut builder = builder::CodeBuilder::new(self.indent_str.clone());
+        builder.add_line(&format!("## {}", class.qualified_name()));
+        builder.add_line("");
+        let namespaces = self.mock_namespaces(mocked);
+        if namespaces.is_empty() {
+            builder.add_line(&format!("Mocked by `{mock_name}` in the global namespace."));
+        } else {
+            builder.add_line(&format!(
+                "Mocked by `{mock_name}` in namespace `{}`.",
+                namespaces.join("::")
+            ));
+        }
+        builder.add_line("");
+        builder.add_line("Mocked methods:");
+        builder.add_line("");
+        for method in &mocked.methods {
+            builder.add_line(&format!(
+                "- `{}`: `{}`",
+                method.name,
+                method.info(&mocked.name).signature
+            ));
+        }
+        if !class.skipped_methods.is_empty() {
+            builder.add_line("");
+            builder.add_line("Skipped methods:");
+            builder.add_line("");
+            for (name, reason) in &class.skipped_methods {
+                builder.add_line(&format!("- `{name}`: {reason}"));
+            }
+        }
+        builder.build()
+    }
+
+    // Markdown listing an `EXPECT_CALL` for each mocked method, to paste into tests. Overloaded
+    // methods get typed matchers to select the overload, and const overloads are selected
+    // via a const mock.
+    fn cheat_sheet(&self, class: &model::ClassToMock, mock_name: &str) -> String {
+        let mut builder = builder::CodeBuilder::new(self.indent_str.clone());
+        builder.add_line(&format!("# {mock_name}"));
+        builder.add_line("");
+        builder.add_line(&format!(
+            "Expectations for the methods of `{}` mocked by `{mock_name} mock`.",
+            class.qualified_name()
+        ));
+        builder.add_line("");
+        builder.add_line("```cpp");
+        builder.add_line("using ::testing::_;");
+        builder.add_line("");
+        let hidden_overloads = hidden_overload_names(class);
+        for method in &class.methods {
+            let overloaded = hidden_overloads.contains(&method.name.as_str())
+                || class
+                    .methods
+                    .iter()
+                    .filter(|other| other.name == method.name)
+                    .count()
+                    > 1;
+            let matchers = method
+                .arguments
+                .iter()
+                .map(|arg| {
+                    if overloaded {
+                        format!("::testing::A<{}>()", arg.type_name)
+                    } else {
+                        "_".to_string()
+                    }
+                })
+                .collect::<Vec<_>>();
+            let mock = if overloaded && method.is_const {
+                "::testing::Const(mock)"
+            } else {
+                "mock"
+            };
+            builder.add_line(&format!(
+                "EXPECT_CALL({mock}, {}({}));",
+                method.name,
+                matchers.join(", ")
+            ));
+        }
+        builder.add_line("```");
+        builder.build()
+    }
+
+    // Skeleton of a gtest fixture with the mock as a member and an empty first test
+    fn build_fixture(
+        &self,
+        builder: &mut builder::CodeBuilder,
+        class: &model::ClassToMock,
+        mock_name: &str,
+    ) {
+        let fixture_name = crate::naming::default_name_fixture(&class.name);
+        builder.maybe_add_line(&self.namespace_start(&self.mock_namespaces(class)));
+        builder.add_line(&format!("class {fixture_name} : public ::testing::Test"));
+        builder.add_line("{");
+        builder.add_line("protected:");
+        builder.push_indent();
+        for method in ["SetUp", "TearDown"] {
+            builder.add_line(&format!("void {method}() override"));
+            builder.add_line("{");
+            builder.add_line("}");
+            builder.add_line("");
+        }
+        builder.add_line(&format!("{mock_name} mock;"));
+        builder.pop_indent();
+        builder.add_line("};");
+        builder.add_line("");
+        builder.add_line(&format!("TEST_F({fixture_name}, FirstTest)"));
+        builder.add_line("{");
+        builder.add_line("}");
+        builder.maybe_add_line(&self.namespace_end(&self.mock_namespaces(class)));
+    }
+
+    // Whether a default constructor is declared in the mock to be defined out-of-line.
+    // It is not if a constructor with default actions is defined inline.
+    fn declares_default_constructor(&self, class: &model::ClassToMock) -> bool {
+        can_add_default_constructor(class)
+            && !(self.style == MockStyle::Gmock
+                && class
+                    .methods
+                    .iter()
+                    .any(|method| self.has_default_action(method)))
+    }
+
+    // Whether the destructor of the mock calls a mocked `Die` method, to be able to expect
+    // destruction. Only virtual destructors are called when deleting via the mocked class.
+    fn mocks_destructor(&self, class: &model::ClassToMock) -> bool {
+        self.mock_destructor && self.style == MockStyle::Gmock && class.has_virtual_destructor
+    }
+
+    // Whether a default action is set for a method in the constructor of the mock
+    fn has_default_action(&self, method: &model::MethodToMock) -> bool {
+        (self.reference_default_actions && method.returns_reference)
+            || (self.value_default_actions && method.default_return_value.is_some())
+    }
+
+    // Adds static assertions that the public mocked methods of a class have the same
+    // signatures as when the mock was generated. Overloaded methods can't be referred to
+    // with `decltype`, so the method pointers are cast to the expected types instead.
+    fn build_signature_checks(
+        &self,
+        builder: &mut builder::CodeBuilder,
+        class: &model::ClassToMock,
+    ) {
+        builder.maybe_add_line(&self.namespace_start(&class.namespaces));
+        let hidden_overloads = hidden_overload_names(class);
+        for method in class
+            .methods
+            .iter()
+            .filter(|method| method.access == model::Access::Public)
+        {
+            let overloaded = hidden_overloads.contains(&method.name.as_str())
+                || class
+                    .methods
+                    .iter()
+                    .filter(|other| other.name == method.name)
+                    .count()
+                    > 1;
+            // The checks are outside of the class, where nested types must be qualified
+            let mut method = method.clone();
+            method.qualify_types_nested_in(&class.qualified_name());
+            let method_pointer = format!("&{}::{}", class.name, method.name);
+            let expected_type = member_pointer_type(&class.name, &method);
+            let condition = if overloaded {
+                format!("static_cast<{expected_type}>({method_pointer}) != nullptr")
+            } else {
+                format!("std::is_same<decltype({method_pointer}), {expected_type}>::value")
+            };
+            builder.add_line(&format!(
+                "static_assert({condition}, \"Signature of {}::{} has changed\");",
+                class.qualified_name(),
+                method.name
+            ));
+        }
+        builder.maybe_add_line(&self.namespace_end(&class.namespaces));
+    }
+
+    // Starts a new access section, e.g., `protected:`, in a class
+    fn add_access_specifier(&self, builder: &mut builder::CodeBuilder, access: model::Access) {
+        builder.pop_indent();
+        builder.add_line(&format!("{access}:"));
+        builder.push_indent();
+    }
+
+    fn build_mock(
+        &self,
+        builder: &mut builder::CodeBuilder,
+        class: &model::ClassToMock,
+        mock_name: &str,
+        method_code: Option<&[String]>,
+    ) {
+        builder.maybe_add_line(&self.namespace_start(&self.mock_namespaces(class)));
+
+        let base_name = self.base_name(class);
+        builder.add_line(&format!("class {mock_name} : public {base_name}"));
+        builder.add_line("{");
+        builder.add_line("public:");
+        builder.push_indent();
+        if self.keep_regions {
+            builder.add_line(&crate::markers::keep_marker(mock_name));
+            builder.add_line(crate::markers::END_KEEP_MARKER);
+        }
+        for (suite, name) in self
+            .friend_tests
+            .iter()
+            .filter_map(|t| friend_test_parts(t))
+        {
+            builder.add_line(&format!("FRIEND_TEST({suite}, {name});"));
+        }
+        if class.has_constructors_to_forward {
+            builder.add_line(&format!("using {}::{};", base_name, class.name));
         }
-    }
-}
-
-impl Generator {
-    pub(crate) fn new(methods_to_mock: MethodsToMockStrategy) -> Self {
-        Self {
-            methods_to_mock,
-            add_deprecation_pragma: false,
-            simplified_nested_namespaces: true,
-            indent_str: "  ".to_string(),
+        let gmock = self.style == MockStyle::Gmock;
+        if gmock && can_add_default_constructor(class) {
+            self.build_default_actions(builder, class, mock_name);
+        }
+        if self.split_source_file {
+            if self.declares_default_constructor(class) {
+                builder.add_line(&format!("{mock_name}();"));
+            }
+            builder.add_line(&format!("~{mock_name}();"));
+        } else if self.mocks_destructor(class) {
+            builder.add_line(&format!("~{mock_name}() override {{ Die(); }}"));
+        }
+        if self.mocks_destructor(class) {
+            builder.add_line(&format!("{}(void, Die, ());", self.mock_macro));
+        }
+        if self.com_interfaces && class.inherits_iunknown {
+            let methods = if gmock {
+                IUNKNOWN_MOCK_METHODS
+            } else {
+                IUNKNOWN_FAKE_METHODS
+            };
+            methods.iter().for_each(|line| {
+                builder.add_line(&line.replacen("MOCK_METHOD", &self.mock_macro, 1))
+            });
+        }
+        // Keep overloads that are not mocked visible, since mocking a method hides all
+        // overloads with the same name. Using declarations are ill-formed if any overload
+        // is private, so such overloads stay hidden.
+        hidden_overload_names(class)
+            .iter()
+            .filter(|name| {
+                !class
+                    .private_overloads
+                    .iter()
+                    .any(|private| private == *name)
+            })
+            .for_each(|name| builder.add_line(&format!("using {base_name}::{name};")));
+        let mut access = model::Access::Public;
+        let mut alias_names = std::collections::HashSet::new();
+        for (index, method) in class.methods.iter().enumerate() {
+            if self.preserve_access && method.access != access {
+                access = method.access;
+                self.add_access_specifier(builder, access);
+            }
+            if self.line_directives
+                && let Some((file, line)) = &method.declaration_location
+            {
+                builder.add_line(&format!(
+                    "#line {line} \"{}\"",
+                    file.to_string_lossy().replace('\\', "/")
+                ));
+            }
+            if let Some(code) = method_code.map(|code| &code[index]) {
+                code.lines().for_each(|line| builder.add_line(line));
+            } else if !gmock {
+                builder.add_line(&self.fake_method(method));
+            } else if self.move_only_helpers && has_unique_ptr_by_value(method) {
+                self.build_delegate_mock(builder, method, true);
+            } else if self.keeps_attributes(method) {
+                self.build_delegate_mock(builder, method, false);
+            } else {
+                let width = self.column_limit.map_or(usize::MAX, |limit| {
+                    limit.saturating_sub(builder.indent_width())
+                });
+                let method = if self.comma_type_aliases {
+                    &alias_comma_types(builder, method, mock_name, &mut alias_names)
+                } else {
+                    method
+                };
+                self.wrapped_mock_method(method, width)
+                    .iter()
+                    .for_each(|line| builder.add_line(line));
+            }
+            if self.line_directives && method.declaration_location.is_some() {
+                builder.add_line(RESTORE_LINE_DIRECTIVE);
+            }
+        }
+        if gmock && self.const_overload_helpers {
+            if access != model::Access::Public {
+                self.add_access_specifier(builder, model::Access::Public);
+            }
+            self.build_const_overload_helpers(builder, class, mock_name);
+        }
+        builder.pop_indent();
+        builder.add_line("};");
+        if gmock && self.mock_aliases {
+            builder.add_line(&format!(
+                "using Nice{mock_name} = ::testing::NiceMock<{mock_name}>;"
+            ));
+            builder.add_line(&format!(
+                "using Strict{mock_name} = ::testing::StrictMock<{mock_name}>;"
+            ));
+        }
+        if let Some(allocation) = self.nice_strict_helpers
+            && gmock
+            && can_add_default_constructor(class)
+        {
+            self.build_nice_strict_helpers(builder, mock_name, allocation);
+        }
+        if self.assert_not_abstract {
+            builder.add_line(&format!(
+                "static_assert(!std::is_abstract<{mock_name}>::value, \
+                 \"{mock_name} is abstract, some pure virtual method is not mocked\");"
+            ));
+        }
+        if let Some(pointer) = self.factory_helpers
+            && can_add_default_constructor(class)
+        {
+            self.build_factory_helper(builder, class, mock_name, pointer);
         }
-    }
 
-    pub(crate) fn methods_to_mock(&mut self, methods: MethodsToMockStrategy) {
-        self.methods_to_mock = methods;
+        builder.maybe_add_line(&self.namespace_end(&self.mock_namespaces(class)));
     }
 
-    pub(crate) fn add_deprecation_pragma(&mut self, value: bool) {
-        self.add_deprecation_pragma = value;
-    }
+    // FakeIt mocks via the interface directly, so the mock is a `fakeit::Mock` with a
+    // constructor faking all methods to return default values, similar to a gmock
+    // NiceMock. Overloaded methods are spelled out with their signatures.
+    fn build_fakeit_mock(
+        &self,
+        builder: &mut builder::CodeBuilder,
+        class: &model::ClassToMock,
+        mock_name: &str,
+    ) {
+        builder.maybe_add_line(&self.namespace_start(&self.mock_namespaces(class)));
 
-    pub(crate) fn simplified_nested_namespaces(&mut self, value: bool) {
-        self.simplified_nested_namespaces = value;
+        builder.add_line(&format!(
+            "class {} : public fakeit::Mock<{}>",
+            mock_name,
+            self.base_name(class)
+        ));
+        builder.add_line("{");
+        builder.add_line("public:");
+        builder.push_indent();
+        builder.add_line(&format!("{mock_name}()"));
+        builder.add_line("{");
+        builder.push_indent();
+        for method in &class.methods {
+            builder.add_line(&format!("Fake({});", fakeit_method(class, method)));
+        }
+        builder.pop_indent();
+        builder.add_line("}");
+        builder.pop_indent();
+        builder.add_line("};");
+
+        builder.maybe_add_line(&self.namespace_end(&self.mock_namespaces(class)));
     }
 
-    pub(crate) fn indent_str(&mut self, indent_str: String) {
-        self.indent_str = indent_str;
+    fn mock_method(&self, method: &model::MethodToMock) -> String {
+        self.wrapped_mock_method(method, usize::MAX).join("\n")
     }
 
-    pub(crate) fn header(&self, source_file_paths: &[String], mocks: &[crate::Mock]) -> String {
-        let mut builder = builder::CodeBuilder::new(self.indent_str.clone());
-        builder.add_line(
-            "// Automatically generated by Mocksmith (https://github.com/jordfras/mocksmith)",
+    // Mock of a method, wrapped across several lines if longer than the given width
+    fn wrapped_mock_method(&self, method: &model::MethodToMock, width: usize) -> Vec<String> {
+        let return_type = method_return_type(method);
+        let arguments = method_arguments(method);
+        let qualifiers = method_qualifiers(method).join(", ");
+        let line = format!(
+            "{}({}, {}, ({}), ({}));",
+            self.mock_macro,
+            return_type,
+            method.name,
+            arguments.join(", "),
+            qualifiers
         );
-        builder.add_line("#pragma once");
-        builder.add_line("");
-        for path in source_file_paths {
-            builder.add_line(&format!("#include \"{path}\""));
+        if line.chars().count() <= width {
+            return vec![line];
         }
-        builder.add_line("#include <gmock/gmock.h>");
+        wrap_mock_method(
+            &format!("{}(", self.mock_macro),
+            &format!("{}, {},", return_type, method.name),
+            &arguments,
+            &format!("({qualifiers}));"),
+            width,
+        )
+    }
 
-        if self.add_deprecation_pragma {
-            builder.add_line("");
-            builder.add_line("#ifdef _MSC_VER");
-            builder.add_line(&format!("#{}pragma warning(push)", self.indent_str));
+    // Adds a constructor setting default actions. Methods returning references get
+    // actions returning references to static dummy objects, since gmock aborts when such
+    // methods are called without actions. Methods returning default constructible and
+    // copyable values get actions returning default values.
+    fn build_default_actions(
+        &self,
+        builder: &mut builder::CodeBuilder,
+        class: &model::ClassToMock,
+        mock_name: &str,
+    ) {
+        let methods = class
+            .methods
+            .iter()
+            .filter(|method| self.has_default_action(method))
+            .collect::<Vec<_>>();
+        if methods.is_empty() {
+            return;
+        }
+        builder.add_line(&format!("{mock_name}()"));
+        builder.add_line("{");
+        builder.push_indent();
+        for method in methods {
+            let matchers = method
+                .arguments
+                .iter()
+                .map(|arg| format!("::testing::A<{}>()", arg.type_name))
+                .collect::<Vec<_>>();
+            // The const overload of a method is only selected via a const mock
+            let mock = if method.is_const {
+                "::testing::Const(*this)"
+            } else {
+                "*this"
+            };
             builder.add_line(&format!(
-                "#{}pragma warning(disable : 4996)",
-                self.indent_str
+                "ON_CALL({}, {}({}))",
+                mock,
+                method.name,
+                matchers.join(", ")
             ));
-            builder.add_line("#endif");
+            builder.push_indent();
+            if method.returns_reference {
+                let (dummy_type, returned) = reference_dummy(method);
+                builder.add_line(&format!(
+                    ".WillByDefault([](auto &&...) -> {} {{",
+                    method.result_type
+                ));
+                builder.push_indent();
+                builder.add_line(&format!("static {dummy_type} dummy{{}};"));
+                builder.add_line(&format!("return {returned};"));
+                builder.pop_indent();
+                builder.add_line("});");
+            } else if let Some(value) = &method.default_return_value {
+                builder.add_line(&format!(".WillByDefault(::testing::Return({value}));"));
+            }
+            builder.pop_indent();
         }
+        builder.pop_indent();
+        builder.add_line("}");
+    }
 
-        for mock in mocks.iter() {
-            builder.add_line("");
-            for line in mock.code.lines() {
-                builder.add_line(line);
+    // Attributes can't be put on `MOCK_METHOD`, which starts with a `static_assert`, so
+    // methods with attributes to keep are mocked with delegation
+    fn keeps_attributes(&self, method: &model::MethodToMock) -> bool {
+        self.keep_method_attributes
+            && !method.attributes.is_empty()
+            && method.ref_qualifier.is_none()
+            && method.calltype.is_none()
+    }
+
+    // Mocks a method by mocking a delegate method, e.g., `DoBar` for `bar`, together with
+    // an overriding adapter method calling the delegate. The adapter gets the attributes
+    // of the method, if kept. With `convert_unique_ptr`, this is the gmock cookbook
+    // pattern for methods taking or returning `std::unique_ptr` by value, where the
+    // delegate uses raw pointers instead.
+    fn build_delegate_mock(
+        &self,
+        builder: &mut builder::CodeBuilder,
+        method: &model::MethodToMock,
+        convert_unique_ptr: bool,
+    ) {
+        let unique_ptr_element = |type_name| {
+            if convert_unique_ptr {
+                unique_ptr_element(type_name)
+            } else {
+                None
             }
+        };
+        let delegate_name = format!("Do{}", capitalize_first(&method.name));
+        let names = method
+            .arguments
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| arg.name.clone().unwrap_or_else(|| format!("arg{i}")))
+            .collect::<Vec<_>>();
+        let delegate_arguments = method
+            .arguments
+            .iter()
+            .zip(&names)
+            .map(|(arg, name)| match unique_ptr_element(&arg.type_name) {
+                Some(element) => format!("{element} * {name}"),
+                None => arg.declaration(name),
+            })
+            .map(|arg_str| wrap_with_parentheses_if_contains_comma(&arg_str))
+            .collect::<Vec<_>>();
+        let delegate_result_type = match unique_ptr_element(&method.result_type) {
+            Some(element) => format!("{element} *"),
+            None => method.result_type.clone(),
+        };
+        let mut delegate_qualifiers = Vec::new();
+        if method.is_const {
+            delegate_qualifiers.push("const");
         }
-
-        if self.add_deprecation_pragma {
-            builder.add_line("");
-            builder.add_line("#ifdef _MSC_VER");
-            builder.add_line(&format!("#{}pragma warning(pop)", self.indent_str));
-            builder.add_line("#endif");
+        if method.is_noexcept {
+            delegate_qualifiers.push("noexcept");
         }
+        builder.add_line(&format!(
+            "{}({}, {}, ({}), ({}));",
+            self.mock_macro,
+            wrap_with_parentheses_if_contains_comma(&delegate_result_type),
+            delegate_name,
+            delegate_arguments.join(", "),
+            delegate_qualifiers.join(", ")
+        ));
 
-        builder.build()
+        let parameters = method
+            .arguments
+            .iter()
+            .zip(&names)
+            .map(|(arg, name)| arg.declaration(name))
+            .collect::<Vec<_>>();
+        let mut qualifiers = String::new();
+        if method.is_const {
+            qualifiers.push_str(" const");
+        }
+        if method.is_noexcept {
+            qualifiers.push_str(" noexcept");
+        }
+        if method.is_virtual {
+            qualifiers.push_str(" override");
+        }
+        let attributes = if self.keeps_attributes(method) {
+            format!("[[{}]] ", method.attributes.join(", "))
+        } else {
+            String::new()
+        };
+        builder.add_line(&format!(
+            "{}{} {}({}){}",
+            attributes,
+            method.result_type,
+            method.name,
+            parameters.join(", "),
+            qualifiers
+        ));
+        builder.add_line("{");
+        builder.push_indent();
+        let forwarded = method
+            .arguments
+            .iter()
+            .zip(&names)
+            .map(|(arg, name)| match unique_ptr_element(&arg.type_name) {
+                Some(_) => format!("{name}.get()"),
+                None if arg.type_name.ends_with("&&") => format!("std::move({name})"),
+                None => name.clone(),
+            })
+            .collect::<Vec<_>>();
+        let call = format!("{}({})", delegate_name, forwarded.join(", "));
+        if unique_ptr_element(&method.result_type).is_some() {
+            builder.add_line(&format!("return {}({});", method.result_type, call));
+        } else if method.result_type == "void" {
+            builder.add_line(&format!("{call};"));
+        } else {
+            builder.add_line(&format!("return {call};"));
+        }
+        builder.pop_indent();
+        builder.add_line("}");
     }
 
-    pub(crate) fn mock(&self, class: &model::ClassToMock, mock_name: &str) -> crate::Mock {
-        let mut builder = builder::CodeBuilder::new(self.indent_str.clone());
-        self.build_mock(&mut builder, class, mock_name);
-        crate::Mock::from(class, mock_name, builder)
+    // Adds helpers implementing the gmock cookbook pattern of letting a non-const overload
+    // delegate to its const overload, so that expectations only need to be set on one of
+    // them
+    fn build_const_overload_helpers(
+        &self,
+        builder: &mut builder::CodeBuilder,
+        class: &model::ClassToMock,
+        mock_name: &str,
+    ) {
+        let pairs = const_overload_pairs(&class.methods);
+        let mut names: Vec<&str> = Vec::new();
+        for (non_const, _) in &pairs {
+            if !names.contains(&non_const.name.as_str()) {
+                names.push(&non_const.name);
+            }
+        }
+        for name in names {
+            builder.add_line("");
+            builder.add_line(&format!(
+                "// Makes the non-const {name}() call the const overload by default, so that"
+            ));
+            builder.add_line("// expectations only need to be set on the const overload");
+            builder.add_line(&format!("void Delegate{}ToConst()", capitalize_first(name)));
+            builder.add_line("{");
+            builder.push_indent();
+            for (non_const, constant) in pairs.iter().filter(|(m, _)| m.name == name) {
+                self.build_delegation(builder, non_const, constant, mock_name);
+            }
+            builder.pop_indent();
+            builder.add_line("}");
+        }
     }
 
-    fn build_mock(
+    fn build_delegation(
         &self,
         builder: &mut builder::CodeBuilder,
-        class: &model::ClassToMock,
+        non_const: &model::MethodToMock,
+        constant: &model::MethodToMock,
         mock_name: &str,
     ) {
-        builder.maybe_add_line(&self.namespace_start(&class.namespaces));
+        let names = (0..non_const.arguments.len())
+            .map(|i| format!("arg{i}"))
+            .collect::<Vec<_>>();
+        let matchers = non_const
+            .arguments
+            .iter()
+            .map(|arg| format!("::testing::A<{}>()", arg.type_name))
+            .collect::<Vec<_>>();
+        let parameters = non_const
+            .arguments
+            .iter()
+            .zip(&names)
+            .map(|(arg, name)| arg.declaration(name))
+            .collect::<Vec<_>>();
+        let forwarded = non_const
+            .arguments
+            .iter()
+            .zip(&names)
+            .map(|(arg, name)| {
+                if arg.type_name.ends_with("&&") {
+                    format!("std::move({name})")
+                } else {
+                    name.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        let call = format!(
+            "static_cast<const {} *>(this)->{}({})",
+            mock_name,
+            constant.name,
+            forwarded.join(", ")
+        );
+        let result = if non_const.result_type == constant.result_type {
+            call
+        } else {
+            format!("const_cast<{}>({})", non_const.result_type, call)
+        };
+        let statement = if non_const.result_type == "void" {
+            format!("{result};")
+        } else {
+            format!("return {result};")
+        };
 
-        builder.add_line(&format!("class {} : public {}", mock_name, class.name));
-        builder.add_line("{");
-        builder.add_line("public:");
+        builder.add_line(&format!(
+            "ON_CALL(*this, {}({}))",
+            non_const.name,
+            matchers.join(", ")
+        ));
         builder.push_indent();
-        class.methods.iter().for_each(|method| {
-            builder.add_line(&format!(
-                "MOCK_METHOD({}, {}, ({}), ({}));",
-                method_return_type(method),
-                method.name,
-                method_arguments(method).join(", "),
-                method_qualifiers(method).join(", ")
-            ));
-        });
+        builder.add_line(&format!(
+            ".WillByDefault([this]({}) -> {} {{",
+            parameters.join(", "),
+            non_const.result_type
+        ));
+        builder.push_indent();
+        builder.add_line(&statement);
         builder.pop_indent();
-        builder.add_line("};");
+        builder.add_line("});");
+        builder.pop_indent();
+    }
 
-        builder.maybe_add_line(&self.namespace_end(&class.namespaces));
+    // Implements a method with a trivial body for the fake style. Values are default
+    // constructed and references refer to static dummy objects. Argument names are left
+    // out to avoid warnings about unused parameters.
+    fn fake_method(&self, method: &model::MethodToMock) -> String {
+        let attributes = if self.keep_method_attributes && !method.attributes.is_empty() {
+            format!("[[{}]] ", method.attributes.join(", "))
+        } else {
+            String::new()
+        };
+        let calltype = method
+            .calltype
+            .as_ref()
+            .map(|calltype| format!("{calltype} "))
+            .unwrap_or_default();
+        let mut qualifiers = signature_qualifiers(method);
+        if method.is_virtual {
+            qualifiers.push_str(" override");
+        }
+        let body = if method.result_type == "void" {
+            "{}".to_string()
+        } else if method.returns_reference {
+            let (dummy_type, returned) = reference_dummy(method);
+            format!("{{ static {dummy_type} dummy{{}}; return {returned}; }}")
+        } else {
+            "{ return {}; }".to_string()
+        };
+        format!(
+            "{}{} {}{}({}){} {}",
+            attributes,
+            method.result_type,
+            calltype,
+            method.name,
+            method
+                .arguments
+                .iter()
+                .map(|arg| arg.type_name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            qualifiers,
+            body
+        )
+    }
+
+    // Name to refer to the mocked class with from the mock. The name is fully qualified
+    // when the mock is in an extra namespace, since names in the extra namespace could
+    // otherwise hide the class, e.g., `foo::testing::Foo` in `foo::testing`.
+    fn base_name(&self, class: &model::ClassToMock) -> String {
+        if self.mock_namespace.is_some() {
+            format!("::{}", class.qualified_name())
+        } else {
+            class.name.clone()
+        }
+    }
+
+    // Namespaces of a mock, i.e., the namespaces of the mocked class followed by the extra
+    // mock namespace, if any
+    fn mock_namespaces(&self, class: &model::ClassToMock) -> Vec<String> {
+        let mut namespaces = class.namespaces.clone();
+        if let Some(mock_namespace) = &self.mock_namespace {
+            namespaces.extend(
+                mock_namespace
+                    .split("::")
+                    .filter(|namespace| !namespace.is_empty())
+                    .map(|namespace| namespace.to_string()),
+            );
+        }
+        namespaces
     }
 
     fn namespace_start(&self, namespaces: &[String]) -> Option<String> {
@@ -149,6 +1896,351 @@ impl Generator {
     }
 }
 
+// Creates an include guard name from a pattern where `{PROJECT}` is replaced with the
+// project name, and `{PATH}` and `{FILE}` with the directory and the stem of the include
+// path of the first mocked header, e.g., `FOO` and `BAR` for `foo/bar.h`. The replaced
+// values are upper case with characters not allowed in macro names replaced with
+// underscores. Empty values are removed together with a following underscore, to not
+// leave leading underscores reserved for the implementation.
+fn include_guard_name(
+    pattern: &str,
+    project: Option<&str>,
+    source_file_paths: &[String],
+) -> String {
+    let path = std::path::Path::new(source_file_paths.first().map_or("mocks.h", |p| p));
+    let directory = path
+        .parent()
+        .map(|parent| {
+            parent
+                .components()
+                .filter_map(|component| match component {
+                    std::path::Component::Normal(name) => Some(name.to_string_lossy()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("_")
+        })
+        .unwrap_or_default();
+    let file = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut name = pattern.to_string();
+    for (placeholder, value) in [
+        ("{PROJECT}", project.unwrap_or_default()),
+        ("{PATH}", &directory),
+        ("{FILE}", &file),
+    ] {
+        if value.is_empty() {
+            name = name.replace(&format!("{placeholder}_"), "");
+        }
+        let value = value
+            .to_uppercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        name = name.replace(placeholder, &value);
+    }
+    name
+}
+
+// Finds pairs of non-const and const overloads with the same arguments, e.g.,
+// `T& get()` and `const T& get() const`
+fn const_overload_pairs(
+    methods: &[model::MethodToMock],
+) -> Vec<(&model::MethodToMock, &model::MethodToMock)> {
+    let same_arguments = |a: &model::MethodToMock, b: &model::MethodToMock| {
+        a.arguments.len() == b.arguments.len()
+            && a.arguments
+                .iter()
+                .zip(&b.arguments)
+                .all(|(a, b)| a.type_name == b.type_name)
+    };
+    methods
+        .iter()
+        .filter(|method| !method.is_const && method.ref_qualifier.is_none())
+        .filter_map(|non_const| {
+            methods
+                .iter()
+                .find(|method| {
+                    method.is_const
+                        && method.ref_qualifier.is_none()
+                        && method.name == non_const.name
+                        && same_arguments(non_const, method)
+                })
+                .map(|constant| (non_const, constant))
+        })
+        .collect()
+}
+
+// Names of mocked methods with overloads in the class that are not mocked, either
+// imported from base classes with using declarations or declared in the class but not
+// mocked, e.g., due to filters or since they are templates
+fn hidden_overload_names(class: &model::ClassToMock) -> Vec<&str> {
+    let mocked_count = |name: &str| {
+        class
+            .methods
+            .iter()
+            .filter(|method| method.name == name)
+            .count()
+    };
+    let declared_count = |name: &str| class.overloads.iter().filter(|n| *n == name).count();
+    let mut names: Vec<&str> = Vec::new();
+    for name in class
+        .using_declarations
+        .iter()
+        .filter(|name| mocked_count(name) > 0)
+        .chain(
+            class
+                .methods
+                .iter()
+                .map(|method| &method.name)
+                .filter(|name| declared_count(name) > mocked_count(name)),
+        )
+    {
+        if !names.contains(&name.as_str()) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+// Refers to a method with the FakeIt macros, spelling out the signature if overloaded
+fn fakeit_method(class: &model::ClassToMock, method: &model::MethodToMock) -> String {
+    let overloaded = class
+        .methods
+        .iter()
+        .filter(|other| other.name == method.name)
+        .count()
+        > 1;
+    if overloaded {
+        let macro_name = if method.is_const {
+            "ConstOverloadedMethod"
+        } else {
+            "OverloadedMethod"
+        };
+        format!(
+            "{}(*this, {}, {}({}))",
+            macro_name,
+            method.name,
+            method.result_type,
+            method
+                .arguments
+                .iter()
+                .map(|arg| arg.type_name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    } else {
+        format!("Method(*this, {})", method.name)
+    }
+}
+
+// Headers declaring types used in the signatures of the mocked methods, and forward
+// declarations replacing headers when all types used from a header can be forward
+// declared
+fn type_headers_and_forward_declarations(
+    class: &model::ClassToMock,
+    forward_declare_types: bool,
+) -> (Vec<std::path::PathBuf>, Vec<String>) {
+    let uses = class
+        .methods
+        .iter()
+        .flat_map(|method| &method.type_uses)
+        .collect::<Vec<_>>();
+    let is_forward_declared = |header: &std::path::PathBuf| {
+        forward_declare_types
+            && uses
+                .iter()
+                .filter(|other| other.header == *header)
+                .all(|other| other.forward_declaration.is_some())
+    };
+    let mut headers = Vec::new();
+    let mut forward_declarations = Vec::new();
+    for type_use in &uses {
+        if !is_forward_declared(&type_use.header) {
+            if !headers.contains(&type_use.header) {
+                headers.push(type_use.header.clone());
+            }
+        } else if let Some(declaration) = &type_use.forward_declaration
+            && !forward_declarations.contains(declaration)
+        {
+            forward_declarations.push(declaration.clone());
+        }
+    }
+    (headers, forward_declarations)
+}
+
+// Interface to extract from a class, with its public methods as pure virtual methods
+fn extracted_interface(class: &model::ClassToMock) -> model::ClassToMock {
+    model::ClassToMock {
+        name: format!("I{}", class.name),
+        namespaces: class.namespaces.clone(),
+        methods: class
+            .methods
+            .iter()
+            .filter(|method| method.access == model::Access::Public)
+            .map(|method| model::MethodToMock {
+                is_virtual: true,
+                calltype: None,
+                ..method.clone()
+            })
+            .collect(),
+        inherits_iunknown: false,
+        has_virtual_destructor: true,
+        has_constructors_to_forward: false,
+        using_declarations: Vec::new(),
+        overloads: Vec::new(),
+        private_overloads: Vec::new(),
+        ambiguous_bases: Vec::new(),
+        unmocked_pure_virtual_methods: Vec::new(),
+        skipped_methods: Vec::new(),
+        declaration_location: class.declaration_location.clone(),
+    }
+}
+
+// Type of a pointer to a method, e.g., `void (Foo::*)(int) const`. A trailing return type
+// is used for return types that can't precede the declarator, e.g., function pointers.
+fn member_pointer_type(class_name: &str, method: &model::MethodToMock) -> String {
+    let calltype = method
+        .calltype
+        .as_ref()
+        .map(|calltype| format!("{calltype} "))
+        .unwrap_or_default();
+    let arguments = method
+        .arguments
+        .iter()
+        .map(|arg| arg.type_name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let declarator = format!(
+        "({calltype}{class_name}::*)({arguments}){}",
+        signature_qualifiers(method)
+    );
+    if method.result_type.contains(['(', '[']) {
+        format!("auto {declarator} -> {}", method.result_type)
+    } else {
+        format!("{} {declarator}", method.result_type)
+    }
+}
+
+// Qualifiers following the arguments in a method declaration, e.g., ` const noexcept`
+fn signature_qualifiers(method: &model::MethodToMock) -> String {
+    let mut qualifiers = String::new();
+    if method.is_const {
+        qualifiers.push_str(" const");
+    }
+    if let Some(ref_qualifier) = &method.ref_qualifier {
+        qualifiers.push(' ');
+        qualifiers.push_str(ref_qualifier);
+    }
+    if method.is_noexcept {
+        qualifiers.push_str(" noexcept");
+    }
+    qualifiers
+}
+
+// Type of a static dummy object for a method returning a reference to refer to, and the
+// expression returning it. Const of the referenced type is kept, e.g., for `const int *&`
+// the dummy must be a pointer to const.
+fn reference_dummy(method: &model::MethodToMock) -> (&str, &'static str) {
+    let dummy_type = method.result_type.trim_end_matches('&').trim_end();
+    let returned = if method.result_type.ends_with("&&") {
+        "std::move(dummy)"
+    } else {
+        "dummy"
+    };
+    (dummy_type, returned)
+}
+
+// Whether a name is a valid C++ identifier, e.g., for a macro
+// Test suite and test name of a test to befriend, given as TestSuite.TestName
+fn friend_test_parts(test: &str) -> Option<(&str, &str)> {
+    test.split_once('.')
+        .filter(|(suite, name)| is_identifier(suite) && is_identifier(name))
+}
+
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn sha256_hex(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+// A default constructor can't be added when the mock forwards the constructors of the
+// mocked class, since the mocked class may lack a default constructor
+pub(crate) fn can_add_default_constructor(class: &model::ClassToMock) -> bool {
+    !class.has_constructors_to_forward
+}
+
+// Methods with ref-qualifiers or calltypes are mocked as usual, since the delegation
+// pattern doesn't cover them
+fn has_unique_ptr_by_value(method: &model::MethodToMock) -> bool {
+    method.ref_qualifier.is_none()
+        && method.calltype.is_none()
+        && (unique_ptr_element(&method.result_type).is_some()
+            || method
+                .arguments
+                .iter()
+                .any(|arg| unique_ptr_element(&arg.type_name).is_some()))
+}
+
+// Gets the element type of a `std::unique_ptr` with the default deleter, e.g., `Buzz` for
+// `std::unique_ptr<Buzz>`
+fn unique_ptr_element(type_name: &str) -> Option<&str> {
+    let element = type_name
+        .strip_prefix("std::unique_ptr<")?
+        .strip_suffix('>')?
+        .trim();
+    let top_level_comma = element.char_indices().any(|(i, c)| {
+        c == ',' && element[..i].matches('<').count() == element[..i].matches('>').count()
+    });
+    (!top_level_comma && !element.ends_with(']')).then_some(element)
+}
+
+fn capitalize_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// Wraps a MOCK_METHOD after the method name and after the argument list, aligning the
+// continuation lines after the opening parenthesis. The argument list gets one argument per
+// line if it is still too wide.
+fn wrap_mock_method(
+    prefix: &str,
+    signature: &str,
+    arguments: &[String],
+    qualifiers: &str,
+    width: usize,
+) -> Vec<String> {
+    let align = " ".repeat(prefix.chars().count());
+    let mut lines = vec![format!("{prefix}{signature}")];
+    let argument_list = format!("{align}({}),", arguments.join(", "));
+    if argument_list.chars().count() <= width || arguments.len() < 2 {
+        lines.push(argument_list);
+    } else {
+        let last = arguments.len() - 1;
+        for (i, argument) in arguments.iter().enumerate() {
+            let open = if i == 0 { "(" } else { " " };
+            let close = if i == last { ")," } else { "," };
+            lines.push(format!("{align}{open}{argument}{close}"));
+        }
+    }
+    lines.push(format!("{align}{qualifiers}"));
+    lines
+}
+
 fn wrap_with_parentheses_if_contains_comma(return_type_or_arg: &str) -> String {
     if return_type_or_arg.contains(',') {
         format!("({return_type_or_arg})")
@@ -157,6 +2249,38 @@ fn wrap_with_parentheses_if_contains_comma(return_type_or_arg: &str) -> String {
     }
 }
 
+// Declares local type aliases for the result and argument types of a method that contain
+// commas, e.g., `using MockFoo_bar_return = std::map<int, int>;`, and returns the method
+// using the aliases. Names already used by other methods in the mock get a numbered suffix,
+// to keep overloads apart.
+fn alias_comma_types(
+    builder: &mut builder::CodeBuilder,
+    method: &model::MethodToMock,
+    mock_name: &str,
+    alias_names: &mut std::collections::HashSet<String>,
+) -> model::MethodToMock {
+    let mut alias = |type_name: &mut String, suffix: &str| {
+        if !type_name.contains(',') {
+            return;
+        }
+        let base = format!("{mock_name}_{}_{suffix}", method.name);
+        let mut name = base.clone();
+        let mut number = 2;
+        while !alias_names.insert(name.clone()) {
+            name = format!("{base}_{number}");
+            number += 1;
+        }
+        builder.add_line(&format!("using {name} = {type_name};"));
+        *type_name = name;
+    };
+    let mut aliased = method.clone();
+    alias(&mut aliased.result_type, "return");
+    for (i, argument) in aliased.arguments.iter_mut().enumerate() {
+        alias(&mut argument.type_name, &format!("arg{i}"));
+    }
+    aliased
+}
+
 fn method_return_type(method: &model::MethodToMock) -> String {
     wrap_with_parentheses_if_contains_comma(&method.result_type)
 }
@@ -165,13 +2289,7 @@ fn method_arguments(method: &model::MethodToMock) -> Vec<String> {
     method
         .arguments
         .iter()
-        .map(|arg| {
-            if let Some(arg_name) = &arg.name {
-                format!("{} {}", arg.type_name, arg_name)
-            } else {
-                arg.type_name.clone()
-            }
-        })
+        .map(|arg| arg.declaration_with_own_name())
         .map(|arg_str| wrap_with_parentheses_if_contains_comma(&arg_str))
         .collect()
 }
@@ -187,8 +2305,118 @@ fn method_qualifiers(method: &model::MethodToMock) -> Vec<String> {
     if method.is_noexcept {
         qualifiers.push("noexcept".to_string());
     }
+    if let Some(calltype) = &method.calltype {
+        qualifiers.push(format!("Calltype({calltype})"));
+    }
     if method.is_virtual {
         qualifiers.push("override".to_string());
     }
     qualifiers
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifiers_are_validated() {
+        assert!(is_identifier("MOCK_METHOD"));
+        assert!(is_identifier("_my_mock2"));
+        assert!(!is_identifier(""));
+        assert!(!is_identifier("2MOCK"));
+        assert!(!is_identifier("MY-MOCK"));
+        assert!(!is_identifier("MOCK METHOD"));
+    }
+
+    #[test]
+    fn line_directives_following_mocked_declarations_are_restored() {
+        let mut generator = Generator::new(MethodsToMockStrategy::AllVirtual);
+        let code = "class MockFoo {\n  #line 4 \"foo.h\"\n  MOCK_METHOD(void, bar, ());\n  \
+                    #line 1 \"\"\n};\n"
+            .to_string();
+        assert_eq!(
+            generator.restore_line_directives(code.clone(), || "MockFoo.h".to_string()),
+            code
+        );
+        generator.line_directives(true);
+        assert_eq!(
+            generator.restore_line_directives(code, || "MockFoo.h".to_string()),
+            "class MockFoo {\n  #line 4 \"foo.h\"\n  MOCK_METHOD(void, bar, ());\n  \
+             #line 5 \"MockFoo.h\"\n};\n"
+        );
+    }
+
+    #[test]
+    fn friend_tests_are_validated() {
+        assert_eq!(friend_test_parts("FooTest.Bar"), Some(("FooTest", "Bar")));
+        assert_eq!(friend_test_parts("FooTest"), None);
+        assert_eq!(friend_test_parts("Foo Test.Bar"), None);
+        assert_eq!(friend_test_parts("FooTest.Bar.Baz"), None);
+        let mut generator = Generator::new(MethodsToMockStrategy::AllVirtual);
+        generator.friend_tests(vec!["FooTest.Bar".to_string(), "Bar".to_string()]);
+        assert_eq!(
+            generator.check(),
+            Err(crate::MocksmithError::InvalidIdentifier("Bar".to_string()))
+        );
+    }
+
+    #[test]
+    fn modules_with_forward_declared_types_are_rejected() {
+        let mut generator = Generator::new(MethodsToMockStrategy::AllVirtual);
+        generator.module_name(Some("mocks".to_string()));
+        assert!(generator.check().is_ok());
+        generator.forward_declare_types(true);
+        assert!(matches!(
+            generator.check(),
+            Err(crate::MocksmithError::IncompatibleOptions(_))
+        ));
+    }
+
+    #[test]
+    fn include_guard_name_uses_path_and_file_of_first_header() {
+        assert_eq!(
+            include_guard_name(
+                "MYPROJECT_{PATH}_{FILE}_MOCK_H_",
+                None,
+                &["foo/bar-baz.h".to_string(), "other.h".to_string()]
+            ),
+            "MYPROJECT_FOO_BAR_BAZ_MOCK_H_"
+        );
+    }
+
+    #[test]
+    fn include_guard_name_uses_project() {
+        assert_eq!(
+            include_guard_name(
+                "{PROJECT}_{PATH}_{FILE}_H_",
+                Some("my-lib"),
+                &["foo/bar.h".to_string()]
+            ),
+            "MY_LIB_FOO_BAR_H_"
+        );
+    }
+
+    #[test]
+    fn include_guard_name_skips_empty_parts() {
+        assert_eq!(
+            include_guard_name("{PATH}_{FILE}_MOCK_H_", None, &["bar.h".to_string()]),
+            "BAR_MOCK_H_"
+        );
+        assert_eq!(
+            include_guard_name("{PATH}_{FILE}_H", None, &["../foo/bar.h".to_string()]),
+            "FOO_BAR_H"
+        );
+        assert_eq!(
+            include_guard_name("{PROJECT}_{PATH}_{FILE}_MOCK_H_", None, &[]),
+            "MOCKS_MOCK_H_"
+        );
+    }
+
+    #[test]
+    fn include_guard_name_keeps_pattern_characters() {
+        assert_eq!(
+            include_guard_name("MY__{FILE}__Mock_h", None, &["bar.h".to_string()]),
+            "MY__BAR__Mock_h"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+// Banner comment at the top of generated files, with variables replaced
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(crate) const DEFAULT_BANNER: &str =
+    "// Automatically generated by Mocksmith (https://github.com/jordfras/mocksmith)";
+
+// Replaces the variables `{SOURCE_FILES}`, `{CLASSES}` and `{VERSION}` in a banner. There
+// is no date variable, since unchanged mocks would then be rewritten and reported as out
+// of date every day. Lines are made comments unless the banner already is a comment.
+pub(crate) fn banner(banner: &str, source_files: &[String], classes: &[&str]) -> String {
+    let text = banner
+        .trim_end()
+        .replace("{SOURCE_FILES}", &source_files.join(", "))
+        .replace("{CLASSES}", &classes.join(", "))
+        .replace("{VERSION}", env!("CARGO_PKG_VERSION"));
+    comment(text)
+}
+
+// Whether a file has the default banner, i.e., was generated without a custom banner
+pub(crate) fn has_default_banner(content: &str) -> bool {
+    content
+        .lines()
+        .any(|line| line.trim_start_matches('\u{feff}').trim_end() == DEFAULT_BANNER)
+}
+
+// Replaces the variables `{YEAR}` and `{FILE_NAME}` in a license header, where the file
+// name is the names of the mocked files without directories. Lines are made comments
+// unless the license already is a comment.
+pub(crate) fn license(license: &str, source_files: &[String]) -> String {
+    let file_names = source_files
+        .iter()
+        .map(|file| {
+            std::path::Path::new(file)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| file.clone())
+        })
+        .collect::<Vec<_>>();
+    let text = license
+        .trim_end()
+        .replace("{YEAR}", &current_date()[..4])
+        .replace("{FILE_NAME}", &file_names.join(", "));
+    comment(text)
+}
+
+fn comment(text: String) -> String {
+    if text.starts_with("//") || text.starts_with("/*") {
+        text
+    } else {
+        text.lines()
+            .map(|line| format!("// {line}").trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// Current UTC date formatted as YYYY-MM-DD, of which the year is used in licenses
+fn current_date() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+// Converts days since 1970-01-01 to a (year, month, day) date in the proleptic Gregorian
+// calendar, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_are_converted_to_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(59), (1970, 3, 1));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(20377), (2025, 10, 16));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn variables_are_replaced_in_banner() {
+        assert_eq!(
+            banner(
+                "Mocks of {CLASSES} in {SOURCE_FILES}\n\nVersion {VERSION}",
+                &["foo.h".to_string(), "bar.h".to_string()],
+                &["Foo", "Bar"]
+            ),
+            format!(
+                "// Mocks of Foo, Bar in foo.h, bar.h\n//\n// Version {}",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn variables_are_replaced_in_license() {
+        let year = &current_date()[..4];
+        assert_eq!(
+            license(
+                "Copyright {YEAR} Acme\n{FILE_NAME}",
+                &["include/foo.h".to_string()]
+            ),
+            format!("// Copyright {year} Acme\n// foo.h")
+        );
+    }
+
+    #[test]
+    fn comments_are_kept_as_is() {
+        assert_eq!(
+            banner("/* Copyright\n * Acme */\n", &[], &[]),
+            "/* Copyright\n * Acme */"
+        );
+        assert_eq!(banner(DEFAULT_BANNER, &[], &[]), DEFAULT_BANNER);
+    }
+}
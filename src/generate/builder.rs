@@ -1,6 +1,7 @@
 // Helper to build a string of code with indentation
 pub(crate) struct CodeBuilder {
     code: String,
+    line_count: usize,
     indent_str: String,
     indent_level: usize,
 }
@@ -9,6 +10,7 @@ impl CodeBuilder {
     pub(crate) fn new(indent_str: String) -> Self {
         CodeBuilder {
             code: String::new(),
+            line_count: 0,
             indent_str,
             indent_level: 0,
         }
@@ -23,11 +25,19 @@ impl CodeBuilder {
         self.indent_level -= 1;
     }
 
+    // Width of the current indentation, in characters
+    pub(crate) fn indent_width(&self) -> usize {
+        self.indent_str.chars().count() * self.indent_level
+    }
+
     pub(crate) fn add_line(&mut self, line: &str) {
-        let indent = self.indent_str.repeat(self.indent_level);
-        self.code.push_str(&indent);
+        if !line.is_empty() {
+            let indent = self.indent_str.repeat(self.indent_level);
+            self.code.push_str(&indent);
+        }
         self.code.push_str(line);
         self.code.push('\n');
+        self.line_count += 1;
     }
 
     pub(crate) fn maybe_add_line(&mut self, line: &Option<String>) {
@@ -38,6 +48,12 @@ impl CodeBuilder {
 
     pub(crate) fn _newline(&mut self) {
         self.code.push('\n');
+        self.line_count += 1;
+    }
+
+    // Number of lines added, which is the index of the next line
+    pub(crate) fn line_count(&self) -> usize {
+        self.line_count
     }
 
     pub(crate) fn build(self) -> String {
@@ -0,0 +1,243 @@
+// Rendering of mocks and headers with user-supplied templates. A template renders the
+// complete mock header, and may define a `mock` macro rendering the code of each mock and
+// a `method` macro rendering the code of each mocked method.
+use crate::model;
+use minijinja::{Environment, State, Value, context};
+
+// Checks that a template can be parsed, to report errors before parsing any headers
+pub(crate) fn check(template: &str) -> crate::Result<()> {
+    environment()
+        .template_from_str(template)
+        .map(|_| ())
+        .map_err(template_error)
+}
+
+// Renders the code for a mock with the `mock` macro of the template. Returns `None` if the
+// template doesn't define the macro, to use the default code.
+pub(crate) fn render_mock(template: &str, class: Value) -> crate::Result<Option<String>> {
+    with_macro(template, "mock", |state| {
+        let mut code = state.call_macro("mock", &[class])?;
+        if !code.ends_with('\n') {
+            code.push('\n');
+        }
+        Ok(code)
+    })
+}
+
+// Renders the code for each mocked method with the `method` macro of the template. Returns
+// `None` if the template doesn't define the macro, to use the default code.
+pub(crate) fn render_methods(
+    template: &str,
+    methods: Vec<Value>,
+) -> crate::Result<Option<Vec<String>>> {
+    with_macro(template, "method", |state| {
+        methods
+            .into_iter()
+            .map(|method| {
+                state
+                    .call_macro("method", &[method])
+                    .map(|code| code.trim_end_matches('\n').to_string())
+            })
+            .collect()
+    })
+}
+
+pub(crate) fn render_header(
+    template: &str,
+    source_files: &[String],
+    type_headers: &[String],
+    banner: &str,
+    mocks: &[crate::Mock],
+) -> crate::Result<String> {
+    let mocks = mocks
+        .iter()
+        .map(|mock| {
+            context! {
+                name => &mock.name,
+                class_name => &mock.parent_name,
+                code => &mock.code,
+                class => &mock.template_class,
+            }
+        })
+        .collect::<Vec<_>>();
+    environment()
+        .template_from_str(template)
+        .and_then(|template| {
+            template.render(context! {
+                source_files => source_files,
+                type_headers => type_headers,
+                banner => banner,
+                mocks => mocks,
+            })
+        })
+        .map_err(template_error)
+}
+
+// Context for the `mock` macro. Each method also gets its mock code as `mock_method`, to
+// be able to only change the layout around the methods.
+pub(crate) fn class_context(
+    class: &model::ClassToMock,
+    mock_name: &str,
+    mock_methods: Vec<String>,
+) -> Value {
+    let methods = class
+        .methods
+        .iter()
+        .zip(mock_methods)
+        .map(|(method, mock_method)| method_context(method, mock_method))
+        .collect::<Vec<_>>();
+    context! {
+        name => mock_name,
+        class_name => &class.name,
+        namespaces => &class.namespaces,
+        methods => methods,
+    }
+}
+
+// Context for the `method` macro, with the default mock code as `mock_method`
+pub(crate) fn method_context(method: &model::MethodToMock, mock_method: String) -> Value {
+    let arguments = method
+        .arguments
+        .iter()
+        .map(|arg| {
+            context! {
+                type_name => &arg.type_name,
+                name => &arg.name,
+            }
+        })
+        .collect::<Vec<_>>();
+    context! {
+        name => &method.name,
+        result_type => &method.result_type,
+        arguments => arguments,
+        is_const => method.is_const,
+        is_virtual => method.is_virtual,
+        is_noexcept => method.is_noexcept,
+        ref_qualifier => &method.ref_qualifier,
+        calltype => &method.calltype,
+        access => method.access.to_string(),
+        mock_method => mock_method,
+    }
+}
+
+// Calls a function with the state of the rendered template, if the template defines a
+// macro with the given name
+fn with_macro<T>(
+    template: &str,
+    name: &str,
+    f: impl FnOnce(&State) -> Result<T, minijinja::Error>,
+) -> crate::Result<Option<T>> {
+    let environment = environment();
+    let template = environment
+        .template_from_str(template)
+        .map_err(template_error)?;
+    let captured = template
+        .render_captured(context! {})
+        .map_err(template_error)?;
+    let state = captured.state();
+    if state.lookup(name).is_none() {
+        return Ok(None);
+    }
+    f(state).map(Some).map_err(template_error)
+}
+
+fn environment() -> Environment<'static> {
+    let mut environment = Environment::new();
+    // Make templates line oriented, like the generated code
+    environment.set_trim_blocks(true);
+    environment.set_lstrip_blocks(true);
+    environment.set_keep_trailing_newline(true);
+    environment
+}
+
+fn template_error(error: minijinja::Error) -> crate::MocksmithError {
+    crate::MocksmithError::TemplateError(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_macro_renders_mock_code() {
+        let template = "\
+{% macro mock(class) %}
+class {{ class.name }} : public {{ class.class_name }}
+{
+{% for method in class.methods %}
+  {{ method.mock_method }}
+{% endfor %}
+};
+{% endmacro %}";
+        let class = context! {
+            name => "MockFoo",
+            class_name => "Foo",
+            methods => vec![context! { mock_method => "MOCK_METHOD(void, bar, (), ());" }],
+        };
+        assert_eq!(
+            render_mock(template, class).unwrap().unwrap(),
+            "class MockFoo : public Foo\n{\n  MOCK_METHOD(void, bar, (), ());\n};\n"
+        );
+    }
+
+    #[test]
+    fn method_macro_renders_method_code() {
+        let template = "\
+{% macro method(method) %}
+// {{ method.name }}
+{{ method.mock_method }}
+{% endmacro %}";
+        let methods = vec![context! {
+            name => "bar",
+            mock_method => "MOCK_METHOD(void, bar, (), ());",
+        }];
+        assert_eq!(
+            render_methods(template, methods).unwrap().unwrap(),
+            vec!["// bar\nMOCK_METHOD(void, bar, (), ());"]
+        );
+        assert_eq!(render_methods("// Header", Vec::new()).unwrap(), None);
+    }
+
+    #[test]
+    fn default_mock_code_is_used_without_mock_macro() {
+        assert_eq!(render_mock("// Header", context! {}).unwrap(), None);
+    }
+
+    #[test]
+    fn header_is_rendered_with_source_files_and_mocks() {
+        let template = "\
+{{ banner }}
+{% for file in source_files + type_headers %}
+#include \"{{ file }}\"
+{% endfor %}
+{% for mock in mocks %}
+// {{ mock.class.methods | length }} methods
+{{ mock.code }}
+{%- endfor %}
+";
+        let mocks = [crate::Mock {
+            code: "class MockFoo {};\n".to_string(),
+            template_class: Some(context! { methods => vec![context! { name => "bar" }] }),
+            ..crate::Mock::test_mock("MockFoo", None)
+        }];
+        assert_eq!(
+            render_header(
+                template,
+                &["foo.h".to_string()],
+                &["bar.h".to_string()],
+                "// Banner",
+                &mocks
+            )
+            .unwrap(),
+            "// Banner\n#include \"foo.h\"\n#include \"bar.h\"\n// 1 methods\nclass MockFoo {};\n"
+        );
+    }
+
+    #[test]
+    fn invalid_template_is_reported() {
+        assert!(matches!(
+            check("{% for %}"),
+            Err(crate::MocksmithError::TemplateError(_))
+        ));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{Mocksmith, MocksmithBuilder, MocksmithError, Result};
+use std::sync::mpsc;
+
+type Job = Box<dyn FnOnce(&Mocksmith) + Send>;
+
+/// Handle to a Mocksmith instance owned by a dedicated worker thread, to use Mocksmith from
+/// several threads.
+///
+/// Since Clang can only be used from one thread at a time, `Mocksmith` can't be sent to
+/// or shared between threads. A handle can be cloned and sent to other threads, and
+/// forwards requests to the worker thread, which handles them one at a time. The worker
+/// thread holds Clang until all handles are dropped.
+///
+/// ```no_run
+/// # use mocksmith::{MocksmithBuilder, MocksmithHandle};
+/// let handle = MocksmithHandle::spawn(MocksmithBuilder::new()).unwrap();
+/// let threads = ["IFoo.h", "IBar.h"].map(|file| {
+///     let handle = handle.clone();
+///     std::thread::spawn(move || {
+///         handle.run(move |mocksmith| mocksmith.create_mock_header_for_files(&[file]))
+///     })
+/// });
+/// ```
+#[derive(Clone)]
+pub struct MocksmithHandle {
+    sender: mpsc::Sender<Job>,
+}
+
+impl MocksmithHandle {
+    /// Starts a worker thread with an instance built by the given builder, see
+    /// `MocksmithBuilder::build_when_available()`. Fails if the instance can't be built.
+    pub fn spawn(builder: MocksmithBuilder) -> Result<Self> {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let (built_sender, built_receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mocksmith = match builder.build_when_available() {
+                Ok(mocksmith) => {
+                    let _ = built_sender.send(Ok(()));
+                    mocksmith
+                }
+                Err(error) => {
+                    let _ = built_sender.send(Err(error));
+                    return;
+                }
+            };
+            // Runs until all handles are dropped
+            for job in receiver {
+                job(&mocksmith);
+            }
+        });
+        built_receiver
+            .recv()
+            .map_err(|_| MocksmithError::Poisoned)??;
+        Ok(Self { sender })
+    }
+
+    /// Calls a function with the instance in the worker thread and returns its result,
+    /// after earlier requests are handled. Fails with `MocksmithError::Poisoned` if the
+    /// worker thread has panicked, e.g., in an earlier function.
+    pub fn run<T>(&self, f: impl FnOnce(&Mocksmith) -> Result<T> + Send + 'static) -> Result<T>
+    where
+        T: Send + 'static,
+    {
+        let (result_sender, result_receiver) = mpsc::channel();
+        self.sender
+            .send(Box::new(move |mocksmith| {
+                let _ = result_sender.send(f(mocksmith));
+            }))
+            .map_err(|_| MocksmithError::Poisoned)?;
+        result_receiver
+            .recv()
+            .map_err(|_| MocksmithError::Poisoned)?
+    }
+}
@@ -27,6 +27,21 @@ pub(crate) fn header_include_path(header: &Path, include_paths: &[PathBuf]) -> S
         .replace('\\', "/")
 }
 
+// Strips a prefix of whole path components from an include path, so "inc" is stripped
+// from "inc/foo.h" but not from "include/foo.h". Returns `None` if the path doesn't start
+// with the prefix.
+pub(crate) fn strip_include_prefix(include_path: &str, prefix: &str) -> Option<String> {
+    Path::new(include_path)
+        .strip_prefix(prefix)
+        .ok()
+        .map(|stripped| {
+            stripped
+                .to_str()
+                .expect("Path should be valid UTF-8")
+                .replace('\\', "/")
+        })
+}
+
 fn canonicalize(path: &Path) -> PathBuf {
     // Use dunce to avoid "verbatim disk" style in Windows if the path exists
     dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
@@ -70,6 +85,28 @@ mod tests {
         assert_eq!(result, "../header.h");
     }
 
+    #[test]
+    fn test_strip_include_prefix() {
+        assert_eq!(
+            strip_include_prefix("inc/foo/bar.h", "inc"),
+            Some("foo/bar.h".to_string())
+        );
+        assert_eq!(
+            strip_include_prefix("inc/foo/bar.h", "inc/"),
+            Some("foo/bar.h".to_string())
+        );
+        assert_eq!(
+            strip_include_prefix("inc/foo/bar.h", "inc/foo"),
+            Some("bar.h".to_string())
+        );
+    }
+
+    #[test]
+    fn test_strip_include_prefix_of_sibling_directory() {
+        assert_eq!(strip_include_prefix("include/foo.h", "inc"), None);
+        assert_eq!(strip_include_prefix("inc/foo/bar.h", "inc/fo"), None);
+    }
+
     #[test]
     #[cfg(windows)]
     fn test_windows_style_paths() {
@@ -1,15 +1,26 @@
+mod builder;
 mod clangwrap;
 mod generate;
+mod handle;
 mod headerpath;
 mod log;
+mod markers;
 mod model;
 pub mod naming;
+mod spans;
 
+pub use builder::MocksmithBuilder;
 use clangwrap::ClangWrap;
-use headerpath::header_include_path;
-use std::path::{Path, PathBuf};
+pub use handle::MocksmithHandle;
+use headerpath::{header_include_path, strip_include_prefix};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 #[derive(thiserror::Error, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum MocksmithError {
     #[error("Another thread is already using Mocksmith")]
     Busy,
@@ -39,10 +50,42 @@ pub enum MocksmithError {
     },
     #[error("No appropriate class to mock was found in the file")]
     NothingToMock,
+    #[error("Invalid template: {0}")]
+    TemplateError(String),
+    #[error("Incompatible options: {0}")]
+    IncompatibleOptions(String),
+    #[error("Invalid identifier: {0}")]
+    InvalidIdentifier(String),
+    #[error("Invalid generated region markers: {0}")]
+    MarkerError(String),
+    #[error("Invalid parser option: {0}")]
+    InvalidParserOption(String),
 }
 
 pub type Result<T> = std::result::Result<T, MocksmithError>;
 
+impl MocksmithError {
+    /// Formats the error like a compiler diagnostic, e.g.,
+    /// `file:line:column: error: message` for parse errors in files, so editors can
+    /// navigate to the location. ANSI color codes are added if `color` is true.
+    pub fn diagnostic(&self, color: bool) -> String {
+        match self {
+            MocksmithError::ParseError {
+                message,
+                file: Some(file),
+                line,
+                column,
+            } => log::format_diagnostic(
+                Severity::Error,
+                Some(&log::location(file, *line, Some(*column))),
+                message,
+                color,
+            ),
+            _ => log::format_diagnostic(Severity::Error, None, &self.to_string(), color),
+        }
+    }
+}
+
 /// Enum to control which methods to mock in a class.
 #[derive(Clone, Copy, Debug)]
 pub enum MethodsToMockStrategy {
@@ -54,8 +97,243 @@ pub enum MethodsToMockStrategy {
     OnlyPureVirtual,
 }
 
-/// Representation of a mock produced by Mocksmith.
+/// Enum to control the style of the generated code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum MockStyle {
+    /// Google Mock mocks using `MOCK_METHOD`.
+    Gmock,
+    /// Plain fake classes implementing methods with trivial bodies, returning default
+    /// constructed values.
+    Fake,
+    /// FakeIt mocks deriving `fakeit::Mock`, faking all methods on construction.
+    FakeIt,
+}
+
+/// Enum to control the smart pointer returned by factory helpers of mocks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum FactoryPointer {
+    /// Factory helpers return `std::unique_ptr`.
+    Unique,
+    /// Factory helpers return `std::shared_ptr`.
+    Shared,
+}
+
+/// Enum to control the language to parse source header files as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Language {
+    /// Headers are parsed as C++.
+    Cpp,
+    /// Headers are parsed as Objective-C++, and may contain Objective-C.
+    ObjectiveCpp,
+}
+
+impl Language {
+    // Name of the language for clang's --language argument
+    pub(crate) fn clang_name(self) -> &'static str {
+        match self {
+            Language::Cpp => "c++",
+            Language::ObjectiveCpp => "objective-c++",
+        }
+    }
+}
+
+/// Enum to control the line endings of generated files.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum LineEnding {
+    /// Lines end with `\n`.
+    Lf,
+    /// Lines end with `\r\n`.
+    Crlf,
+}
+
+/// Enum to control how nice and strict mocks are returned by helpers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum HelperAllocation {
+    /// Helpers return mocks by value, which requires C++17.
+    Value,
+    /// Helpers return heap allocated mocks in `std::unique_ptr`.
+    Heap,
+}
+
+/// Information about a method in a class to mock, used to filter which methods to mock.
+#[derive(Debug)]
+pub struct MethodInfo<'a> {
+    /// Name of the class the method belongs to
+    pub class_name: &'a str,
+    /// Name of the method
+    pub name: &'a str,
+    /// Signature of the method in the same form as a C++ function type, e.g.,
+    /// `int (const std::string &, bool) const`
+    pub signature: String,
+    /// Whether the method is const qualified
+    pub is_const: bool,
+    /// Whether the method is virtual
+    pub is_virtual: bool,
+    /// Whether the method is noexcept
+    pub is_noexcept: bool,
+    /// Ref qualifier of the method, i.e., `&` or `&&`, if any
+    pub ref_qualifier: Option<&'a str>,
+}
+
+/// Information about a class that can be mocked, when listing the classes in a header
+/// rather than generating mocks. Fields may be added in minor releases.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ClassInfo {
+    /// Name of the class qualified with its namespaces, e.g., `foo::Bar`
+    pub qualified_name: String,
+    /// File declaring the class
+    pub file: Option<PathBuf>,
+    /// Line of the class declaration
+    pub line: u32,
+    /// Number of methods mocked with `MethodsToMockStrategy::All`, i.e., non-static methods
+    pub methods: usize,
+    /// Number of methods mocked with `MethodsToMockStrategy::AllVirtual`
+    pub virtual_methods: usize,
+    /// Number of methods mocked with `MethodsToMockStrategy::OnlyPureVirtual`
+    pub pure_virtual_methods: usize,
+}
+
+/// A class to mock parsed from a source header, with the methods to mock after applying
+/// filters, see `Mocksmith::parse_file()`. Fields may be added in minor releases.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ParsedClass {
+    /// Name of the class, without namespaces
+    pub name: String,
+    /// Namespaces enclosing the class, outermost first
+    pub namespaces: Vec<String>,
+    /// File declaring the class
+    pub file: Option<PathBuf>,
+    /// Line of the class declaration, 0 if unknown
+    pub line: u32,
+    /// Methods to mock
+    pub methods: Vec<ParsedMethod>,
+    /// Names of methods declared in the class that are not mocked, with the reasons
+    pub skipped_methods: Vec<(String, String)>,
+}
+
+impl ParsedClass {
+    /// Name of the class qualified with its namespaces, e.g., `foo::Bar`.
+    pub fn qualified_name(&self) -> String {
+        self.namespaces
+            .iter()
+            .chain(std::iter::once(&self.name))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+}
+
+/// A method to mock in a parsed class. Fields may be added in minor releases.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ParsedMethod {
+    pub name: String,
+    pub result_type: String,
+    pub arguments: Vec<ParsedArgument>,
+    pub is_const: bool,
+    pub is_virtual: bool,
+    pub is_pure_virtual: bool,
+    pub is_noexcept: bool,
+    pub is_deprecated: bool,
+    /// Ref qualifier of the method, i.e., `&` or `&&`, if any
+    pub ref_qualifier: Option<String>,
+    /// Calling convention of COM interface methods, e.g., `STDMETHODCALLTYPE`, if any
+    pub calltype: Option<String>,
+    /// Access specifier of the method, e.g., `public`
+    pub access: String,
+    /// Standard attributes of the method, e.g., `nodiscard`, without brackets
+    pub attributes: Vec<String>,
+    /// File declaring the method
+    pub file: Option<PathBuf>,
+    /// Line of the method declaration, 0 if unknown
+    pub line: u32,
+}
+
+/// An argument of a parsed method. Fields may be added in minor releases.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ParsedArgument {
+    /// Type of the argument, e.g., `const std::string &`
+    pub type_name: String,
+    /// Name of the argument, if named in the declaration
+    pub name: Option<String>,
+}
+
+/// Severity of a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Severity {
+    Note,
+    Warning,
+    Error,
+    Fatal,
+}
+
+/// What reported a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum DiagnosticSource {
+    /// The Clang parser, e.g., a parse error in a source header
+    Clang,
+    /// Mocksmith itself, e.g., a warning about a mock that can't be used as expected
+    Mocksmith,
+}
+
+/// A diagnostic reported when parsing source headers or generating mocks, see
+/// `Mocksmith::diagnostic_fun()`. Fields may be added in minor releases.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// File the diagnostic is located in, if known
+    pub file: Option<PathBuf>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub source: DiagnosticSource,
+}
+
+/// Phase of creating mocks from a file, timed when collecting timings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Phase {
+    /// Parsing the file with Clang
+    Parse,
+    /// Finding the classes and methods to mock in the parsed file
+    Traversal,
+    /// Generating the code of mocks and mock headers
+    Generation,
+}
+
+/// Time spent in a phase of creating mocks, see `Mocksmith::collect_timings()`. Fields
+/// may be added in minor releases.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Timing {
+    /// File the mocks are created from, None when parsing a string
+    pub file: Option<PathBuf>,
+    pub phase: Phase,
+    pub duration: Duration,
+}
+
+/// Representation of a mock produced by Mocksmith. Fields may be added in minor releases,
+/// so mocks can only be created by Mocksmith.
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct Mock {
     /// Path to the header file of the mocked class
     pub source_file: Option<PathBuf>,
@@ -65,15 +343,93 @@ pub struct Mock {
     pub name: String,
     /// Code for the mock
     pub code: String,
+    /// Whether any of the mocked methods is marked as deprecated
+    pub has_deprecated_methods: bool,
+    /// Out-of-line definitions of the constructor and destructor of the mock, for a
+    /// source file, when mocks are split into header and source files
+    pub definitions: Option<String>,
+    /// Skeleton of a test fixture for the mocked class, without includes, when fixtures
+    /// are emitted
+    pub fixture: Option<String>,
+    /// Static assertions checking that the signatures of the mocked methods are unchanged,
+    /// without includes, when generating signature checks
+    pub signature_checks: Option<String>,
+    /// Markdown listing `EXPECT_CALL` snippets with placeholder matchers for the mocked
+    /// methods, when emitting cheat sheets
+    pub cheat_sheet: Option<String>,
+    /// Markdown section documenting the mocked class, the mocked methods and the skipped
+    /// methods, when emitting documentation
+    pub documentation: Option<String>,
+    /// Paths to headers declaring types used in the signatures of the mocked methods,
+    /// other than the header of the mocked class and system headers
+    pub type_headers: Vec<PathBuf>,
+    /// Forward declarations of types only used by pointer or reference in the signatures
+    /// of the mocked methods, replacing headers in `type_headers`, when forward
+    /// declaring types
+    pub forward_declarations: Vec<String>,
+    /// SHA-256 hash, as hex digits, of the declaration of the mocked class as parsed, to
+    /// detect changes of the class without generating the mock
+    pub source_hash: String,
+    /// Paths to the mocked header and all headers it includes, directly or indirectly, as
+    /// visited when parsing, e.g., for build system dependency files. Empty when parsing
+    /// from a string.
+    pub dependencies: Vec<PathBuf>,
+    // Context of the mocked class for the header template, when using a template
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) template_class: Option<minijinja::Value>,
 }
 
-/// Representation of a mock header produced by Mocksmith.
+#[cfg(test)]
+impl Mock {
+    // Mock without code, for unit tests of functions taking mocks
+    pub(crate) fn test_mock(name: &str, source_file: Option<&str>) -> Self {
+        Self {
+            source_file: source_file.map(PathBuf::from),
+            parent_name: String::new(),
+            name: name.to_string(),
+            code: String::new(),
+            has_deprecated_methods: false,
+            definitions: None,
+            fixture: None,
+            signature_checks: None,
+            cheat_sheet: None,
+            documentation: None,
+            type_headers: Vec::new(),
+            forward_declarations: Vec::new(),
+            source_hash: String::new(),
+            dependencies: Vec::new(),
+            template_class: None,
+        }
+    }
+}
+
+/// Location of a part of the code of a mock header. Fields may be added in minor
+/// releases.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Span {
+    /// Range of bytes in the code, including the line ending of the last line
+    pub bytes: std::ops::Range<usize>,
+    /// Range of line numbers, starting at 1
+    pub lines: std::ops::Range<usize>,
+}
+
+/// Representation of a mock header produced by Mocksmith. Fields may be added in minor
+/// releases.
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct MockHeader {
     /// The mocks within the header
     pub mocks: Vec<Mock>,
     /// Code for the complete mock header
     pub code: String,
+    /// Location of the code of each mock in `code`, in the same order as `mocks`. None if
+    /// the header is laid out by a template.
+    pub mock_spans: Vec<Option<Span>>,
+    /// Location of the include directives in `code`, if any
+    pub includes_span: Option<Span>,
 }
 
 impl crate::MockHeader {
@@ -81,19 +437,41 @@ impl crate::MockHeader {
         Self {
             mocks: Vec::new(),
             code: String::new(),
+            mock_spans: Vec::new(),
+            includes_span: None,
         }
     }
 }
 
+type NameFun = Box<dyn Fn(&str) -> String>;
+type NameOutputFileFun = Box<dyn Fn(&MockHeader) -> String>;
+
 /// Mocksmith is a struct for generating Google Mock mocks for C++ classes.
 pub struct Mocksmith {
     clangwrap: ClangWrap,
     generator: generate::Generator,
 
     include_paths: Vec<PathBuf>,
+    include_prefix: Option<String>,
+    strip_include_prefix: Option<String>,
     methods_to_mock: MethodsToMockStrategy,
+    com_interfaces: bool,
+    qt_macros: bool,
+    skip_deprecated: bool,
+    reference_default_actions: bool,
     filter_class: Box<dyn Fn(&str) -> bool>,
-    name_mock: Box<dyn Fn(&str) -> String>,
+    filter_qualified_class: Box<dyn Fn(&str) -> bool>,
+    exclude_class: Box<dyn Fn(&str) -> bool>,
+    filter_method: Box<dyn Fn(&MethodInfo) -> bool>,
+    style: MockStyle,
+    name_mock: Option<NameFun>,
+    name_output_file: Option<NameOutputFileFun>,
+    extract_interface: bool,
+    include_type_headers: bool,
+    fully_qualify_types: bool,
+    sort_output: bool,
+    collect_timings: bool,
+    timings: RefCell<Vec<Timing>>,
 }
 
 impl Mocksmith {
@@ -112,10 +490,10 @@ impl Mocksmith {
     /// instance before returning since Clang can only be used from one thread. If a
     /// thread using Mocksmith panics, the poisoning is cleared.
     pub fn new_when_available() -> Result<Self> {
-        let mut clangwrap = ClangWrap::blocking_new();
+        let mut clangwrap = ClangWrap::blocking_new(None);
         while let Err(MocksmithError::Poisoned) = clangwrap {
             ClangWrap::clear_poison();
-            clangwrap = ClangWrap::blocking_new();
+            clangwrap = ClangWrap::blocking_new(None);
         }
         Self::create(clangwrap?)
     }
@@ -126,9 +504,26 @@ impl Mocksmith {
             clangwrap,
             generator: generate::Generator::new(methods_to_mock),
             include_paths: Vec::new(),
+            include_prefix: None,
+            strip_include_prefix: None,
             methods_to_mock,
+            com_interfaces: false,
+            qt_macros: false,
+            skip_deprecated: false,
+            reference_default_actions: false,
             filter_class: Box::new(|_| true),
-            name_mock: Box::new(naming::default_name_mock),
+            filter_qualified_class: Box::new(|_| true),
+            exclude_class: Box::new(|_| false),
+            filter_method: Box::new(|_| true),
+            style: MockStyle::Gmock,
+            name_mock: None,
+            name_output_file: None,
+            extract_interface: false,
+            include_type_headers: false,
+            fully_qualify_types: false,
+            sort_output: false,
+            collect_timings: false,
+            timings: RefCell::new(Vec::new()),
         };
         Ok(mocksmith)
     }
@@ -150,6 +545,42 @@ impl Mocksmith {
         self
     }
 
+    /// Adds system include paths to search for headers, like the `-isystem` compiler
+    /// option, e.g., for third-party libraries. Warnings in headers found in these paths
+    /// are suppressed, and the paths are not used to determine how to include the mocked
+    /// headers.
+    pub fn system_include_paths(mut self, system_include_paths: &[PathBuf]) -> Self {
+        self.clangwrap
+            .add_system_include_paths(system_include_paths);
+        self
+    }
+
+    /// Adds a file that only exists in memory, to be found by Clang when parsing source
+    /// headers, e.g., a header included by a string given to
+    /// `create_mocks_from_string()`. The path is the path of an include path joined with
+    /// the name of the included header, e.g., `include/types.h` for `#include "types.h"`
+    /// with the include path `include`. The directory of the path should exist. Virtual
+    /// files hide files on disk with the same paths.
+    pub fn virtual_file<P>(mut self, path: P, content: &str) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.clangwrap
+            .add_virtual_file(path.as_ref().to_path_buf(), content.to_string());
+        self
+    }
+
+    /// Returns the version of the loaded libclang, e.g., `clang version 18.1.3`.
+    pub fn clang_version(&self) -> String {
+        self.clangwrap.version()
+    }
+
+    /// Returns the arguments passed to Clang when parsing headers, with the current
+    /// settings, e.g., include paths, language and C++ standard.
+    pub fn clang_arguments(&self) -> Vec<String> {
+        self.clangwrap.clang_arguments(&self.include_paths)
+    }
+
     /// Sets which methods to mock in the classes. Default is `AllVirtual`, which mocks
     /// all virtual methods.
     pub fn methods_to_mock(mut self, methods: MethodsToMockStrategy) -> Self {
@@ -165,6 +596,32 @@ impl Mocksmith {
         self
     }
 
+    /// Sets a function to filter which classes to mock by their names qualified with
+    /// namespaces, e.g., `foo::Bar`, applied in addition to the class filter. The function
+    /// should return `true` if the class should be mocked.
+    pub fn qualified_class_filter_fun(mut self, filter: impl Fn(&str) -> bool + 'static) -> Self {
+        self.filter_qualified_class = Box::new(filter);
+        self
+    }
+
+    /// Sets a function to exclude classes from mocking, applied after the class filter.
+    /// The function takes the name of a class and should return `true` if the class
+    /// should not be mocked, e.g., to mock all classes matching `I.*` except `IInternal.*`.
+    pub fn class_exclude_fun(mut self, exclude: impl Fn(&str) -> bool + 'static) -> Self {
+        self.exclude_class = Box::new(exclude);
+        self
+    }
+
+    /// Sets a function to filter which methods to mock. The function takes information
+    /// about a method and should return `true` if the method should be mocked. Methods
+    /// that are not mocked are inherited from the mocked class, making partial mocks, and a
+    /// warning is logged for pure virtual methods that are not mocked since the mock is
+    /// then abstract. Classes without any methods left to mock are not mocked.
+    pub fn method_filter_fun(mut self, filter: impl Fn(&MethodInfo) -> bool + 'static) -> Self {
+        self.filter_method = Box::new(filter);
+        self
+    }
+
     /// Errors detected by Clang during parsing normally causes mock generation to fail.
     /// Setting this option disables which may be useful, e.g., when not able to provide
     /// all the include paths. Beware that this may lead to unknown types in arguments
@@ -175,6 +632,47 @@ impl Mocksmith {
         self
     }
 
+    /// Sets whether to include the mocked headers with angle brackets rather than quotes
+    /// in mock headers. Default is false.
+    pub fn include_with_angle_brackets(mut self, value: bool) -> Self {
+        self.generator.include_with_angle_brackets(value);
+        self
+    }
+
+    /// Sets whether to include the headers declaring types used in the signatures of
+    /// mocked methods in mock headers, rather than relying on the mocked headers including
+    /// them. Types declared in system headers are not considered. Default is false.
+    pub fn include_type_headers(mut self, value: bool) -> Self {
+        self.include_type_headers = value;
+        self
+    }
+
+    /// Sets whether to forward declare classes only used by pointer or reference in the
+    /// signatures of mocked methods, rather than including their headers, when including
+    /// headers with `include_type_headers()`. Headers are still included if any type
+    /// from them is used by value. Default is false.
+    pub fn forward_declare_types(mut self, value: bool) -> Self {
+        self.generator.forward_declare_types(value);
+        self
+    }
+
+    /// Sets a prefix to prepend to the paths of the mocked headers when including them in
+    /// mock headers, e.g., "mylib" to include `mylib/ifoo.h` rather than `ifoo.h`. It is
+    /// applied after stripping any prefix set with `strip_include_prefix()`. Default is
+    /// None.
+    pub fn include_prefix(mut self, prefix: Option<String>) -> Self {
+        self.include_prefix = prefix;
+        self
+    }
+
+    /// Sets a prefix to strip from the paths of the mocked headers when including them in
+    /// mock headers, e.g., "src" to include `ifoo.h` rather than `src/ifoo.h`. Paths
+    /// without the prefix are kept as is. Default is None.
+    pub fn strip_include_prefix(mut self, prefix: Option<String>) -> Self {
+        self.strip_include_prefix = prefix;
+        self
+    }
+
     /// Sets the C++ standard to use when parsing the source header files. Default is
     /// "c++17".
     pub fn cpp_standard(mut self, standard: Option<String>) -> Self {
@@ -182,26 +680,234 @@ impl Mocksmith {
         self
     }
 
+    /// Sets the language to parse the source header files as. Headers parsed as
+    /// Objective-C++ may contain Objective-C, but only C++ classes are mocked. Default is
+    /// `Language::Cpp`.
+    pub fn language(mut self, language: Language) -> Self {
+        self.clangwrap.set_language(language);
+        self
+    }
+
     /// Sets additional arguments to the clang C++ parser.
     pub fn additional_clang_args(mut self, args: Vec<String>) -> Self {
         self.clangwrap.set_additional_clang_args(args);
         self
     }
 
+    /// Sets the system root directory to parse source headers with, e.g., of a
+    /// cross-compilation toolchain. The directory must exist. Detected system include
+    /// paths and system root are not used with a system root, see
+    /// `detect_system_includes()`. Default is none.
+    pub fn sysroot(mut self, sysroot: Option<PathBuf>) -> Self {
+        self.clangwrap.set_sysroot(sysroot);
+        self
+    }
+
+    /// Sets the target triple to parse source headers for, e.g., `aarch64-linux-gnu`.
+    /// Detected system include paths and system root, which are of the host, are not used
+    /// with a target. Default is the host target.
+    pub fn target(mut self, target: Option<String>) -> Self {
+        self.clangwrap.set_target(target);
+        self
+    }
+
+    /// Sets headers to include before the content of each source header when parsing,
+    /// like the `-include` compiler option, e.g., a `config.h` with macros needed to parse
+    /// the source headers. Headers are searched for like with `#include "..."`. Default is
+    /// none.
+    pub fn force_includes(mut self, files: Vec<PathBuf>) -> Self {
+        self.clangwrap.set_force_includes(files);
+        self
+    }
+
+    /// Sets macros to define when parsing source headers, like the `-D` compiler option,
+    /// as `NAME` or `NAME=VALUE`. Default is none.
+    pub fn define_macros(mut self, definitions: Vec<String>) -> Self {
+        self.clangwrap.set_macro_definitions(definitions);
+        self
+    }
+
+    /// Sets macros to undefine when parsing source headers, like the `-U` compiler option.
+    /// Macros are undefined after the macros set with `define_macros()` are defined.
+    /// Default is none.
+    pub fn undefine_macros(mut self, names: Vec<String>) -> Self {
+        self.clangwrap.set_undefined_macros(names);
+        self
+    }
+
+    /// Sets a function called with each diagnostic reported when parsing source headers,
+    /// e.g., parse errors and Clang warnings, and when generating mocks, e.g., warnings
+    /// about abstract mocks. Diagnostics are reported whether they are logged or not, so
+    /// tools can present them.
+    pub fn diagnostic_fun(mut self, fun: impl Fn(&Diagnostic) + 'static) -> Self {
+        self.clangwrap.set_diagnostic_fun(Box::new(fun));
+        self
+    }
+
+    /// Sets whether to collect the time spent parsing files, finding the classes to mock
+    /// and generating code, to profile slow runs. The timings are returned by
+    /// `take_timings()`. Default is false.
+    pub fn collect_timings(mut self, value: bool) -> Self {
+        self.collect_timings = value;
+        self
+    }
+
+    /// Returns the timings collected since the last call, see `collect_timings()`.
+    pub fn take_timings(&self) -> Vec<Timing> {
+        self.timings.take()
+    }
+
+    /// Sets whether to collect all diagnostics reported when parsing source headers and
+    /// generating mocks, see `diagnostic_fun()`. Parsing fails with the first error, but
+    /// all errors and warnings are collected, to show them all at once. The diagnostics
+    /// are returned by `take_diagnostics()`. Default is false.
+    pub fn collect_diagnostics(mut self, value: bool) -> Self {
+        self.clangwrap.set_collect_diagnostics(value);
+        self
+    }
+
+    /// Returns the diagnostics collected since the last call, whether creating mocks
+    /// succeeded or failed, see `collect_diagnostics()`.
+    pub fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        self.clangwrap.take_diagnostics()
+    }
+
+    /// Sets whether to color diagnostics logged, e.g., parse errors and warnings, with ANSI
+    /// escape codes. Diagnostics are formatted like compiler output,
+    /// `file:line:column: warning: message`. Default is false.
+    pub fn color_diagnostics(mut self, value: bool) -> Self {
+        self.clangwrap.set_color_diagnostics(value);
+        self
+    }
+
+    /// Sets whether to add the include paths in the `CPATH` and `CPLUS_INCLUDE_PATH`
+    /// environment variables to the arguments to the clang C++ parser, like compilers do.
+    /// Default is false.
+    pub fn use_env_includes(mut self, value: bool) -> Self {
+        self.clangwrap.set_use_env_includes(value);
+        self
+    }
+
+    /// Sets whether to detect the system include paths, e.g., of the C++ standard library,
+    /// and add them to the arguments to the clang C++ parser. The paths are taken from the
+    /// `INCLUDE` environment variable of a Visual Studio developer prompt on Windows, and
+    /// otherwise from the default include paths of clang++, if installed. On macOS, the SDK
+    /// path from `xcrun --show-sdk-path` is also passed as `-isysroot`, unless a system root
+    /// is given in the additional clang arguments. The detected paths are not used with a
+    /// system root or a target, see `sysroot()` and `target()`. Detection is done when
+    /// calling this function. Default is false.
+    pub fn detect_system_includes(mut self, value: bool) -> Self {
+        self.clangwrap.set_detect_system_includes(value);
+        self
+    }
+
+    /// Sets whether to predefine lightweight versions of Qt macros like `Q_OBJECT`,
+    /// `signals`, `slots` and `Q_DECL_*`, so QObject derived classes can be parsed.
+    /// Methods in signal sections and the meta-object methods declared by `Q_OBJECT`, i.e.,
+    /// `metaObject`, `qt_metacast` and `qt_metacall`, are then never mocked since they are
+    /// implemented by moc. Default is false.
+    pub fn qt_macros(mut self, value: bool) -> Self {
+        self.qt_macros = value;
+        self.clangwrap.set_qt_macros(value);
+        self
+    }
+
+    /// Sets whether to mock COM interfaces, e.g., declared with `MIDL_INTERFACE` and
+    /// `STDMETHOD`. Structs are then also considered for mocking, the COM macros are
+    /// predefined if not parsing for Windows, and mocks for the `IUnknown` methods are
+    /// added to mocks of interfaces that directly inherit `IUnknown`. Default is false.
+    pub fn com_interfaces(mut self, value: bool) -> Self {
+        self.com_interfaces = value;
+        self.clangwrap.set_com_interfaces(value);
+        self.generator.com_interfaces(value);
+        self
+    }
+
     /// For easy testability of parser warnings.
     pub fn parse_function_bodies(mut self, value: bool) -> Self {
         self.clangwrap.set_parse_function_bodies(value);
         self
     }
 
+    /// Sets whether to skip mocking methods marked as deprecated. Classes with only
+    /// deprecated methods to mock are then not mocked at all. Default is false.
+    pub fn skip_deprecated_methods(mut self, value: bool) -> Self {
+        self.skip_deprecated = value;
+        self
+    }
+
     /// Sets whether to add MSVC pragma to allow overriding methods marked as deprecated.
     /// If it is not added mocked methods marked as deprecated will cause compilation
-    /// warnings. The pragma is only added when generating headers. Default is false.
+    /// warnings. The pragma is only added when generating headers. It is always added
+    /// when any mocked method is marked as deprecated. Default is false.
     pub fn msvc_allow_overriding_deprecated_methods(mut self, value: bool) -> Self {
         self.generator.add_deprecation_pragma(value);
         self
     }
 
+    /// Sets whether to add GCC diagnostic pragmas, also understood by Clang, to allow
+    /// overriding methods marked as deprecated. If it is not added mocked methods marked
+    /// as deprecated will cause compilation warnings with `-Wdeprecated-declarations`. The
+    /// pragmas are only added when generating headers. Default is false.
+    pub fn gcc_allow_overriding_deprecated_methods(mut self, value: bool) -> Self {
+        self.generator.add_gcc_deprecation_pragma(value);
+        self
+    }
+
+    /// Sets whether to add Clang and GCC diagnostic pragmas disabling warnings commonly
+    /// triggered by mocks in strict builds, e.g., `-Wsuggest-override` and
+    /// `-Wweak-vtables`. The pragmas are only added when generating headers. Default is
+    /// false.
+    pub fn suppress_mock_warnings(mut self, value: bool) -> Self {
+        self.generator.add_warning_suppression_pragma(value);
+        self
+    }
+
+    /// Sets whether to surround the code of generated headers and source files with
+    /// `// NOLINTBEGIN` and `// NOLINTEND` comments, to not fail clang-tidy checks on
+    /// generated code. With an empty list all checks are suppressed, otherwise only the
+    /// given checks, e.g., `modernize-*`. Default is None, i.e., no comments.
+    pub fn nolint_checks(mut self, checks: Option<Vec<String>>) -> Self {
+        self.generator.nolint_checks(checks);
+        self
+    }
+
+    /// Sets whether to add a comment with a hash of the mocked class declarations, e.g.,
+    /// `// mocksmith-source-sha256: ...`, after the banner of generated headers. The hash
+    /// makes it possible to detect stale mocks without parsing the source files. Default
+    /// is false.
+    pub fn source_hash_comment(mut self, value: bool) -> Self {
+        self.generator.source_hash_comment(value);
+        self
+    }
+
+    /// Sets a preprocessor macro, e.g., `UNIT_TEST`, that must be defined for the code of
+    /// generated headers and source files to be compiled. The code is wrapped in
+    /// `#if defined(UNIT_TEST)` and `#endif`, to be able to keep mocks alongside production
+    /// code. Default is None, i.e., no guard.
+    pub fn guard_macro(mut self, guard_macro: Option<String>) -> Self {
+        self.generator.guard_macro(guard_macro);
+        self
+    }
+
+    /// Sets a name of a C++20 module, e.g., `myproject.mocks`, to generate module interface
+    /// units exporting the mocks instead of headers. The headers of the mocked classes and
+    /// gmock are included in the global module fragment. Creating mocks fails if combined
+    /// with `guard_macro()` or `assert_not_abstract()`. Default is None, i.e., headers
+    /// are generated.
+    pub fn module_name(mut self, module_name: Option<String>) -> Self {
+        self.generator.module_name(module_name);
+        self
+    }
+
+    /// Controls whether to import gmock as a header unit, `import <gmock/gmock.h>;`, in
+    /// module interface units rather than including it in the global module fragment.
+    /// This requires a build system compiling gmock as a header unit. Default is false.
+    pub fn import_gmock(mut self, value: bool) -> Self {
+        self.generator.import_gmock(value);
+        self
+    }
+
     /// Controls whether to use C++17 style nested namespace declarations with colon
     /// separation or older style. Default is true.
     pub fn simplified_nested_namespaces(mut self, value: bool) -> Self {
@@ -209,15 +915,348 @@ impl Mocksmith {
         self
     }
 
+    /// Sets an extra namespace, e.g., "testing", to put mocks in, nested inside the
+    /// namespaces of the mocked classes, e.g., `myns::testing` for a class in `myns`. This
+    /// prevents mocks from colliding with other symbols, e.g., in unity builds. The mocked
+    /// classes are then referred to with fully qualified names. Default is None.
+    pub fn mock_namespace(mut self, namespace: Option<String>) -> Self {
+        self.generator.mock_namespace(namespace);
+        self
+    }
+
+    /// Sets whether to mock methods under the same access specifiers as in the mocked
+    /// classes, e.g., mocking protected methods under `protected:`. Otherwise all methods
+    /// are mocked as public, as recommended for gmock. Default is false.
+    pub fn preserve_access(mut self, value: bool) -> Self {
+        self.generator.preserve_access(value);
+        self
+    }
+
+    /// Sets tests to befriend in each mock with gtest's `FRIEND_TEST`, given as
+    /// `TestSuite.TestName`, so the tests can call protected and private methods mocked
+    /// with `preserve_access()`. The mock headers include `gtest/gtest_prod.h`. Default is
+    /// none.
+    pub fn friend_tests(mut self, tests: Vec<String>) -> Self {
+        self.generator.friend_tests(tests);
+        self
+    }
+
+    /// Sets whether to generate a skeleton of a gtest fixture for each mock, available with
+    /// `create_fixture_for_mock()`. Default is false.
+    pub fn emit_fixture(mut self, value: bool) -> Self {
+        self.generator.emit_fixture(value);
+        self
+    }
+
+    /// Sets whether to emit a cheat sheet for each gmock mock, a markdown file listing an
+    /// `EXPECT_CALL` with placeholder matchers for each mocked method, see
+    /// `create_cheat_sheet_for_mock()`. Default is false.
+    pub fn emit_cheat_sheet(mut self, value: bool) -> Self {
+        self.generator.emit_cheat_sheet(value);
+        self
+    }
+
+    /// Sets whether to emit markdown documentation of the mocked classes, see
+    /// `create_documentation_for_header()`. Default is false.
+    pub fn emit_documentation(mut self, value: bool) -> Self {
+        self.generator.emit_documentation(value);
+        self
+    }
+
+    /// Sets whether to rewrite the types of arguments and return values to fully qualified
+    /// spellings in mocks, e.g., `foo::Bar` rather than `Bar`, for mocked headers relying
+    /// on `using namespace` directives. Default is false.
+    pub fn fully_qualify_types(mut self, value: bool) -> Self {
+        self.fully_qualify_types = value;
+        self
+    }
+
+    /// Sets whether to sort mocks by the qualified names of the mocked classes, and mocked
+    /// methods by name and signature, rather than keeping the order of the mocked
+    /// headers. This keeps generated code stable when headers are reorganized. Default is
+    /// false.
+    pub fn sort_output(mut self, value: bool) -> Self {
+        self.sort_output = value;
+        self
+    }
+
     /// Sets the string to use for indentation for the generated code. Default is 2 spaces.
     pub fn indent_str(mut self, indent: String) -> Self {
         self.generator.indent_str(indent);
         self
     }
 
+    /// Sets a column limit for the generated code. `MOCK_METHOD` lines exceeding the limit
+    /// are wrapped after the method name and the argument list, and with one argument per
+    /// line if needed. Default is None, i.e., no wrapping.
+    pub fn column_limit(mut self, limit: Option<usize>) -> Self {
+        self.generator.column_limit(limit);
+        self
+    }
+
+    /// Sets whether to reproduce standard attributes of mocked methods, i.e.,
+    /// `[[nodiscard]]` and `[[deprecated]]`, on the generated mock methods. Otherwise
+    /// they are stripped, which may change warnings when calling methods via the mock.
+    /// Since `MOCK_METHOD` can't have attributes, such methods are overridden with the
+    /// attributes and forward to a mocked delegate, e.g., `DoBar` for `bar`. Methods with
+    /// ref-qualifiers or calltypes are mocked without attributes. Default is false.
+    pub fn keep_method_attributes(mut self, value: bool) -> Self {
+        self.generator.keep_method_attributes(value);
+        self
+    }
+
+    /// Controls whether to generate helpers for pairs of non-const and const overloads,
+    /// e.g., `T& get()` and `const T& get() const`. A helper such as
+    /// `DelegateGetToConst()` makes the non-const overload call the const overload by
+    /// default, so expectations only need to be set on the const overload. Default is
+    /// false.
+    pub fn const_overload_helpers(mut self, value: bool) -> Self {
+        self.generator.const_overload_helpers(value);
+        self
+    }
+
+    /// Controls whether to mock methods taking or returning `std::unique_ptr` by value with
+    /// the gmock cookbook pattern for move-only types. A delegate method, e.g.,
+    /// `DoShare()`, using raw pointers instead is mocked, and the original method is
+    /// overridden to call the delegate. Default is false.
+    pub fn move_only_helpers(mut self, value: bool) -> Self {
+        self.generator.move_only_helpers(value);
+        self
+    }
+
+    /// Controls whether to add a constructor to mocks setting default actions, returning
+    /// references to static dummy objects, for methods returning references. Without
+    /// actions gmock aborts when such methods are called. The constructor is not added
+    /// to mocks forwarding constructors of the mocked classes. Default is false.
+    pub fn reference_default_actions(mut self, value: bool) -> Self {
+        self.reference_default_actions = value;
+        self.generator.reference_default_actions(value);
+        self
+    }
+
+    /// Controls whether to add `#line` directives before mocked methods, mapping them to
+    /// the lines of the method declarations in the mocked headers. This makes compilers
+    /// report errors in mocked method signatures at the original declarations. Another
+    /// `#line` directive after each mocked method restores the numbering of the generated
+    /// file, named by the function set with `line_directives_file_fun()`. In the code of a
+    /// single `Mock`, the numbering is of a header with only that mock, named like the
+    /// mock. Default is false.
+    pub fn line_directives(mut self, value: bool) -> Self {
+        self.generator.line_directives(value);
+        self
+    }
+
+    /// Sets a function to name the file a mock header is written to, used in the `#line`
+    /// directives restoring the numbering of the generated file, see `line_directives()`.
+    /// Default is `naming::default_name_output_file()`.
+    pub fn line_directives_file_fun(
+        mut self,
+        name_output_file: impl Fn(&MockHeader) -> String + 'static,
+    ) -> Self {
+        self.name_output_file = Some(Box::new(name_output_file));
+        self
+    }
+
+    /// Sets the name of the macro used to mock methods, e.g., a project specific wrapper
+    /// of `MOCK_METHOD` taking the same arguments. Creating mocks fails if the name is not
+    /// a valid identifier. Default is `MOCK_METHOD`.
+    pub fn mock_macro(mut self, mock_macro: String) -> Self {
+        self.generator.mock_macro(mock_macro);
+        self
+    }
+
+    /// Controls whether to declare local type aliases in mocks for result and argument
+    /// types containing commas, e.g., `using MockFoo_bar_return = std::map<int, int>;`,
+    /// instead of wrapping the types in parentheses in `MOCK_METHOD`. Default is false.
+    pub fn comma_type_aliases(mut self, value: bool) -> Self {
+        self.generator.comma_type_aliases(value);
+        self
+    }
+
+    /// Controls whether to add a region, delimited by `// mocksmith:keep(MockFoo)` and
+    /// `// mocksmith:endkeep` lines, at the start of each mock, for hand-written members
+    /// and setup. The content of the regions is kept when regenerating mocks with
+    /// `preserve_kept_regions()`. Default is false.
+    pub fn keep_regions(mut self, value: bool) -> Self {
+        self.generator.keep_regions(value);
+        self
+    }
+
+    /// Controls whether to mock destruction of mocks of classes with virtual destructors,
+    /// by adding a mocked method `Die` called from the destructor of the mock. This
+    /// makes it possible to expect destruction, e.g., `EXPECT_CALL(mock, Die())`. Default
+    /// is false.
+    pub fn mock_destructor(mut self, value: bool) -> Self {
+        self.generator.mock_destructor(value);
+        self
+    }
+
+    /// Controls whether to generate static assertions checking the signatures of the
+    /// mocked methods, see `create_signature_checks_for_header()`. Default is false.
+    pub fn signature_checks(mut self, value: bool) -> Self {
+        self.generator.signature_checks(value);
+        self
+    }
+
+    /// Controls whether to add a constructor to mocks setting default actions returning
+    /// default values, e.g., `0` or `Foo{}`, for methods returning default constructible
+    /// and copyable types, making mocks lenient by default. The actions are added to the
+    /// same constructor as actions added with `reference_default_actions()`. Default is
+    /// false.
+    pub fn value_default_actions(mut self, value: bool) -> Self {
+        self.generator.value_default_actions(value);
+        self
+    }
+
+    /// Controls whether to add aliases for `::testing::NiceMock` and
+    /// `::testing::StrictMock` of each mock, e.g., `NiceMockFoo` and `StrictMockFoo` for
+    /// `MockFoo`. Default is false.
+    pub fn mock_aliases(mut self, value: bool) -> Self {
+        self.generator.mock_aliases(value);
+        self
+    }
+
+    /// Sets a pattern for include guards in mock headers, replacing `#pragma once`. In the
+    /// pattern, `{PROJECT}` is replaced with the project set with
+    /// `include_guard_project()`, and `{PATH}` and `{FILE}` with the directory and the
+    /// stem of the include path of the mocked header, e.g.,
+    /// `{PROJECT}_{PATH}_{FILE}_MOCK_H_` gives `MYLIB_FOO_BAR_MOCK_H_` for `foo/bar.h` in
+    /// project `mylib`. Replaced values are made upper case and characters not allowed in
+    /// macro names are replaced with underscores. Default is None, i.e., using
+    /// `#pragma once`.
+    pub fn include_guard_pattern(mut self, pattern: Option<String>) -> Self {
+        self.generator.include_guard_pattern(pattern);
+        self
+    }
+
+    /// Sets the project name replacing `{PROJECT}` in include guard patterns. Without a
+    /// project, `{PROJECT}` is removed together with a following underscore. Default is
+    /// None.
+    pub fn include_guard_project(mut self, project: Option<String>) -> Self {
+        self.generator.include_guard_project(project);
+        self
+    }
+
+    /// Controls whether to split mocks into header and source files. The mocks then only
+    /// declare their constructors and destructors, which are defined in the source file
+    /// created with `create_mock_source_for_header()`, to reduce compile times of tests
+    /// using large mocks. Default is false.
+    pub fn split_source_file(mut self, value: bool) -> Self {
+        self.generator.split_source_file(value);
+        self
+    }
+
+    /// Sets the style of the generated code, Google Mock mocks, FakeIt mocks or plain
+    /// fakes. Unless a custom naming function is set, fakes are named with a "Fake" prefix
+    /// rather than "Mock". Default is `MockStyle::Gmock`.
+    pub fn style(mut self, style: MockStyle) -> Self {
+        self.style = style;
+        self.generator.style(style);
+        self
+    }
+
+    /// Sets whether to generate a factory helper for each mock, e.g., `makeMockFoo()`,
+    /// returning a smart pointer to the mocked class and a raw pointer to the mock for
+    /// expectations, for tests injecting dependencies with smart pointers. Not
+    /// supported for FakeIt mocks. Default is None, i.e., no helpers.
+    pub fn factory_helpers(mut self, pointer: Option<FactoryPointer>) -> Self {
+        self.generator.factory_helpers(pointer);
+        self
+    }
+
+    /// Sets whether to generate helpers for each mock, e.g., `makeNiceMockFoo()` and
+    /// `makeStrictMockFoo()`, creating `::testing::NiceMock` and `::testing::StrictMock`
+    /// instances of the mock, by value or heap allocated. Only supported for Google Mock.
+    /// Default is None, i.e., no helpers.
+    pub fn nice_strict_helpers(mut self, allocation: Option<HelperAllocation>) -> Self {
+        self.generator.nice_strict_helpers(allocation);
+        self
+    }
+
+    /// Sets whether to extract interfaces from concrete classes, to mock classes lacking
+    /// interfaces. For each class, e.g., `Foo`, a pure virtual interface `IFoo` with the
+    /// public methods of the class is generated, together with an adapter `FooAdapter`
+    /// implementing the interface by forwarding to a `Foo`, and a mock of the interface.
+    /// All non-static methods are considered, regardless of `methods_to_mock()`. Default
+    /// is false.
+    pub fn extract_interface(mut self, value: bool) -> Self {
+        self.extract_interface = value;
+        self.generator.extract_interface(value);
+        self
+    }
+
+    /// Sets whether to add a `static_assert` after each mock checking that the mock is not
+    /// abstract, to detect pure virtual methods that are not mocked, e.g., due to method
+    /// filters, when compiling the mock header. Not supported for FakeIt mocks. Default
+    /// is false.
+    pub fn assert_not_abstract(mut self, value: bool) -> Self {
+        self.generator.assert_not_abstract(value);
+        self
+    }
+
+    /// Sets a template controlling the layout of generated code, using Jinja2 syntax as
+    /// implemented by MiniJinja. The template renders the complete header with the
+    /// variables `source_files` and `type_headers`, the include paths of the mocked
+    /// headers and of headers declaring used types, `banner`, the banner comment, and
+    /// `mocks`, each with `name`, `class_name`, `code` and `class`, the mocked class as
+    /// described below. Any license header is added before the rendered code. If the
+    /// template defines a macro `mock(class)`, it renders the code of each mock. Its
+    /// argument has `name`, `class_name`, `namespaces` and `methods`, each method with
+    /// `name`, `result_type`, `arguments` (with `type_name` and `name`), `is_const`,
+    /// `is_virtual`, `is_noexcept`, `ref_qualifier`, `calltype`, `access` and the
+    /// `mock_method` code. If the template defines a macro `method(method)`, it renders
+    /// the code of each mocked method, given a method as above with the default
+    /// `mock_method` code, also when using the built-in layout. Blocks are trimmed, so each template line maps to a line of code. Creating
+    /// mocks fails if the template cannot be parsed. Default is None, i.e., using the
+    /// built-in layout.
+    pub fn template(mut self, template: Option<String>) -> Self {
+        self.generator.template(template);
+        self
+    }
+
+    /// Sets the banner comment at the top of generated files, replacing the default
+    /// "Automatically generated" comment. The variables `{SOURCE_FILES}`, `{CLASSES}` and
+    /// `{VERSION}` are replaced with the mocked header files, the mocked classes and the
+    /// Mocksmith version. There is no date variable, since files would then change every
+    /// day and never be up to date. Lines are made comments unless the banner starts with
+    /// `//` or `/*`. Default is None, i.e., the default comment.
+    pub fn banner(mut self, banner: Option<String>) -> Self {
+        self.generator.banner(banner);
+        self
+    }
+
+    /// Sets the line endings of generated files. Default is `LineEnding::Lf`.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.generator.line_ending(line_ending);
+        self
+    }
+
+    /// Sets whether to make sure generated files end with a newline, also when rendered
+    /// with a template. Default is false.
+    pub fn final_newline(mut self, value: bool) -> Self {
+        self.generator.final_newline(value);
+        self
+    }
+
+    /// Sets whether to start generated files with a UTF-8 byte order mark, which some
+    /// MSVC setups require. Default is false.
+    pub fn byte_order_mark(mut self, value: bool) -> Self {
+        self.generator.byte_order_mark(value);
+        self
+    }
+
+    /// Sets a license header inserted at the top of generated files, before the banner.
+    /// The variables `{YEAR}` and `{FILE_NAME}` are replaced with the current year and the
+    /// names of the mocked header files. Lines are made comments unless the license starts
+    /// with `//` or `/*`. Default is None, i.e., no license header.
+    pub fn license(mut self, license: Option<String>) -> Self {
+        self.generator.license(license);
+        self
+    }
+
     /// Sets a custom function to generate mock names based on class names.
     pub fn mock_name_fun(mut self, name_mock: impl Fn(&str) -> String + 'static) -> Self {
-        self.name_mock = Box::new(name_mock);
+        self.name_mock = Some(Box::new(name_mock));
         self
     }
 
@@ -232,19 +1271,161 @@ impl Mocksmith {
         }
         self.clangwrap
             .with_tu_from_file(&self.include_paths, file.as_ref(), |tu| {
-                let mut mocks = self.create_mocks(tu)?;
+                let mut mocks = self.create_mocks(tu, Some(file.as_ref()))?;
+                let dependencies = clangwrap::visited_files(tu, file.as_ref());
                 mocks.iter_mut().for_each(|m| {
                     m.source_file = Some(file.as_ref().to_path_buf());
+                    m.dependencies = dependencies.clone();
                 });
                 Ok(mocks)
             })
     }
 
+    /// Lists the classes in the given file that can be mocked with any strategy for which
+    /// methods to mock, with the number of methods mocked with each strategy. Class filters
+    /// are applied. No mocks are generated.
+    pub fn list_classes_in_file<P>(&self, file: P) -> Result<Vec<ClassInfo>>
+    where
+        P: AsRef<Path>,
+    {
+        if !file.as_ref().is_file() {
+            return Err(MocksmithError::InputFileError(file.as_ref().to_path_buf()));
+        }
+        self.clangwrap
+            .with_tu_from_file(&self.include_paths, file.as_ref(), |tu| {
+                Ok(model::classes_in_translation_unit(
+                    tu,
+                    MethodsToMockStrategy::All,
+                    self.com_interfaces,
+                    self.qt_macros,
+                )
+                .into_iter()
+                .filter(|class| self.is_class_selected(class))
+                .map(|class| {
+                    let (file, line) = class
+                        .declaration_location
+                        .clone()
+                        .map_or((None, 0), |(file, line)| (Some(file), line));
+                    ClassInfo {
+                        qualified_name: class.qualified_name(),
+                        file,
+                        line,
+                        methods: class.methods.len(),
+                        virtual_methods: class
+                            .methods
+                            .iter()
+                            .filter(|method| method.is_virtual)
+                            .count(),
+                        pure_virtual_methods: class
+                            .methods
+                            .iter()
+                            .filter(|method| method.is_pure_virtual)
+                            .count(),
+                    }
+                })
+                .collect())
+            })
+    }
+
+    /// Parses the given file and returns the classes to mock, with the methods to mock,
+    /// after applying filters, to analyze classes or generate other code than mocks. If no
+    /// appropriate classes to mock are found, an empty vector is returned.
+    pub fn parse_file<P>(&self, file: P) -> Result<Vec<ParsedClass>>
+    where
+        P: AsRef<Path>,
+    {
+        if !file.as_ref().is_file() {
+            return Err(MocksmithError::InputFileError(file.as_ref().to_path_buf()));
+        }
+        self.clangwrap
+            .with_tu_from_file(&self.include_paths, file.as_ref(), |tu| {
+                Ok(self
+                    .classes_to_mock(tu)
+                    .iter()
+                    .map(model::ClassToMock::to_parsed)
+                    .collect())
+            })
+    }
+
+    /// Parses the given string and returns the classes to mock, see `parse_file()`.
+    pub fn parse_string(&self, content: &str) -> Result<Vec<ParsedClass>> {
+        self.clangwrap
+            .with_tu_from_string(&self.include_paths, content, None, |tu| {
+                Ok(self
+                    .classes_to_mock(tu)
+                    .iter()
+                    .map(model::ClassToMock::to_parsed)
+                    .collect())
+            })
+    }
+
+    /// Generates a JSON document describing the classes to mock in the given files, as
+    /// parsed and filtered, with the methods to mock and the methods skipped with reasons.
+    /// This lets other tools use the parse results without using libclang themselves. The
+    /// document has a `classes` array with an object per class.
+    pub fn create_model_for_files<P>(&self, files: &[P]) -> Result<String>
+    where
+        P: AsRef<Path>,
+    {
+        let mut classes = Vec::new();
+        for file in files {
+            if !file.as_ref().is_file() {
+                return Err(MocksmithError::InputFileError(file.as_ref().to_path_buf()));
+            }
+            self.clangwrap
+                .with_tu_from_file(&self.include_paths, file.as_ref(), |tu| {
+                    classes.extend(self.classes_to_mock(tu).iter().map(|class| class.to_json()));
+                    Ok(())
+                })?;
+        }
+        Ok(format!("{:#}\n", serde_json::json!({ "classes": classes })))
+    }
+
     /// Generates mocks for classes in the given string. If no appropriate classes to mock
     /// are found, an empty vector is returned.
     pub fn create_mocks_from_string(&self, content: &str) -> Result<Vec<Mock>> {
         self.clangwrap
-            .with_tu_from_string(&self.include_paths, content, |tu| self.create_mocks(tu))
+            .with_tu_from_string(&self.include_paths, content, None, |tu| {
+                self.create_mocks(tu, None)
+            })
+    }
+
+    /// Generates mocks for classes in the given string, parsed as if it was the content
+    /// of the given file, which doesn't need to exist. Headers included with quotes are
+    /// searched for relative to the file, and the file is reported in parse errors. If no
+    /// appropriate classes to mock are found, an empty vector is returned.
+    pub fn create_mocks_from_string_as_file<P>(&self, content: &str, file: P) -> Result<Vec<Mock>>
+    where
+        P: AsRef<Path>,
+    {
+        self.clangwrap.with_tu_from_string(
+            &self.include_paths,
+            content,
+            Some(file.as_ref()),
+            |tu| {
+                let mut mocks = self.create_mocks(tu, Some(file.as_ref()))?;
+                mocks.iter_mut().for_each(|m| {
+                    m.source_file = Some(file.as_ref().to_path_buf());
+                });
+                Ok(mocks)
+            },
+        )
+    }
+
+    /// Generate the contents for a header file with mocks for classes in the given string,
+    /// parsed as if it was the content of the given file, see
+    /// `create_mocks_from_string_as_file()`. The header includes the given file.
+    pub fn create_mock_header_for_string_as_file<P>(
+        &self,
+        content: &str,
+        file: P,
+    ) -> Result<MockHeader>
+    where
+        P: AsRef<Path>,
+    {
+        let source_file_include_paths = [self.header_include_path(file.as_ref())];
+        let mocks = self.create_mocks_from_string_as_file(content, file.as_ref())?;
+        self.mock_header(&source_file_include_paths, mocks)
     }
 
     /// Generate the contents for a header file with mocks for classes in the give file.
@@ -258,38 +1439,386 @@ impl Mocksmith {
             .map(|f| self.header_include_path(f.as_ref()))
             .collect();
 
-        let mut header = MockHeader::new();
+        let mut mocks = Vec::new();
+        for file in files {
+            mocks.extend(self.create_mocks_for_file(file.as_ref())?);
+        }
+        self.mock_header(&source_file_include_paths, mocks)
+    }
+
+    /// Generate the contents for one header file per given file, with mocks for the classes
+    /// in the file, like `create_mock_header_for_files()` with one file. The files are
+    /// processed one at a time, and each header, or error, is passed to a function before
+    /// processing the next file. This allows writing or reporting results while processing
+    /// many files, without keeping all mocks in memory. Headers without mocks are passed
+    /// too.
+    pub fn create_mocks_for_files_with<P>(&self, files: &[P], mut f: impl FnMut(Result<MockHeader>))
+    where
+        P: AsRef<Path>,
+    {
         for file in files {
-            let mocks = self.create_mocks_for_file(file.as_ref())?;
-            header.mocks.extend(mocks);
+            f(self.create_mock_header_for_files(&[file.as_ref()]));
+        }
+    }
+
+    /// Generate the contents for one header file per mock, for classes in the given file.
+    /// Each header only includes the headers needed by its mock. If no appropriate classes
+    /// to mock are found, an empty vector is returned.
+    pub fn create_mock_headers_per_class_for_file<P>(&self, file: P) -> Result<Vec<MockHeader>>
+    where
+        P: AsRef<Path>,
+    {
+        let source_file_include_paths = [self.header_include_path(file.as_ref())];
+        self.create_mocks_for_file(file.as_ref())?
+            .into_iter()
+            .map(|mock| self.mock_header(&source_file_include_paths, vec![mock]))
+            .collect()
+    }
+
+    fn mock_header(
+        &self,
+        source_file_include_paths: &[String],
+        mocks: Vec<Mock>,
+    ) -> Result<MockHeader> {
+        let mut header = MockHeader::new();
+        header.mocks = mocks;
+
+        let mut type_header_include_paths: Vec<String> = Vec::new();
+        if self.include_type_headers {
+            for type_header in header.mocks.iter().flat_map(|mock| &mock.type_headers) {
+                let path = self.header_include_path(type_header);
+                if !source_file_include_paths.contains(&path)
+                    && !type_header_include_paths.contains(&path)
+                {
+                    type_header_include_paths.push(path);
+                }
+            }
         }
 
-        header.code = self
+        let start = Instant::now();
+        let header_code = self.generator.header(
+            source_file_include_paths,
+            &type_header_include_paths,
+            &header.mocks,
+        )?;
+        let code = self
             .generator
-            .header(&source_file_include_paths, &header.mocks);
+            .restore_line_directives(header_code.code, || match &self.name_output_file {
+                Some(name_output_file) => name_output_file(&header),
+                None => naming::default_name_output_file(&header),
+            });
+        header.code = self.generator.file_content(code);
+        let file = header
+            .mocks
+            .first()
+            .and_then(|mock| mock.source_file.as_deref());
+        self.record_timing(file, Phase::Generation, start.elapsed());
+        header.mock_spans = header_code
+            .mock_lines
+            .into_iter()
+            .map(|lines| lines.map(|lines| spans::lines_span(&header.code, lines)))
+            .collect();
+        header.includes_span = header_code
+            .include_lines
+            .map(|lines| spans::lines_span(&header.code, lines));
 
         Ok(header)
     }
 
+    /// Generate the contents for a source file defining the constructors and destructors
+    /// of the mocks in a header, when splitting mocks into header and source files. The
+    /// source file includes the mock header with the given path. If no mocks need
+    /// definitions, None is returned.
+    pub fn create_mock_source_for_header(
+        &self,
+        header: &MockHeader,
+        header_include_path: &str,
+    ) -> Option<String> {
+        self.generator
+            .source(header_include_path, &header.mocks)
+            .map(|source| self.generator.file_content(source))
+    }
+
+    /// Generate the contents for a header with static assertions checking that the
+    /// signatures of the mocked methods are unchanged, including the mocked headers. This
+    /// makes a committed mock fail to compile with a clear message when the mocked
+    /// classes change. If signature checks are not generated, None is returned.
+    pub fn create_signature_checks_for_header(&self, header: &MockHeader) -> Option<String> {
+        let mut source_file_include_paths: Vec<String> = Vec::new();
+        for file in header
+            .mocks
+            .iter()
+            .filter_map(|mock| mock.source_file.as_ref())
+        {
+            let path = self.header_include_path(file);
+            if !source_file_include_paths.contains(&path) {
+                source_file_include_paths.push(path);
+            }
+        }
+        self.generator
+            .signature_checks_header(&source_file_include_paths, &header.mocks)
+            .map(|checks| self.generator.file_content(checks))
+    }
+
+    /// Generate the contents for a markdown file with ready-to-paste `EXPECT_CALL` snippets
+    /// for the methods of a mock. If cheat sheets are not emitted, None is returned.
+    pub fn create_cheat_sheet_for_mock(&self, mock: &Mock) -> Option<String> {
+        mock.cheat_sheet
+            .as_ref()
+            .map(|cheat_sheet| self.generator.file_content(cheat_sheet.clone()))
+    }
+
+    /// Copies the content of the regions kept in mocks, see `keep_regions()`, from the
+    /// existing content of a file to regenerated content for the file.
+    pub fn preserve_kept_regions(&self, existing: &str, generated: &str) -> String {
+        markers::keep_regions(existing, generated)
+    }
+
+    /// Updates the content of an existing file, e.g., a test file, by replacing the lines
+    /// between a `// mocksmith:begin` line and a `// mocksmith:end` line with the code of
+    /// the mocks. The rest of the file is kept as is, to be able to mix generated and
+    /// hand-written code. An error is returned unless the file has exactly one region.
+    pub fn update_marked_region(&self, existing: &str, mocks: &[Mock]) -> Result<String> {
+        let code = mocks
+            .iter()
+            .map(|mock| mock.code.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        markers::replace_region(existing, &code)
+    }
+
+    /// Generate the contents for a markdown file documenting the mocks in a header, listing
+    /// the mocked classes with their namespaces, the mocked methods and the methods skipped
+    /// with reasons, as well as the output file of the mocks. If documentation is not
+    /// emitted, None is returned.
+    pub fn create_documentation_for_header(
+        &self,
+        header: &MockHeader,
+        output_file: &Path,
+    ) -> Option<String> {
+        self.generator
+            .documentation(output_file, &header.mocks)
+            .map(|documentation| self.generator.file_content(documentation))
+    }
+
+    /// Generate the contents for a source file with a gtest fixture skeleton for a mock,
+    /// containing the mock as a member and an empty first test. The source file includes
+    /// the mock header with the given path. If fixtures are not emitted, None is returned.
+    pub fn create_fixture_for_mock(
+        &self,
+        mock: &Mock,
+        header_include_path: &str,
+    ) -> Option<String> {
+        self.generator
+            .fixture(header_include_path, mock)
+            .map(|fixture| self.generator.file_content(fixture))
+    }
+
+    /// Whether the content of a file has the default banner of files generated by
+    /// Mocksmith. Files generated with a custom banner are not recognized.
+    pub fn is_generated_file(&self, content: &str) -> bool {
+        generate::has_default_banner(content)
+    }
+
+    /// Applies the configured line endings, final newline and byte order mark to generated
+    /// code written as a file, e.g., the code of mocks created with
+    /// `create_mocks_from_string()`. Headers, sources and fixtures created by Mocksmith
+    /// already have them applied.
+    pub fn file_content(&self, code: String) -> String {
+        self.generator.file_content(code)
+    }
+
     fn header_include_path(&self, header_file: &Path) -> String {
-        if self.include_paths.is_empty() {
+        let mut path = if self.include_paths.is_empty() {
             header_include_path(header_file, &[PathBuf::from(".")])
         } else {
             header_include_path(header_file, &self.include_paths)
+        };
+        if let Some(stripped) = self
+            .strip_include_prefix
+            .as_ref()
+            .and_then(|prefix| strip_include_prefix(&path, prefix))
+        {
+            path = stripped;
+        }
+        if let Some(prefix) = &self.include_prefix {
+            path = format!("{}/{}", prefix.trim_end_matches('/'), path);
         }
+        path
+    }
+
+    fn create_mocks(&self, tu: &clang::TranslationUnit, file: Option<&Path>) -> Result<Vec<Mock>> {
+        self.generator.check()?;
+        self.record_timing(file, Phase::Parse, self.clangwrap.parse_duration());
+        let start = Instant::now();
+        let classes = self.classes_to_mock(tu);
+        self.record_timing(file, Phase::Traversal, start.elapsed());
+        let start = Instant::now();
+        let mocks = classes
+            .into_iter()
+            .inspect(|class| self.warn_about_ambiguous_bases(class))
+            .inspect(|class| self.warn_about_methods_returning_references(class))
+            .inspect(|class| self.warn_about_abstract_mocks(class))
+            .map(|class| self.generator.mock(&class, &self.mock_name(&class)))
+            .collect();
+        self.record_timing(file, Phase::Generation, start.elapsed());
+        mocks
     }
 
-    fn create_mocks(&self, tu: &clang::TranslationUnit) -> Result<Vec<Mock>> {
-        let classes = model::classes_in_translation_unit(tu, self.methods_to_mock);
-        Ok(classes
+    fn record_timing(&self, file: Option<&Path>, phase: Phase, duration: Duration) {
+        if self.collect_timings {
+            self.timings.borrow_mut().push(Timing {
+                file: file.map(Path::to_path_buf),
+                phase,
+                duration,
+            });
+        }
+    }
+
+    // Finds the classes to mock in a translation unit, with the methods to mock, after
+    // applying filters
+    fn classes_to_mock(&self, tu: &clang::TranslationUnit) -> Vec<model::ClassToMock> {
+        let methods_to_mock = if self.extract_interface {
+            MethodsToMockStrategy::All
+        } else {
+            self.methods_to_mock
+        };
+        let mut classes = model::classes_in_translation_unit(
+            tu,
+            methods_to_mock,
+            self.com_interfaces,
+            self.qt_macros,
+        );
+        if self.sort_output {
+            classes.sort_by_key(|class| class.qualified_name());
+        }
+        classes
+            .into_iter()
+            .map(|mut class| {
+                // Calling conventions are only kept for COM interfaces
+                if !self.com_interfaces {
+                    class
+                        .methods
+                        .iter_mut()
+                        .for_each(|method| method.calltype = None);
+                }
+                // Methods that are not mocked are inherited from the mocked class
+                let (methods, unmocked): (Vec<_>, Vec<_>) = std::mem::take(&mut class.methods)
+                    .into_iter()
+                    .partition(|method| {
+                        !(self.skip_deprecated && method.is_deprecated)
+                            && (self.filter_method)(&method.info(&class.name))
+                    });
+                class.methods = methods;
+                for method in &unmocked {
+                    let reason = if self.skip_deprecated && method.is_deprecated {
+                        "deprecated"
+                    } else {
+                        "excluded by method filter"
+                    };
+                    class
+                        .skipped_methods
+                        .push((method.name.clone(), reason.to_string()));
+                }
+                class.unmocked_pure_virtual_methods = unmocked
+                    .into_iter()
+                    .filter(|method| method.is_pure_virtual)
+                    .map(|method| method.name)
+                    .collect();
+                if self.fully_qualify_types {
+                    class
+                        .methods
+                        .iter_mut()
+                        .for_each(|method| method.qualify_types());
+                }
+                if self.sort_output {
+                    class.methods.sort_by_cached_key(|method| {
+                        (method.name.clone(), method.info(&class.name).signature)
+                    });
+                }
+                class
+            })
+            .filter(|class| !class.methods.is_empty())
+            .filter(|class| self.is_class_selected(class))
+            .collect()
+    }
+
+    // Whether a class is selected for mocking by the class filter and not excluded
+    fn is_class_selected(&self, class: &model::ClassToMock) -> bool {
+        (self.filter_class)(&class.name)
+            && (self.filter_qualified_class)(&class.qualified_name())
+            && !(self.exclude_class)(&class.name)
+    }
+
+    // Logs and reports a warning, at the location of a declaration if known
+    fn warn(&self, location: &Option<(PathBuf, u32)>, message: &str) {
+        if let Some(log) = self.clangwrap.log() {
+            let location = location
+                .as_ref()
+                .map(|(file, line)| log::location(file, *line, None));
+            log.diagnostic(Severity::Warning, location.as_deref(), message);
+        }
+        self.clangwrap.report(Diagnostic {
+            severity: Severity::Warning,
+            message: message.to_string(),
+            file: location.as_ref().map(|(file, _)| file.clone()),
+            line: location.as_ref().map(|(_, line)| *line),
+            column: None,
+            source: DiagnosticSource::Mocksmith,
+        });
+    }
+
+    fn warn_about_ambiguous_bases(&self, class: &model::ClassToMock) {
+        for base in &class.ambiguous_bases {
+            self.warn(
+                &class.declaration_location,
+                &format!(
+                    "Class {} inherits {} more than once without virtual inheritance, so \
+                     the mock cannot be unambiguously used as a {}",
+                    class.name, base, base
+                ),
+            );
+        }
+    }
+
+    fn warn_about_abstract_mocks(&self, class: &model::ClassToMock) {
+        for method in &class.unmocked_pure_virtual_methods {
+            self.warn(
+                &class.declaration_location,
+                &format!(
+                    "Pure virtual method {}::{} is not mocked, so the mock is abstract",
+                    class.name, method
+                ),
+            );
+        }
+    }
+
+    fn warn_about_methods_returning_references(&self, class: &model::ClassToMock) {
+        if self.reference_default_actions && generate::can_add_default_constructor(class) {
+            return;
+        }
+        for method in class
+            .methods
             .iter()
-            .filter(|class| (self.filter_class)(class.name.as_str()))
-            .map(|class| self.generator.mock(class, &self.mock_name(class)))
-            .collect())
+            .filter(|method| method.returns_reference)
+        {
+            self.warn(
+                &method.declaration_location,
+                &format!(
+                    "Method {}::{} returns a reference, so gmock aborts if the mocked method \
+                     is called without an action",
+                    class.name, method.name
+                ),
+            );
+        }
     }
 
     fn mock_name(&self, class: &model::ClassToMock) -> String {
-        (self.name_mock)(&class.name)
+        match (&self.name_mock, self.style) {
+            (Some(name_mock), _) => name_mock(&class.name),
+            (None, MockStyle::Gmock | MockStyle::FakeIt) => naming::default_name_mock(&class.name),
+            (None, MockStyle::Fake) => naming::default_name_fake(&class.name),
+        }
     }
 }
 
@@ -1,4 +1,5 @@
-use std::{cell::RefCell, io::Write};
+use crate::{DiagnosticSource, Severity};
+use std::{cell::RefCell, io::Write, path::Path};
 
 #[macro_export]
 macro_rules! log {
@@ -23,6 +24,67 @@ macro_rules! verbose {
 pub(crate) struct Logger {
     write: RefCell<Box<dyn std::io::Write>>,
     pub(crate) verbose: bool,
+    // Whether to color diagnostics with ANSI escape codes
+    pub(crate) color: bool,
+}
+
+// Severities are labeled like by compilers
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Fatal => "fatal error",
+        })
+    }
+}
+
+impl std::fmt::Display for DiagnosticSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DiagnosticSource::Clang => "clang",
+            DiagnosticSource::Mocksmith => "mocksmith",
+        })
+    }
+}
+
+impl Severity {
+    // ANSI escape code for the label, with the colors used by Clang
+    fn color(&self) -> &'static str {
+        match self {
+            Severity::Note => "\x1b[1;36m",
+            Severity::Warning => "\x1b[1;35m",
+            Severity::Error | Severity::Fatal => "\x1b[1;31m",
+        }
+    }
+}
+
+// Location of a diagnostic, formatted like file:line:column
+pub(crate) fn location(file: &Path, line: u32, column: Option<u32>) -> String {
+    match column {
+        Some(column) => format!("{}:{line}:{column}", file.display()),
+        None => format!("{}:{line}", file.display()),
+    }
+}
+
+// Formats a diagnostic like compilers do, e.g., `file:line:column: error: message`, so
+// editors can navigate to the location. Colors are added for terminals.
+pub(crate) fn format_diagnostic(
+    severity: Severity,
+    location: Option<&str>,
+    message: &str,
+    color: bool,
+) -> String {
+    let (bold, severity_color, reset) = if color {
+        ("\x1b[1m", severity.color(), "\x1b[0m")
+    } else {
+        ("", "", "")
+    };
+    let location = location
+        .map(|location| format!("{bold}{location}:{reset} "))
+        .unwrap_or_default();
+    format!("{location}{severity_color}{severity}:{reset} {message}")
 }
 
 impl Logger {
@@ -30,9 +92,14 @@ impl Logger {
         Logger {
             write: RefCell::new(write),
             verbose,
+            color: false,
         }
     }
 
+    pub(crate) fn diagnostic(&self, severity: Severity, location: Option<&str>, message: &str) {
+        self.log(&format_diagnostic(severity, location, message, self.color));
+    }
+
     pub(crate) fn log(&self, message: &str) {
         let mut write = self.write.borrow_mut();
         writeln!(write, "{message}").unwrap_or_else(|_| eprintln!("{message}"));
@@ -70,4 +137,21 @@ mod tests {
         verbose!(log, "{}", fun());
         assert_eq!(calls, 1);
     }
+
+    #[test]
+    fn diagnostics_are_formatted_like_compiler_output() {
+        assert_eq!(
+            format_diagnostic(
+                Severity::Error,
+                Some(&location(Path::new("foo.h"), 3, Some(7))),
+                "unknown type name 'Bar'",
+                false
+            ),
+            "foo.h:3:7: error: unknown type name 'Bar'"
+        );
+        assert_eq!(
+            format_diagnostic(Severity::Warning, None, "abstract mock", true),
+            "\x1b[1;35mwarning:\x1b[0m abstract mock"
+        );
+    }
 }
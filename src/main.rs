@@ -1,12 +1,46 @@
 mod args;
+mod diff;
+mod exitcode;
+mod since;
+mod stats;
 
 use anyhow::Context;
 use args::arguments;
-use std::{io::Read, path::Path};
+use exitcode::Failure;
+use std::{
+    cell::{Cell, RefCell},
+    io::{IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
-use mocksmith::{MockHeader, Mocksmith, naming};
+use mocksmith::{
+    ClassInfo, Diagnostic, DiagnosticSource, Mock, MockHeader, Mocksmith, MocksmithError, Severity,
+    naming,
+};
 
-fn maybe_write_file(file: &Path, content: &str, always_write: bool) -> anyhow::Result<()> {
+// Writes a file if the content has changed, or prints a diff of the changes instead of
+// writing when diffing. The content is printed to stdout if the file is -.
+fn maybe_write_file(
+    file: &Path,
+    content: &str,
+    always_write: bool,
+    diff: bool,
+) -> anyhow::Result<()> {
+    if is_stdout(file) {
+        print!("{content}");
+        return Ok(());
+    }
+    if diff {
+        let (current_content, old_name) = match std::fs::read_to_string(file) {
+            Ok(current_content) => (current_content, format!("a/{}", file.display())),
+            Err(_) => (String::new(), "/dev/null".to_string()),
+        };
+        let new_name = format!("b/{}", file.display());
+        diff::print_diff(&current_content, content, &old_name, &new_name);
+        return Ok(());
+    }
     let current_content = if !always_write {
         std::fs::read_to_string(file).unwrap_or_default()
     } else {
@@ -14,11 +48,455 @@ fn maybe_write_file(file: &Path, content: &str, always_write: bool) -> anyhow::R
     };
     if always_write || current_content != content {
         std::fs::write(file, content)
-            .with_context(|| format!("Failed to write mock header file {}", file.display()))?;
+            .with_context(|| format!("Failed to write mock file {}", file.display()))?;
+    }
+    Ok(())
+}
+
+// Whether an output file is -, meaning stdout
+fn is_stdout(file: &Path) -> bool {
+    file == Path::new("-")
+}
+
+// Runs file I/O, recording the time spent for --stats
+fn timed_io<T>(
+    io_timings: &RefCell<Vec<(Option<PathBuf>, Duration)>>,
+    source_file: Option<&Path>,
+    io: impl FnOnce() -> T,
+) -> T {
+    let start = Instant::now();
+    let result = io();
+    io_timings
+        .borrow_mut()
+        .push((source_file.map(Path::to_path_buf), start.elapsed()));
+    result
+}
+
+// Entry of the manifest listing the files and mocks generated from a header. The mock
+// source file, checks file, fixture files and cheat sheet files are null when not
+// generated.
+fn manifest_entry(
+    source_file: &Path,
+    output_file: &Path,
+    mock_source_file: Option<&Path>,
+    checks_file: Option<&Path>,
+    header: &MockHeader,
+    fixture_files: &[Option<PathBuf>],
+    cheat_sheet_files: &[Option<PathBuf>],
+) -> serde_json::Value {
+    serde_json::json!({
+        "source_file": source_file.to_string_lossy(),
+        "output_file": output_file.to_string_lossy(),
+        "mock_source_file": mock_source_file.map(|file| file.to_string_lossy()),
+        "checks_file": checks_file.map(|file| file.to_string_lossy()),
+        "mocks": header
+            .mocks
+            .iter()
+            .zip(fixture_files)
+            .zip(cheat_sheet_files)
+            .map(|((mock, fixture_file), cheat_sheet_file)| {
+                serde_json::json!({
+                    "class_name": mock.parent_name,
+                    "mock_name": mock.name,
+                    "fixture_file": fixture_file.as_ref().map(|file| file.to_string_lossy()),
+                    "cheat_sheet_file":
+                        cheat_sheet_file.as_ref().map(|file| file.to_string_lossy()),
+                })
+            })
+            .collect::<Vec<_>>(),
+    })
+}
+
+// Compiles regexes filtering classes or methods by name
+fn name_regexes(patterns: &[String], error: &str) -> anyhow::Result<Vec<regex::Regex>> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            regex::Regex::new(pattern).map_err(|err| {
+                mocksmith::MocksmithError::InvalidRegex(format!("{error}: {err}")).into()
+            })
+        })
+        .collect()
+}
+
+// Converts a glob pattern to a regex matching complete paths
+fn glob_regex(glob: &str) -> regex::Regex {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex::Regex::new(&regex).expect("Regex from glob should be valid")
+}
+
+// Whether a header file matches any of the exclude patterns. Patterns without a slash
+// match the file name, other patterns the whole path.
+fn is_excluded(file: &Path, patterns: &[(bool, regex::Regex)]) -> bool {
+    let path = file.to_string_lossy().replace('\\', "/");
+    let file_name = file
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    patterns.iter().any(|(has_slash, regex)| {
+        if *has_slash {
+            regex.is_match(&path)
+        } else {
+            regex.is_match(&file_name)
+        }
+    })
+}
+
+// Reads a list of files, one per line or separated by NUL characters, from a file or from
+// stdin if the path is -. Lines are trimmed, but NUL separated paths are used as is.
+fn read_file_list(path: &Path, null_separated: bool) -> anyhow::Result<Vec<PathBuf>> {
+    let content = if path == Path::new("-") {
+        let mut content = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut content)
+            .context("Failed to read from stdin")?;
+        content
+    } else {
+        std::fs::read(path)
+            .with_context(|| format!("Could not read file list {}", path.display()))?
+    };
+    if null_separated {
+        return Ok(content
+            .split(|byte| *byte == 0)
+            .filter(|entry| !entry.is_empty())
+            .map(path_from_bytes)
+            .collect());
+    }
+    Ok(String::from_utf8_lossy(&content)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+// Paths are arbitrary bytes on Unix, but expected to be UTF-8 elsewhere
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+// Pipes the content of a generated file through a shell command, with the path of the file
+// in the MOCKSMITH_OUTPUT_FILE environment variable, and returns the command's output
+fn post_process(command: Option<&str>, file: &Path, content: String) -> anyhow::Result<String> {
+    let Some(command) = command else {
+        return Ok(content);
+    };
+    let (shell, shell_option) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut process = std::process::Command::new(shell)
+        .args([shell_option, command])
+        .env("MOCKSMITH_OUTPUT_FILE", file)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Could not run post-processing command '{command}'"))?;
+    let mut stdin = process.stdin.take().expect("Stdin should be piped");
+    // Write from another thread to not block if the command writes before reading all input
+    let writer = std::thread::spawn(move || stdin.write_all(content.as_bytes()));
+    let output = process
+        .wait_with_output()
+        .with_context(|| format!("Could not run post-processing command '{command}'"))?;
+    writer
+        .join()
+        .expect("Writing to post-processing command should not panic")
+        .with_context(|| format!("Could not write to post-processing command '{command}'"))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Post-processing command '{command}' failed for {}: {}",
+            file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8(output.stdout)
+        .with_context(|| format!("Post-processing command '{command}' output is not UTF-8"))
+}
+
+// Keeps the content of the regions kept in mocks in an existing file when regenerating it
+fn with_kept_regions(mocksmith: &Mocksmith, file: &Path, content: String) -> String {
+    match std::fs::read_to_string(file) {
+        Ok(existing) => mocksmith.preserve_kept_regions(&existing, &content),
+        Err(_) => content,
+    }
+}
+
+// Rule of a Make dependency file making an output file depend on the headers visited when
+// generating the mocks in it
+fn depfile_rule(output_file: &Path, header: &MockHeader) -> String {
+    let escape = |path: &Path| path.to_string_lossy().replace(' ', "\\ ");
+    let mut dependencies: Vec<&Path> = Vec::new();
+    for dependency in header.mocks.iter().flat_map(|mock| &mock.dependencies) {
+        if !dependencies.contains(&dependency.as_path()) {
+            dependencies.push(dependency);
+        }
+    }
+    let mut rule = format!("{}:", escape(output_file));
+    for dependency in dependencies {
+        rule.push_str(&format!(" \\\n  {}", escape(dependency)));
+    }
+    rule.push('\n');
+    rule
+}
+
+// Prints the classes that can be mocked in the header files, as text or JSON
+fn list_classes(
+    mocksmith: &Mocksmith,
+    source_files: &[PathBuf],
+    format: &str,
+) -> anyhow::Result<()> {
+    let mut classes = Vec::new();
+    for file in source_files {
+        classes.extend(
+            mocksmith
+                .list_classes_in_file(file)
+                .with_context(|| format!("Could not list classes in file {}", file.display()))?,
+        );
+    }
+    let location = |class: &ClassInfo| {
+        class
+            .file
+            .as_ref()
+            .map(|file| file.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    if format == "json" {
+        let classes = classes
+            .iter()
+            .map(|class| {
+                serde_json::json!({
+                    "qualified_name": class.qualified_name,
+                    "file": location(class),
+                    "line": class.line,
+                    "methods": class.methods,
+                    "virtual_methods": class.virtual_methods,
+                    "pure_virtual_methods": class.pure_virtual_methods,
+                })
+            })
+            .collect::<Vec<_>>();
+        println!("{:#}", serde_json::json!({ "classes": classes }));
+    } else {
+        for class in &classes {
+            println!(
+                "{} ({}:{}): {} methods, {} virtual, {} pure virtual",
+                class.qualified_name,
+                location(class),
+                class.line,
+                class.methods,
+                class.virtual_methods,
+                class.pure_virtual_methods
+            );
+        }
+    }
+    Ok(())
+}
+
+// Lets the user pick the classes to mock from a numbered list of the classes found in the
+// header files, and returns the qualified names of the picked classes
+fn select_classes(mocksmith: &Mocksmith, source_files: &[PathBuf]) -> anyhow::Result<Vec<String>> {
+    let mut classes = Vec::new();
+    for file in source_files {
+        let file_classes = mocksmith
+            .list_classes_in_file(file)
+            .with_context(|| format!("Could not list classes in file {}", file.display()))?;
+        if !file_classes.is_empty() {
+            eprintln!("{}:", file.display());
+        }
+        for class in file_classes {
+            eprintln!(
+                "  {:>3}. {} ({} methods)",
+                classes.len() + 1,
+                class.qualified_name,
+                class.methods
+            );
+            classes.push(class);
+        }
+    }
+    if classes.is_empty() {
+        anyhow::bail!("No classes to mock found in the header files");
+    }
+    loop {
+        eprint!("Classes to mock, e.g., 1,3-5, all or none [all]: ");
+        let mut line = String::new();
+        if std::io::stdin()
+            .read_line(&mut line)
+            .context("Failed to read from stdin")?
+            == 0
+        {
+            anyhow::bail!("No classes selected");
+        }
+        match parse_selection(line.trim(), classes.len()) {
+            Some(selected) => {
+                return Ok(selected
+                    .into_iter()
+                    .map(|index| classes[index].qualified_name.clone())
+                    .collect());
+            }
+            None => eprintln!("Invalid selection '{}'", line.trim()),
+        }
+    }
+}
+
+// Parses a selection of numbers and ranges of numbers from 1 to count, separated by commas
+// or spaces, into zero based indices
+fn parse_selection(selection: &str, count: usize) -> Option<Vec<usize>> {
+    match selection {
+        "" | "all" => return Some((0..count).collect()),
+        "none" => return Some(Vec::new()),
+        _ => {}
+    }
+    let number = |text: &str| {
+        text.trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|number| (1..=count).contains(number))
+    };
+    let mut indices = Vec::new();
+    for part in selection
+        .split([',', ' '])
+        .filter(|part| !part.trim().is_empty())
+    {
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => (number(first)?, number(last)?),
+            None => (number(part)?, number(part)?),
+        };
+        indices.extend((first - 1)..last);
+    }
+    indices.sort();
+    indices.dedup();
+    Some(indices)
+}
+
+// Fails if no mocks are generated, from all header files together or, with mode "each",
+// from any of the header files
+fn check_mocks_generated<'a>(
+    mode: Option<&str>,
+    source_files: &[PathBuf],
+    mocks: impl IntoIterator<Item = &'a Mock>,
+) -> anyhow::Result<()> {
+    let Some(mode) = mode else {
+        return Ok(());
+    };
+    let canonical = |path: &Path| dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mocked_files = mocks
+        .into_iter()
+        .map(|mock| mock.source_file.as_deref().map(canonical))
+        .collect::<Vec<_>>();
+    if mocked_files.is_empty() {
+        return Err(Failure::NothingToMock(
+            "No mocks generated, check that the class and method filters are correct".to_string(),
+        )
+        .into());
+    }
+    if mode == "each"
+        && let Some(file) = source_files
+            .iter()
+            .find(|file| !mocked_files.contains(&Some(canonical(file))))
+    {
+        return Err(Failure::NothingToMock(format!(
+            "No mocks generated from {}, check that the class and method filters are correct",
+            file.display()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+// Files listed in a manifest from an earlier run, except fixtures which are meant to be
+// edited and never regenerated
+fn manifest_files(manifest_file: &Path) -> Vec<PathBuf> {
+    let Some(manifest) = std::fs::read_to_string(manifest_file)
+        .ok()
+        .and_then(|manifest| serde_json::from_str::<serde_json::Value>(&manifest).ok())
+    else {
+        return Vec::new();
+    };
+    let mut files = Vec::new();
+    for header in manifest["headers"].as_array().into_iter().flatten() {
+        for key in ["output_file", "mock_source_file", "checks_file"] {
+            files.extend(header[key].as_str().map(PathBuf::from));
+        }
+        for mock in header["mocks"].as_array().into_iter().flatten() {
+            files.extend(mock["cheat_sheet_file"].as_str().map(PathBuf::from));
+        }
+    }
+    files
+}
+
+// Removes files in the output directory generated by an earlier run, i.e., listed in the
+// earlier manifest, but not generated by the current run. Files generated by other runs
+// writing to the same directory are kept. When diffing, the removals are printed as diffs
+// instead.
+fn prune(
+    output_dir: &Path,
+    previous_files: &[PathBuf],
+    written_files: &[PathBuf],
+    diff: bool,
+) -> anyhow::Result<()> {
+    let canonical = |path: &Path| dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let output_dir = canonical(output_dir);
+    let written_files = written_files
+        .iter()
+        .map(|file| canonical(file))
+        .collect::<Vec<_>>();
+    for file in previous_files {
+        let canonical_file = canonical(file);
+        if written_files.contains(&canonical_file) || !canonical_file.starts_with(&output_dir) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        if diff {
+            diff::print_diff(&content, "", &format!("a/{}", file.display()), "/dev/null");
+        } else {
+            std::fs::remove_file(file)
+                .with_context(|| format!("Failed to remove stale file {}", file.display()))?;
+            println!("Removed stale file {}", file.display());
+        }
     }
     Ok(())
 }
 
+// Directory of a header file relative to a source root, to mirror the source tree in the
+// output directory
+fn relative_source_dir(source_file: &Path, source_root: &Path) -> anyhow::Result<PathBuf> {
+    let canonical = |path: &Path| dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    canonical(source_file)
+        .parent()
+        .and_then(|dir| dir.strip_prefix(canonical(source_root)).ok())
+        .map(Path::to_path_buf)
+        .with_context(|| {
+            format!(
+                "Header file {} is not in source root {}",
+                source_file.display(),
+                source_root.display()
+            )
+        })
+}
+
 fn maybe_create_dir(path: &Path) -> anyhow::Result<()> {
     if !path.exists() {
         std::fs::create_dir_all(path)
@@ -27,15 +505,176 @@ fn maybe_create_dir(path: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn main() -> anyhow::Result<()> {
+// Whether to color diagnostics written to a terminal, unless disabled with --no-color or
+// the NO_COLOR environment variable
+fn use_color(arguments: &args::Arguments, is_terminal: bool) -> bool {
+    is_terminal
+        && !arguments.no_color
+        && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+// Formats an error like a compiler diagnostic. Parse errors in files are reported at their
+// location, other errors with their causes.
+fn error_diagnostic(error: &anyhow::Error, color: bool) -> String {
+    match error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<MocksmithError>())
+    {
+        Some(parse_error @ MocksmithError::ParseError { file: Some(_), .. }) => {
+            parse_error.diagnostic(color)
+        }
+        // Same colors as for diagnostics from the library
+        _ if color => format!("\x1b[1;31merror:\x1b[0m {error:#}"),
+        _ => format!("error: {error:#}"),
+    }
+}
+
+// JSON representation of a diagnostic
+fn diagnostic_json(diagnostic: &Diagnostic) -> serde_json::Value {
+    serde_json::json!({
+        "severity": diagnostic.severity.to_string(),
+        "message": diagnostic.message,
+        "file": diagnostic.file.as_ref().map(|file| file.to_string_lossy()),
+        "line": diagnostic.line,
+        "column": diagnostic.column,
+        "source": diagnostic.source.to_string(),
+    })
+}
+
+// Writes diagnostics as a JSON array. An error is added unless already reported as an
+// error diagnostic, e.g., for parse errors.
+fn write_diagnostics_json(
+    diagnostics: &[Diagnostic],
+    error: Option<&anyhow::Error>,
+    destination: &str,
+) -> anyhow::Result<()> {
+    let mut entries = diagnostics.iter().map(diagnostic_json).collect::<Vec<_>>();
+    if let Some(error) = error
+        && !diagnostics
+            .iter()
+            .any(|diagnostic| matches!(diagnostic.severity, Severity::Error | Severity::Fatal))
+    {
+        entries.push(serde_json::json!({
+            "severity": Severity::Error.to_string(),
+            "message": format!("{error:#}"),
+            "file": null,
+            "line": null,
+            "column": null,
+            "source": DiagnosticSource::Mocksmith.to_string(),
+        }));
+    }
+    let json = format!("{:#}\n", serde_json::Value::Array(entries));
+    match destination {
+        "stdout" => print!("{json}"),
+        "stderr" => eprint!("{json}"),
+        file => std::fs::write(file, json)
+            .with_context(|| format!("Failed to write diagnostics file {file}"))?,
+    }
+    Ok(())
+}
+
+fn main() -> std::process::ExitCode {
     let arguments = arguments();
+    let color = use_color(&arguments, std::io::stderr().is_terminal());
+    let diagnostics_output =
+        (arguments.diagnostics == "json").then(|| arguments.diagnostics_output.clone());
+    let diagnostics = Rc::new(RefCell::new(Vec::new()));
+    let result = run(
+        arguments,
+        diagnostics_output.is_some().then(|| diagnostics.clone()),
+    );
+    if let Some(destination) = &diagnostics_output {
+        let written =
+            write_diagnostics_json(&diagnostics.borrow(), result.as_ref().err(), destination);
+        if let Err(error) = written {
+            eprintln!("{}", error_diagnostic(&error, color));
+            return std::process::ExitCode::from(exitcode::exit_code(&error));
+        }
+    } else if let Err(error) = &result {
+        eprintln!("{}", error_diagnostic(error, color));
+    }
+    match &result {
+        Ok(()) => std::process::ExitCode::from(exitcode::SUCCESS),
+        Err(error) => std::process::ExitCode::from(exitcode::exit_code(error)),
+    }
+}
 
-    let log_write = if arguments.silent {
-        None
-    } else if arguments.output_dir.is_some() || arguments.output_file.is_some() {
-        Some(Box::new(std::io::stdout()) as Box<dyn std::io::Write>)
+fn run(
+    mut arguments: args::Arguments,
+    diagnostics: Option<Rc<RefCell<Vec<Diagnostic>>>>,
+) -> anyhow::Result<()> {
+    if arguments.output_file.as_deref().is_some_and(is_stdout) {
+        if arguments.in_place {
+            anyhow::bail!("The output file can't be updated in place when writing to stdout");
+        }
+        if arguments.check {
+            anyhow::bail!("Output files can't be checked when writing to stdout");
+        }
+        if arguments.diff {
+            anyhow::bail!("Changes to output files can't be diffed when writing to stdout");
+        }
+        if arguments.depfile.is_some() {
+            anyhow::bail!("A dependency file can't be written when writing to stdout");
+        }
+    }
+    // Checking files shows the changes like when diffing
+    arguments.diff |= arguments.check;
+    if let Some(files_from) = &arguments.files_from {
+        let listed_files = read_file_list(files_from, arguments.null)?;
+        if listed_files.is_empty() {
+            anyhow::bail!("No header files listed in {}", files_from.display());
+        }
+        arguments.source_files.extend(listed_files);
+    }
+    if !arguments.exclude.is_empty() {
+        let patterns = arguments
+            .exclude
+            .iter()
+            .map(|glob| (glob.contains('/'), glob_regex(glob)))
+            .collect::<Vec<_>>();
+        arguments
+            .source_files
+            .retain(|file| !is_excluded(file, &patterns));
+        if arguments.source_files.is_empty() {
+            anyhow::bail!("All header files are excluded");
+        }
+    }
+    if let Some(git_ref) = &arguments.since {
+        let changed_files = since::changed_files(git_ref)?;
+        // Headers are searched for in the current directory if no include paths are given
+        let include_dirs = if arguments.include_dir.is_empty() {
+            vec![PathBuf::from(".")]
+        } else {
+            arguments.include_dir.clone()
+        };
+        let include_dirs = [include_dirs, arguments.system_include_dir.clone()].concat();
+        let mut affected = since::AffectedFiles::new(changed_files, include_dirs);
+        arguments
+            .source_files
+            .retain(|file| affected.is_affected(file));
+        if arguments.source_files.is_empty() {
+            return Ok(());
+        }
+    }
+
+    let stderr_color = use_color(&arguments, std::io::stderr().is_terminal());
+    let (log_write, log_color) = if arguments.silent {
+        (None, false)
+    } else if arguments.output_dir.is_some()
+        || arguments
+            .output_file
+            .as_deref()
+            .is_some_and(|file| !is_stdout(file))
+    {
+        (
+            Some(Box::new(std::io::stdout()) as Box<dyn std::io::Write>),
+            use_color(&arguments, std::io::stdout().is_terminal()),
+        )
     } else {
-        Some(Box::new(std::io::stderr()) as Box<dyn std::io::Write>)
+        (
+            Some(Box::new(std::io::stderr()) as Box<dyn std::io::Write>),
+            use_color(&arguments, std::io::stderr().is_terminal()),
+        )
     };
 
     let use_simplified_nested_namespaces = if let Some(std) = &arguments.std {
@@ -46,35 +685,165 @@ fn main() -> anyhow::Result<()> {
     } else {
         true
     };
+    let include_guard_pattern = arguments.include_guard_pattern();
+    let nolint_checks = arguments.nolint_checks();
+    let line_ending = arguments.line_ending();
+    let language = arguments.language();
 
     let mut mocksmith = Mocksmith::new(log_write, arguments.verbose)
         .context("Could not create Mocksmith instance")?
+        .color_diagnostics(log_color)
         .include_paths(&arguments.include_dir)
+        .system_include_paths(&arguments.system_include_dir)
         .methods_to_mock(arguments.methods_to_mock())
+        .style(arguments.style())
+        .factory_helpers(arguments.factory_helpers())
+        .nice_strict_helpers(arguments.nice_strict_helpers())
+        .extract_interface(arguments.extract_interface)
+        .assert_not_abstract(arguments.assert_not_abstract)
+        .include_with_angle_brackets(arguments.include_angle_brackets)
+        .include_type_headers(arguments.include_type_headers)
+        .forward_declare_types(arguments.forward_declare_types)
+        .include_prefix(arguments.include_prefix)
+        .strip_include_prefix(arguments.strip_include_prefix)
         .ignore_errors(arguments.ignore_errors)
         .cpp_standard(arguments.std)
+        .language(language)
         .additional_clang_args(arguments.clang_args)
+        .sysroot(arguments.sysroot)
+        .target(arguments.target)
+        .force_includes(arguments.force_include)
+        .define_macros(arguments.defines)
+        .undefine_macros(arguments.undefines)
+        .use_env_includes(arguments.use_env_includes)
+        .detect_system_includes(arguments.detect_system_includes)
+        .qt_macros(arguments.qt)
+        .com_interfaces(arguments.com)
         .simplified_nested_namespaces(use_simplified_nested_namespaces)
+        .skip_deprecated_methods(arguments.skip_deprecated)
+        .keep_method_attributes(arguments.keep_attributes)
+        .const_overload_helpers(arguments.const_overload_helpers)
+        .move_only_helpers(arguments.move_only_helpers)
+        .reference_default_actions(arguments.reference_default_actions)
+        .value_default_actions(arguments.value_default_actions)
+        .line_directives(arguments.line_directives)
+        .signature_checks(arguments.signature_checks)
+        .mock_macro(arguments.mock_macro)
+        .mock_destructor(arguments.mock_destructor)
+        .comma_type_aliases(arguments.comma_type_aliases)
+        .keep_regions(arguments.keep_regions)
+        .mock_aliases(arguments.mock_aliases)
+        .column_limit(arguments.column_limit)
+        .mock_namespace(arguments.mock_namespace)
+        .preserve_access(arguments.preserve_access)
+        .friend_tests(arguments.friend_test)
+        .fully_qualify_types(arguments.fully_qualify_types)
+        .sort_output(arguments.sort)
+        .include_guard_pattern(include_guard_pattern)
+        .include_guard_project(arguments.include_guard_project)
+        .split_source_file(arguments.split_source)
+        .emit_fixture(arguments.emit_fixture)
+        .emit_cheat_sheet(arguments.emit_cheat_sheet)
+        .emit_documentation(arguments.emit_docs.is_some())
         .msvc_allow_overriding_deprecated_methods(arguments.msvc_allow_deprecated)
-        .parse_function_bodies(arguments.parse_function_bodies);
-    if let Some(class_filter) = &arguments.class_filter {
-        let regex = regex::Regex::new(class_filter).map_err(|err| {
-            mocksmith::MocksmithError::InvalidRegex(format!("Invalid class filter: {err}"))
-        })?;
-        mocksmith = mocksmith.class_filter_fun(move |class_name| regex.is_match(class_name));
+        .gcc_allow_overriding_deprecated_methods(arguments.gcc_allow_deprecated)
+        .suppress_mock_warnings(arguments.suppress_warnings)
+        .nolint_checks(nolint_checks)
+        .source_hash_comment(arguments.source_hash)
+        .guard_macro(arguments.guard_macro)
+        .module_name(arguments.module.clone())
+        .import_gmock(arguments.import_gmock)
+        .line_ending(line_ending)
+        .final_newline(arguments.final_newline)
+        .byte_order_mark(arguments.bom)
+        .parse_function_bodies(arguments.parse_function_bodies)
+        .collect_timings(arguments.stats);
+    if let Some(banner_file) = &arguments.banner_file {
+        let banner = std::fs::read_to_string(banner_file)
+            .with_context(|| format!("Could not read banner file {}", banner_file.display()))?;
+        mocksmith = mocksmith.banner(Some(banner));
+    } else if arguments.banner_text.is_some() {
+        mocksmith = mocksmith.banner(arguments.banner_text.clone());
+    }
+    if let Some(license_file) = &arguments.license_file {
+        let license = std::fs::read_to_string(license_file)
+            .with_context(|| format!("Could not read license file {}", license_file.display()))?;
+        mocksmith = mocksmith.license(Some(license));
+    }
+    if let Some(template_file) = &arguments.template {
+        let template = std::fs::read_to_string(template_file)
+            .with_context(|| format!("Could not read template file {}", template_file.display()))?;
+        mocksmith = mocksmith.template(Some(template));
+    }
+    if !arguments.class_filter.is_empty() {
+        let regexes = name_regexes(&arguments.class_filter, "Invalid class filter")?;
+        mocksmith = mocksmith.class_filter_fun(move |class_name| {
+            regexes.iter().any(|regex| regex.is_match(class_name))
+        });
+    }
+    if !arguments.class_exclude.is_empty() {
+        let regexes = name_regexes(&arguments.class_exclude, "Invalid class exclude filter")?;
+        mocksmith = mocksmith.class_exclude_fun(move |class_name| {
+            regexes.iter().any(|regex| regex.is_match(class_name))
+        });
+    }
+    if !arguments.method_filter.is_empty() || !arguments.method_exclude.is_empty() {
+        let filters = name_regexes(&arguments.method_filter, "Invalid method filter")?;
+        let excludes = name_regexes(&arguments.method_exclude, "Invalid method exclude filter")?;
+        mocksmith = mocksmith.method_filter_fun(move |method| {
+            (filters.is_empty() || filters.iter().any(|regex| regex.is_match(method.name)))
+                && !excludes.iter().any(|regex| regex.is_match(method.name))
+        });
+    }
+    if let Some(diagnostics) = diagnostics {
+        mocksmith = mocksmith
+            .diagnostic_fun(move |diagnostic| diagnostics.borrow_mut().push(diagnostic.clone()));
     }
     if let Some(name_sed_replacement) = &arguments.name_mock_sed_replacement {
         let namer = naming::SedReplacement::from_sed_replacement(name_sed_replacement)?;
         mocksmith = mocksmith.mock_name_fun(move |class_name| namer.name(class_name));
     }
 
+    if arguments.version_verbose {
+        println!("mocksmith {}", env!("CARGO_PKG_VERSION"));
+        println!("libclang: {}", mocksmith.clang_version());
+        println!(
+            "LIBCLANG_PATH: {}",
+            std::env::var("LIBCLANG_PATH").unwrap_or_else(|_| "not set".to_string())
+        );
+        println!("Clang arguments: {}", mocksmith.clang_arguments().join(" "));
+        return Ok(());
+    }
+    if let Some(format) = &arguments.list_classes {
+        return list_classes(&mocksmith, &arguments.source_files, format);
+    }
+    if arguments.emit_model {
+        print!(
+            "{}",
+            mocksmith.create_model_for_files(&arguments.source_files)?
+        );
+        return Ok(());
+    }
+
+    if arguments.interactive {
+        if arguments.files_from.as_deref() == Some(Path::new("-")) {
+            anyhow::bail!(
+                "Classes can't be selected interactively when reading --files-from stdin"
+            );
+        }
+        let selected = select_classes(&mocksmith, &arguments.source_files)?;
+        mocksmith = mocksmith.qualified_class_filter_fun(move |class_name| {
+            selected.iter().any(|selected| selected == class_name)
+        });
+    }
+
     // Function to name output files
-    let name_output_file: Box<dyn Fn(&mocksmith::MockHeader) -> String> =
+    let name_output_file: std::rc::Rc<dyn Fn(&mocksmith::MockHeader) -> String> =
         if let Some(name_output_file_sed_replacement) = &arguments.name_output_file_sed_replacement
         {
             let namer =
                 naming::SedReplacement::from_sed_replacement(name_output_file_sed_replacement)?;
-            Box::new(move |header: &mocksmith::MockHeader| {
+            std::rc::Rc::new(move |header: &mocksmith::MockHeader| {
                 // We should not call this if there are no mocks
                 assert!(!header.mocks.is_empty());
                 // Since only used with --output_dir there should be a source file
@@ -89,60 +858,347 @@ fn main() -> anyhow::Result<()> {
                         .to_string_lossy(),
                 )
             })
+        } else if arguments.module.is_some() {
+            std::rc::Rc::new(|header: &mocksmith::MockHeader| {
+                std::path::Path::new(&naming::default_name_output_file(header))
+                    .with_extension("cppm")
+                    .to_string_lossy()
+                    .into_owned()
+            })
         } else {
-            Box::new(naming::default_name_output_file)
+            std::rc::Rc::new(naming::default_name_output_file)
         };
+    if arguments.line_directives {
+        // Lines following mocked methods are numbered as in the written files
+        let output_file_name = arguments
+            .output_file
+            .as_deref()
+            .filter(|file| !is_stdout(file))
+            .and_then(Path::file_name)
+            .map(|file_name| file_name.to_string_lossy().into_owned());
+        let name_output_file = name_output_file.clone();
+        mocksmith = mocksmith.line_directives_file_fun(move |header| {
+            output_file_name
+                .clone()
+                .unwrap_or_else(|| name_output_file(header))
+        });
+    }
 
+    let post_process_command = arguments.post_process.as_deref();
+    let fail_on_empty = arguments.fail_on_empty.as_deref();
+    let io_timings = RefCell::new(Vec::new());
     if arguments.source_files.is_empty() {
         let mut content = String::new();
-        std::io::stdin()
-            .read_to_string(&mut content)
-            .context("Failed to read from stdin")?;
-        mocksmith
-            .create_mocks_from_string(&content)
-            .context("Could not create mocks")?
-            .into_iter()
-            .for_each(|mock| {
-                print!("{}", mock.code);
+        timed_io(&io_timings, None, || {
+            std::io::stdin().read_to_string(&mut content)
+        })
+        .context("Failed to read from stdin")?;
+        if let Some(stdin_name) = &arguments.stdin_name
+            && let Some(output_file) = &arguments.output_file
+        {
+            let header = mocksmith
+                .create_mock_header_for_string_as_file(&content, stdin_name)
+                .context("Could not create mocks")?;
+            check_mocks_generated(fail_on_empty, &[], &header.mocks)?;
+            let code = post_process(post_process_command, output_file, header.code)?;
+            timed_io(&io_timings, Some(stdin_name), || {
+                maybe_write_file(output_file, &code, arguments.always_write, arguments.diff)
+            })?;
+        } else {
+            let mocks = match &arguments.stdin_name {
+                Some(stdin_name) => {
+                    mocksmith.create_mocks_from_string_as_file(&content, stdin_name)
+                }
+                None => mocksmith.create_mocks_from_string(&content),
+            };
+            let mocks = mocks.context("Could not create mocks")?;
+            check_mocks_generated(fail_on_empty, &[], &mocks)?;
+            let code = mocks.into_iter().map(|mock| mock.code).collect::<String>();
+            let code = mocksmith.file_content(code);
+            timed_io(&io_timings, arguments.stdin_name.as_deref(), || {
+                print!("{code}")
             });
-    } else if arguments.output_file.is_some() {
+        }
+    } else if let Some(output_file) = &arguments.output_file {
         let header = mocksmith.create_mock_header_for_files(&arguments.source_files)?;
-        maybe_write_file(
-            &arguments.output_file.unwrap(),
-            &header.code,
-            arguments.always_write,
-        )?;
+        check_mocks_generated(fail_on_empty, &arguments.source_files, &header.mocks)?;
+        if arguments.in_place {
+            let existing = timed_io(&io_timings, None, || std::fs::read_to_string(output_file))
+                .with_context(|| {
+                    format!("Could not read file to update {}", output_file.display())
+                })?;
+            let updated = mocksmith
+                .update_marked_region(&existing, &header.mocks)
+                .with_context(|| format!("Could not update file {}", output_file.display()))?;
+            let updated = with_kept_regions(&mocksmith, output_file, updated);
+            let updated = post_process(post_process_command, output_file, updated)?;
+            timed_io(&io_timings, None, || {
+                maybe_write_file(
+                    output_file,
+                    &updated,
+                    arguments.always_write,
+                    arguments.diff,
+                )
+            })?;
+        } else {
+            let code = with_kept_regions(&mocksmith, output_file, header.code.clone());
+            let code = post_process(post_process_command, output_file, code)?;
+            timed_io(&io_timings, None, || {
+                maybe_write_file(output_file, &code, arguments.always_write, arguments.diff)
+            })?;
+        }
+        if let Some(depfile) = &arguments.depfile {
+            timed_io(&io_timings, None, || {
+                maybe_write_file(
+                    depfile,
+                    &depfile_rule(output_file, &header),
+                    arguments.always_write,
+                    arguments.diff,
+                )
+            })?;
+        }
     } else if let Some(output_dir) = arguments.output_dir {
-        let headers = arguments
-            .source_files
-            .iter()
-            .map(|header| {
+        let mut headers: Vec<(Vec<MockHeader>, &PathBuf)> = Vec::new();
+        let mut failed_files = Vec::new();
+        for source_file in &arguments.source_files {
+            let file_headers = if arguments.split_per_class {
+                mocksmith.create_mock_headers_per_class_for_file(source_file)
+            } else {
                 mocksmith
-                    .create_mock_header_for_files(&[header])
-                    .with_context(|| {
-                        format!(
-                            "Could not create mock header from file {}",
-                            header.display()
-                        )
-                    })
-            })
-            .collect::<anyhow::Result<Vec<MockHeader>>>()?;
-        if !arguments.no_create_output_dir {
+                    .create_mock_header_for_files(&[source_file])
+                    .map(|mock_header| vec![mock_header])
+            }
+            .with_context(|| {
+                format!(
+                    "Could not create mock header from file {}",
+                    source_file.display()
+                )
+            });
+            match file_headers {
+                Ok(file_headers) => headers.push((file_headers, source_file)),
+                // Report the failure and continue with the other files. Errors are only
+                // reported in the JSON diagnostics when written.
+                Err(error) if arguments.keep_going => {
+                    if arguments.diagnostics != "json" {
+                        eprintln!("{}", error_diagnostic(&error, stderr_color));
+                    }
+                    failed_files.push((source_file, exitcode::exit_code(&error)));
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        let parsed_files = headers
+            .iter()
+            .map(|(_, source_file)| source_file.to_path_buf())
+            .collect::<Vec<_>>();
+        check_mocks_generated(
+            fail_on_empty,
+            &parsed_files,
+            headers
+                .iter()
+                .flat_map(|(headers, _)| headers)
+                .flat_map(|header| &header.mocks),
+        )?;
+        if !arguments.no_create_output_dir && !arguments.stdout_multiplex && !arguments.diff {
             maybe_create_dir(output_dir.as_path())?;
         }
-        headers.into_iter().try_for_each(|header| {
-            if !header.mocks.is_empty() {
-                let output_file = output_dir.join(name_output_file(&header));
-                maybe_write_file(&output_file, &header.code, arguments.always_write)
-            } else {
-                // We might want to log something if no mocks are found
-                Ok(())
+        if let Some(docs_dir) = &arguments.emit_docs
+            && !arguments.stdout_multiplex
+            && !arguments.diff
+        {
+            maybe_create_dir(docs_dir)?;
+        }
+        let manifest_file = output_dir.join("mocksmith_manifest.json");
+        let previous_files = if arguments.prune {
+            manifest_files(&manifest_file)
+        } else {
+            Vec::new()
+        };
+        let written_files = std::cell::RefCell::new(Vec::new());
+        // Writes a file, or prints it after a marker line with the path relative to the
+        // output directory when multiplexing files to stdout
+        // Header file the written files are created from, for --stats
+        let io_source_file = Cell::new(None);
+        let write_file = |file: &Path, content: &str, always_write: bool| {
+            written_files.borrow_mut().push(file.to_path_buf());
+            timed_io(&io_timings, io_source_file.get(), || {
+                if arguments.stdout_multiplex {
+                    let name = file.strip_prefix(&output_dir).unwrap_or(file);
+                    print!("--- file: {}\n{content}", name.display());
+                    Ok(())
+                } else {
+                    maybe_write_file(file, content, always_write, arguments.diff)
+                }
+            })
+        };
+        let mut manifest_entries = Vec::new();
+        let mut depfile_rules = String::new();
+        headers
+            .into_iter()
+            .flat_map(|(headers, source_file)| {
+                headers.into_iter().map(move |header| (header, source_file))
+            })
+            .try_for_each(|(header, source_file)| -> anyhow::Result<()> {
+                io_source_file.set(Some(source_file.as_path()));
+                if !header.mocks.is_empty() {
+                    let mut mock_dir = output_dir.clone();
+                    if let Some(source_root) = &arguments.source_root {
+                        mock_dir.push(relative_source_dir(source_file, source_root)?);
+                        if !arguments.stdout_multiplex && !arguments.diff {
+                            maybe_create_dir(&mock_dir)?;
+                        }
+                    }
+                    let output_file = mock_dir.join(name_output_file(&header));
+                    let code = with_kept_regions(&mocksmith, &output_file, header.code.clone());
+                    let code = post_process(post_process_command, &output_file, code)?;
+                    write_file(&output_file, &code, arguments.always_write)?;
+                    depfile_rules.push_str(&depfile_rule(&output_file, &header));
+                    let header_file_name = output_file
+                        .file_name()
+                        .expect("Output file should have a name")
+                        .to_string_lossy();
+                    let mut mock_source_file = None;
+                    if let Some(source) =
+                        mocksmith.create_mock_source_for_header(&header, &header_file_name)
+                    {
+                        let file = output_file.with_extension("cpp");
+                        let source = post_process(post_process_command, &file, source)?;
+                        write_file(&file, &source, arguments.always_write)?;
+                        mock_source_file = Some(file);
+                    }
+                    let mut checks_file = None;
+                    if let Some(checks) = mocksmith.create_signature_checks_for_header(&header) {
+                        let file = output_file.with_file_name(format!(
+                            "{}_checks.h",
+                            output_file
+                                .file_stem()
+                                .expect("Output file should have a name")
+                                .to_string_lossy()
+                        ));
+                        let checks = post_process(post_process_command, &file, checks)?;
+                        write_file(&file, &checks, arguments.always_write)?;
+                        checks_file = Some(file);
+                    }
+                    let mut fixture_files = Vec::new();
+                    for mock in &header.mocks {
+                        let fixture_file = mock_dir.join(format!(
+                            "{}.cpp",
+                            naming::default_name_fixture(&mock.parent_name)
+                        ));
+                        if let Some(fixture) =
+                            mocksmith.create_fixture_for_mock(mock, &header_file_name)
+                        {
+                            // Fixtures are only skeletons to fill in, so never overwrite them
+                            if !fixture_file.exists() {
+                                let fixture =
+                                    post_process(post_process_command, &fixture_file, fixture)?;
+                                write_file(&fixture_file, &fixture, true)?;
+                            }
+                            // Kept fixtures are not stale even if not written
+                            written_files.borrow_mut().push(fixture_file.clone());
+                            fixture_files.push(Some(fixture_file));
+                        } else {
+                            fixture_files.push(None);
+                        }
+                    }
+                    if let Some(docs_dir) = &arguments.emit_docs
+                        && let Some(documentation) =
+                            mocksmith.create_documentation_for_header(&header, &output_file)
+                    {
+                        let file = docs_dir.join(
+                            output_file
+                                .with_extension("md")
+                                .file_name()
+                                .expect("Output file should have a name"),
+                        );
+                        write_file(&file, &documentation, arguments.always_write)?;
+                    }
+                    let mut cheat_sheet_files = Vec::new();
+                    for mock in &header.mocks {
+                        if let Some(cheat_sheet) = mocksmith.create_cheat_sheet_for_mock(mock) {
+                            let file = mock_dir.join(format!("{}.md", mock.name));
+                            write_file(&file, &cheat_sheet, arguments.always_write)?;
+                            cheat_sheet_files.push(Some(file));
+                        } else {
+                            cheat_sheet_files.push(None);
+                        }
+                    }
+                    manifest_entries.push(manifest_entry(
+                        source_file,
+                        &output_file,
+                        mock_source_file.as_deref(),
+                        checks_file.as_deref(),
+                        &header,
+                        &fixture_files,
+                        &cheat_sheet_files,
+                    ));
+                    Ok(())
+                } else {
+                    // We might want to log something if no mocks are found
+                    Ok(())
+                }
+            })?;
+        io_source_file.set(None);
+        if let Some(depfile) = &arguments.depfile {
+            timed_io(&io_timings, None, || {
+                maybe_write_file(
+                    depfile,
+                    &depfile_rules,
+                    arguments.always_write,
+                    arguments.diff,
+                )
+            })?;
+        }
+        if arguments.manifest {
+            let manifest = serde_json::json!({ "headers": manifest_entries });
+            write_file(
+                &manifest_file,
+                &format!("{manifest:#}\n"),
+                arguments.always_write,
+            )?;
+        }
+        if arguments.prune {
+            prune(
+                &output_dir,
+                &previous_files,
+                &written_files.borrow(),
+                arguments.diff,
+            )?;
+        }
+        if let Some((_, exit_code)) = failed_files.first() {
+            // Exit with the code of the first failure, e.g., for parse errors
+            return Err(Failure::WithExitCode {
+                message: format!(
+                    "Could not create mocks from {} of {} header files:\n{}",
+                    failed_files.len(),
+                    arguments.source_files.len(),
+                    failed_files
+                        .iter()
+                        .map(|(file, _)| format!("  {}", file.display()))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                ),
+                exit_code: *exit_code,
             }
-        })?;
+            .into());
+        }
     } else {
         let header = mocksmith.create_mock_header_for_files(&arguments.source_files)?;
-        print!("{}", header.code);
+        check_mocks_generated(fail_on_empty, &arguments.source_files, &header.mocks)?;
+        timed_io(&io_timings, None, || print!("{}", header.code));
     }
 
+    if arguments.check && diff::changes_printed() {
+        return Err(Failure::Stale(
+            "Mock files are out of date, regenerate them to apply the changes shown".to_string(),
+        )
+        .into());
+    }
+    if arguments.stats {
+        eprint!(
+            "{}",
+            stats::stats_table(&mocksmith.take_timings(), &io_timings.into_inner())
+        );
+    }
     Ok(())
 }
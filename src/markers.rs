@@ -0,0 +1,186 @@
+// Regions in existing files delimited by marker comments, to mix generated and
+// hand-written code in the same file
+
+// Marker comments delimiting the generated region of a file
+pub(crate) const BEGIN_MARKER: &str = "// mocksmith:begin";
+pub(crate) const END_MARKER: &str = "// mocksmith:end";
+
+// Marker comments delimiting a named region in generated code, e.g.,
+// `// mocksmith:keep(MockFoo)`, whose content is kept when regenerating the code
+pub(crate) const KEEP_MARKER: &str = "// mocksmith:keep";
+pub(crate) const END_KEEP_MARKER: &str = "// mocksmith:endkeep";
+
+// Marker line starting a region with the given name
+pub(crate) fn keep_marker(name: &str) -> String {
+    format!("{KEEP_MARKER}({name})")
+}
+
+// Copies the content of named regions in an existing file to the regions with the same
+// names in regenerated code. Regions only in the regenerated code are left empty, and
+// regions only in the existing file are dropped.
+pub(crate) fn keep_regions(existing: &str, generated: &str) -> String {
+    let existing_regions = named_regions(existing);
+    let mut content = String::new();
+    let mut lines = generated.split_inclusive('\n');
+    while let Some(line) = lines.next() {
+        content.push_str(line);
+        let Some(name) = keep_region_name(line) else {
+            continue;
+        };
+        let Some(kept) = existing_regions
+            .iter()
+            .find(|(existing_name, _)| *existing_name == name)
+            .map(|(_, kept)| kept)
+        else {
+            continue;
+        };
+        // Skip the generated content of the region, up to the end marker
+        for line in lines.by_ref() {
+            if line.trim() == END_KEEP_MARKER {
+                content.push_str(kept);
+                content.push_str(line);
+                break;
+            }
+        }
+    }
+    content
+}
+
+// Named regions in a file, with the lines between the marker lines
+fn named_regions(content: &str) -> Vec<(&str, String)> {
+    let mut regions = Vec::new();
+    let mut lines = content.split_inclusive('\n');
+    while let Some(line) = lines.next() {
+        if let Some(name) = keep_region_name(line) {
+            let kept = lines
+                .by_ref()
+                .take_while(|line| line.trim() != END_KEEP_MARKER)
+                .collect::<String>();
+            regions.push((name, kept));
+        }
+    }
+    regions
+}
+
+fn keep_region_name(line: &str) -> Option<&str> {
+    line.trim()
+        .strip_prefix(KEEP_MARKER)?
+        .strip_prefix('(')?
+        .strip_suffix(')')
+}
+
+// Replaces the lines between the begin and end markers in a file with generated code,
+// keeping the marker lines and all lines outside the region
+pub(crate) fn replace_region(existing: &str, generated: &str) -> crate::Result<String> {
+    let is_marker = |line: &str, marker: &str| line.trim() == marker;
+    let lines = existing.split_inclusive('\n').collect::<Vec<_>>();
+    let begins = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| is_marker(line, BEGIN_MARKER))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    let begin = match begins.as_slice() {
+        [begin] => *begin,
+        [] => return Err(marker_error(&format!("missing {BEGIN_MARKER}"))),
+        _ => return Err(marker_error(&format!("more than one {BEGIN_MARKER}"))),
+    };
+    let end = lines
+        .iter()
+        .skip(begin + 1)
+        .position(|line| is_marker(line, END_MARKER))
+        .map(|i| i + begin + 1)
+        .ok_or_else(|| marker_error(&format!("missing {END_MARKER} after {BEGIN_MARKER}")))?;
+
+    let mut content = lines[..=begin].concat();
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(generated);
+    if !generated.is_empty() && !generated.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&lines[end..].concat());
+    Ok(content)
+}
+
+fn marker_error(message: &str) -> crate::MocksmithError {
+    crate::MocksmithError::MarkerError(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn region_between_markers_is_replaced() {
+        let existing = "#include <foo.h>\n\
+                        // mocksmith:begin\n\
+                        old mock\n\
+                        // mocksmith:end\n\
+                        TEST(Foo, Bar) {}\n";
+        assert_eq!(
+            replace_region(existing, "new mock\n").unwrap(),
+            "#include <foo.h>\n\
+             // mocksmith:begin\n\
+             new mock\n\
+             // mocksmith:end\n\
+             TEST(Foo, Bar) {}\n"
+        );
+    }
+
+    #[test]
+    fn indented_markers_are_found() {
+        let existing = "  // mocksmith:begin\n  // mocksmith:end";
+        assert_eq!(
+            replace_region(existing, "mock").unwrap(),
+            "  // mocksmith:begin\nmock\n  // mocksmith:end"
+        );
+    }
+
+    #[test]
+    fn content_of_named_regions_is_kept() {
+        let existing = "class MockFoo {\n\
+                        \x20 // mocksmith:keep(MockFoo)\n\
+                        \x20 int helper;\n\
+                        \x20 // mocksmith:endkeep\n\
+                        \x20 old();\n\
+                        };\n";
+        let generated = "class MockFoo {\n\
+                         \x20 // mocksmith:keep(MockFoo)\n\
+                         \x20 // mocksmith:endkeep\n\
+                         \x20 new();\n\
+                         };\n\
+                         class MockBar {\n\
+                         \x20 // mocksmith:keep(MockBar)\n\
+                         \x20 // mocksmith:endkeep\n\
+                         };\n";
+        assert_eq!(
+            keep_regions(existing, generated),
+            "class MockFoo {\n\
+             \x20 // mocksmith:keep(MockFoo)\n\
+             \x20 int helper;\n\
+             \x20 // mocksmith:endkeep\n\
+             \x20 new();\n\
+             };\n\
+             class MockBar {\n\
+             \x20 // mocksmith:keep(MockBar)\n\
+             \x20 // mocksmith:endkeep\n\
+             };\n"
+        );
+    }
+
+    #[test]
+    fn missing_or_repeated_markers_are_errors() {
+        assert!(replace_region("// mocksmith:end\n", "").is_err());
+        assert!(replace_region("// mocksmith:begin\n", "").is_err());
+        assert!(replace_region("// mocksmith:end\n// mocksmith:begin\n", "").is_err());
+        assert!(
+            replace_region(
+                "// mocksmith:begin\n// mocksmith:end\n// mocksmith:begin\n// mocksmith:end\n",
+                ""
+            )
+            .is_err()
+        );
+    }
+}
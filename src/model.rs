@@ -1,34 +1,180 @@
+mod json;
+mod parsed;
+mod qualify;
+mod source;
+
+use std::path::PathBuf;
+
+// Access specifier of a method in its class
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Access {
+    Public,
+    Protected,
+    Private,
+}
+
+impl std::fmt::Display for Access {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Access::Public => "public",
+            Access::Protected => "protected",
+            Access::Private => "private",
+        })
+    }
+}
+
 // Represents a class that shall be mocked
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct ClassToMock {
     pub(crate) name: String,
     pub(crate) namespaces: Vec<String>,
     pub(crate) methods: Vec<MethodToMock>,
+    pub(crate) inherits_iunknown: bool,
+    // Whether the destructor of the class is virtual, declared so in the class or a base
+    pub(crate) has_virtual_destructor: bool,
+    // Whether the class has constructors with arguments, own or inherited from a base
+    // class, which the mock needs to forward
+    pub(crate) has_constructors_to_forward: bool,
+    // Names of methods imported from base classes with using declarations
+    pub(crate) using_declarations: Vec<String>,
+    // Names of all accessible methods and method templates declared in the class, once
+    // per overload, to find overloads that are not mocked
+    pub(crate) overloads: Vec<String>,
+    // Names of private methods and method templates declared in the class, which can't be
+    // brought into the mock with using declarations
+    pub(crate) private_overloads: Vec<String>,
+    // Indirect base classes inherited more than once without virtual inheritance on all
+    // paths, which makes conversions from the class (and mock) to them ambiguous
+    pub(crate) ambiguous_bases: Vec<String>,
+    // Names of pure virtual methods left out from mocking, e.g., by method filters,
+    // which makes the mock abstract
+    pub(crate) unmocked_pure_virtual_methods: Vec<String>,
+    // Names of methods declared in the class that are not mocked, with the reasons
+    pub(crate) skipped_methods: Vec<(String, String)>,
+    // File and line of the class declaration, where macros are expanded
+    pub(crate) declaration_location: Option<(PathBuf, u32)>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct MethodToMock {
     pub(crate) name: String,
     pub(crate) result_type: String,
     pub(crate) arguments: Vec<Argument>,
     pub(crate) is_const: bool,
     pub(crate) is_virtual: bool,
+    pub(crate) is_pure_virtual: bool,
     pub(crate) is_noexcept: bool,
+    pub(crate) is_deprecated: bool,
+    // gmock cannot return a default value for references, so such methods need actions
+    pub(crate) returns_reference: bool,
+    // Expression for a default value of the result type, e.g., `0` or `Foo{}`, if the type
+    // is default constructible and copyable, to be returned with `::testing::Return`
+    pub(crate) default_return_value: Option<String>,
+    // Standard attributes of the method, e.g., "nodiscard", without brackets
+    pub(crate) attributes: Vec<String>,
     pub(crate) ref_qualifier: Option<String>,
+    pub(crate) calltype: Option<String>,
+    // Access specifier of the method in the mocked class
+    pub(crate) access: Access,
+    // Types in the signature declared in other files than the mocked file and system
+    // headers
+    pub(crate) type_uses: Vec<TypeUse>,
+    // Fully qualified names of the types in the signature, e.g., "foo::Bar"
+    pub(crate) qualified_type_names: Vec<String>,
+    // File and line of the method declaration, where macros are expanded
+    pub(crate) declaration_location: Option<(PathBuf, u32)>,
 }
 
-#[derive(Debug)]
+// Use of a type in a method signature, where the type is declared in another header
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TypeUse {
+    pub(crate) header: PathBuf,
+    // Forward declaration of the type, e.g., `namespace foo { class Bar; }`, if the type
+    // is only used by pointer or reference and can be forward declared
+    pub(crate) forward_declaration: Option<String>,
+}
+
+#[derive(Clone, Debug)]
 pub(crate) struct Argument {
     pub(crate) type_name: String,
     pub(crate) name: Option<String>,
 }
 
-// Finds classes to mock in the main file of a translation unit
+impl Argument {
+    // Declares the argument with the given name. Names of function pointers and arrays are
+    // placed inside the type, e.g., `void (*callback)(int)` and `int values[8]`.
+    pub(crate) fn declaration(&self, name: &str) -> String {
+        if let Some(position) = declarator_name_position(&self.type_name) {
+            let (before, after) = self.type_name.split_at(position);
+            let separator = if before.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+                " "
+            } else {
+                ""
+            };
+            format!("{before}{separator}{name}{after}")
+        } else {
+            format!("{} {}", self.type_name, name)
+        }
+    }
+
+    // Declares the argument with its own name, if it has any
+    pub(crate) fn declaration_with_own_name(&self) -> String {
+        match &self.name {
+            Some(name) => self.declaration(name),
+            None => self.type_name.clone(),
+        }
+    }
+}
+
+// Finds where the name of an argument goes in a type containing a declarator, i.e.,
+// inside the parentheses of a function pointer or reference, `void (*)(int)`, or before
+// the brackets of an array, `int [8]`
+fn declarator_name_position(type_name: &str) -> Option<usize> {
+    let mut start = 0;
+    while let Some(open) = type_name[start..].find('(').map(|i| i + start) {
+        let close = matching_parenthesis(type_name, open)?;
+        let inner = type_name[open + 1..close]
+            .trim_end_matches(|c: char| c.is_alphanumeric() || c == '_' || c == ' ');
+        let in_template =
+            type_name[..open].matches('<').count() > type_name[..open].matches('>').count();
+        if !in_template && inner.ends_with(['*', '&', '^']) {
+            return Some(close);
+        }
+        start = open + 1;
+    }
+    type_name.find(" [").map(|i| i + 1)
+}
+
+fn matching_parenthesis(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Macros used to declare COM methods with the stdcall calling convention
+const STDCALL_MACROS: [&str; 3] = ["STDMETHODCALLTYPE", "STDMETHOD", "STDMETHOD_"];
+
+// Finds classes to mock in the main file of a translation unit. Structs are only
+// considered when `include_structs` is set, since COM interfaces are declared as structs.
+// The meta-object methods of Qt classes are only skipped when `qt_macros` is set.
 pub(crate) fn classes_in_translation_unit(
     root: &clang::TranslationUnit,
     methods_to_mock: crate::MethodsToMockStrategy,
+    include_structs: bool,
+    qt_macros: bool,
 ) -> Vec<ClassToMock> {
-    AstTraverser::new(root, methods_to_mock).traverse()
+    AstTraverser::new(root, methods_to_mock, include_structs, qt_macros).traverse()
 }
 
 impl ClassToMock {
@@ -36,6 +182,7 @@ impl ClassToMock {
         class: &clang::Entity,
         namespaces: &Vec<clang::Entity>,
         methods_to_mock: crate::MethodsToMockStrategy,
+        qt_macros: bool,
     ) -> Self {
         Self {
             name: class.get_name().expect("Class should have a name"),
@@ -43,17 +190,199 @@ impl ClassToMock {
                 .iter()
                 .map(|ns| ns.get_name().expect("Namespace should have a name"))
                 .collect::<Vec<_>>(),
-            methods: class
+            methods: mockable_methods(class, methods_to_mock, qt_macros)
+                .iter()
+                .map(MethodToMock::from_entity)
+                .collect(),
+            skipped_methods: skipped_methods(class, methods_to_mock, qt_macros),
+            inherits_iunknown: inherits_iunknown(class),
+            has_virtual_destructor: has_virtual_destructor(class),
+            has_constructors_to_forward: class.get_children().iter().any(|child| {
+                match child.get_kind() {
+                    clang::EntityKind::Constructor => {
+                        !child.is_default_constructor()
+                            && !child.is_copy_constructor()
+                            && !child.is_move_constructor()
+                    }
+                    clang::EntityKind::UsingDeclaration => is_inherited_constructors(class, child),
+                    _ => false,
+                }
+            }),
+            using_declarations: class
+                .get_children()
+                .iter()
+                .filter(|child| child.get_kind() == clang::EntityKind::UsingDeclaration)
+                .filter(|using| !is_inherited_constructors(class, using))
+                .filter_map(|using| using.get_name())
+                .fold(Vec::new(), |mut names, name| {
+                    // The same name may be imported from several bases, e.g., in a diamond
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                    names
+                }),
+            overloads: class
+                .get_children()
+                .iter()
+                .filter(|child| {
+                    matches!(
+                        child.get_kind(),
+                        clang::EntityKind::Method | clang::EntityKind::FunctionTemplate
+                    ) && child.get_accessibility() != Some(clang::Accessibility::Private)
+                })
+                .filter_map(|method| method.get_name())
+                .collect(),
+            private_overloads: class
                 .get_children()
                 .iter()
-                .filter(|child| child.get_kind() == clang::EntityKind::Method)
-                .filter(|method| methods_to_mock.should_mock(method))
-                .map(|method| MethodToMock::from_entity(method))
+                .filter(|child| {
+                    matches!(
+                        child.get_kind(),
+                        clang::EntityKind::Method | clang::EntityKind::FunctionTemplate
+                    ) && child.get_accessibility() == Some(clang::Accessibility::Private)
+                })
+                .filter_map(|method| method.get_name())
                 .collect(),
+            ambiguous_bases: ambiguous_bases(class),
+            unmocked_pure_virtual_methods: Vec::new(),
+            declaration_location: expansion_location(class),
+        }
+    }
+}
+
+impl ClassToMock {
+    // Name of the class qualified with its namespaces, e.g., `foo::Bar`
+    pub(crate) fn qualified_name(&self) -> String {
+        self.namespaces
+            .iter()
+            .chain(std::iter::once(&self.name))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+
+    // Text describing the class and its methods to mock, independent of the formatting of
+    // the source, to detect changes of the class
+    pub(crate) fn declaration_text(&self) -> String {
+        let mut text = format!("class {}\n", self.qualified_name());
+        for method in &self.methods {
+            text.push_str(&format!(
+                "{}: {}{} {}({}){}{}{}{}\n",
+                method.access,
+                if method.is_virtual { "virtual " } else { "" },
+                method.result_type,
+                method.name,
+                method
+                    .arguments
+                    .iter()
+                    .map(|arg| arg.type_name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                if method.is_const { " const" } else { "" },
+                method
+                    .ref_qualifier
+                    .as_ref()
+                    .map(|ref_qualifier| format!(" {ref_qualifier}"))
+                    .unwrap_or_default(),
+                if method.is_noexcept { " noexcept" } else { "" },
+                if method.is_pure_virtual { " = 0" } else { "" },
+            ));
         }
+        text
     }
 }
 
+// Checks if a using declaration inherits constructors, i.e., `using Base::Base`
+fn is_inherited_constructors(class: &clang::Entity, using: &clang::Entity) -> bool {
+    let Some(name) = using.get_name() else {
+        return false;
+    };
+    base_class_names(class).contains(&name)
+}
+
+// Finds base classes that are inherited more than once where not all paths use virtual
+// inheritance
+fn ambiguous_bases(class: &clang::Entity) -> Vec<String> {
+    let mut bases = Vec::new();
+    collect_bases(class, &mut bases);
+
+    let mut ambiguous: Vec<String> = Vec::new();
+    for (name, _) in &bases {
+        let non_virtual_paths = bases
+            .iter()
+            .filter(|(other, is_virtual)| other == name && !is_virtual)
+            .count();
+        let has_virtual_path = bases
+            .iter()
+            .any(|(other, is_virtual)| other == name && *is_virtual);
+        if (non_virtual_paths > 1 || (non_virtual_paths == 1 && has_virtual_path))
+            && !ambiguous.contains(name)
+        {
+            ambiguous.push(name.clone());
+        }
+    }
+    ambiguous
+}
+
+// Recursively collects the names of all base classes, once per inheritance path, and
+// whether the base is inherited virtually
+fn collect_bases(class: &clang::Entity, bases: &mut Vec<(String, bool)>) {
+    for base in class
+        .get_children()
+        .iter()
+        .filter(|child| child.get_kind() == clang::EntityKind::BaseSpecifier)
+    {
+        let Some(base_type) = base.get_type() else {
+            continue;
+        };
+        bases.push((
+            base_type.get_canonical_type().get_display_name(),
+            base.is_virtual_base(),
+        ));
+        if let Some(definition) = base_type
+            .get_declaration()
+            .and_then(|declaration| declaration.get_definition())
+        {
+            collect_bases(&definition, bases);
+        }
+    }
+}
+
+// Whether a class derives from IUnknown, directly or through other base classes
+fn inherits_iunknown(class: &clang::Entity) -> bool {
+    let mut bases = Vec::new();
+    collect_bases(class, &mut bases);
+    bases.iter().any(|(name, _)| name == "IUnknown")
+}
+
+// Whether a class declares a virtual destructor, itself or in a base class
+fn has_virtual_destructor(class: &clang::Entity) -> bool {
+    class
+        .get_children()
+        .iter()
+        .any(|child| match child.get_kind() {
+            clang::EntityKind::Destructor => child.is_virtual_method(),
+            clang::EntityKind::BaseSpecifier => child
+                .get_type()
+                .and_then(|base_type| base_type.get_declaration())
+                .and_then(|declaration| declaration.get_definition())
+                .is_some_and(|definition| has_virtual_destructor(&definition)),
+            _ => false,
+        })
+}
+
+// Names of the direct base classes of a class, without namespaces and template arguments
+fn base_class_names(class: &clang::Entity) -> Vec<String> {
+    class
+        .get_children()
+        .iter()
+        .filter(|child| child.get_kind() == clang::EntityKind::BaseSpecifier)
+        .filter_map(|base| base.get_type())
+        .filter_map(|base_type| base_type.get_declaration())
+        .filter_map(|base| base.get_name())
+        .collect()
+}
+
 impl MethodToMock {
     fn from_entity(method: &clang::Entity) -> Self {
         Self {
@@ -67,30 +396,408 @@ impl MethodToMock {
                 .expect("Method should have arguments")
                 .iter()
                 .map(|arg| Argument {
-                    type_name: arg
-                        .get_type()
-                        .expect("Argument should have a type")
-                        .get_display_name(),
+                    type_name: source::extract_argument_type_from_source(arg).unwrap_or_else(
+                        || {
+                            arg.get_type()
+                                .expect("Argument should have a type")
+                                .get_display_name()
+                        },
+                    ),
                     name: arg.get_name(),
                 })
                 .collect(),
             is_const: method.is_const_method(),
             is_virtual: method.is_virtual_method(),
+            is_pure_virtual: method.is_pure_virtual_method(),
             is_noexcept: (method.get_exception_specification()
                 == Some(clang::ExceptionSpecification::BasicNoexcept)),
+            is_deprecated: method.get_availability() == clang::Availability::Deprecated,
+            returns_reference: method.get_result_type().is_some_and(|t| {
+                matches!(
+                    t.get_kind(),
+                    clang::TypeKind::LValueReference | clang::TypeKind::RValueReference
+                )
+            }),
+            default_return_value: method.get_result_type().and_then(|t| default_value(&t)),
+            attributes: standard_attributes(method),
             ref_qualifier: method.get_type().and_then(|t| t.get_ref_qualifier()).map(
                 |rq| match rq {
                     clang::RefQualifier::LValue => "&".to_string(),
                     clang::RefQualifier::RValue => "&&".to_string(),
                 },
             ),
+            calltype: uses_stdcall(method).then(|| "STDMETHODCALLTYPE".to_string()),
+            access: match method.get_accessibility() {
+                Some(clang::Accessibility::Protected) => Access::Protected,
+                Some(clang::Accessibility::Private) => Access::Private,
+                _ => Access::Public,
+            },
+            type_uses: type_uses(&type_declarations(method)),
+            qualified_type_names: type_declarations(method)
+                .iter()
+                .filter_map(|(declaration, _)| qualify::qualified_name(declaration))
+                .collect(),
+            declaration_location: expansion_location(method),
+        }
+    }
+}
+
+// File and line of an entity, where macros are expanded
+fn expansion_location(entity: &clang::Entity) -> Option<(PathBuf, u32)> {
+    entity.get_location().and_then(|location| {
+        let location = location.get_expansion_location();
+        location.file.map(|file| (file.get_path(), location.line))
+    })
+}
+
+// Expression for a default value of a type, if the type is default constructible and
+// copyable. Deleted constructors are reported as unavailable by Clang. Abstract classes
+// need no check, since they can't be returned by value.
+fn default_value(type_: &clang::Type) -> Option<String> {
+    let canonical = type_.get_canonical_type();
+    match canonical.get_kind() {
+        clang::TypeKind::Bool => Some("false".to_string()),
+        clang::TypeKind::Pointer
+        | clang::TypeKind::MemberPointer
+        | clang::TypeKind::BlockPointer
+        | clang::TypeKind::Nullptr => Some("nullptr".to_string()),
+        clang::TypeKind::Float | clang::TypeKind::Double | clang::TypeKind::LongDouble => {
+            Some("0".to_string())
         }
+        _ if canonical.is_integer() => Some("0".to_string()),
+        clang::TypeKind::Enum => Some(value_initialization(type_)),
+        clang::TypeKind::Record => {
+            let declaration = canonical.get_declaration()?.get_definition()?;
+            // Members of implicitly instantiated templates are found in the template
+            let declaration = declaration.get_template().unwrap_or(declaration);
+            is_default_constructible_and_copyable(&declaration).then(|| value_initialization(type_))
+        }
+        _ => None,
+    }
+}
+
+fn value_initialization(type_: &clang::Type) -> String {
+    let name = type_.get_display_name();
+    format!(
+        "{}{{}}",
+        name.trim_start_matches("const ").trim_end_matches(" const")
+    )
+}
+
+fn is_default_constructible_and_copyable(class: &clang::Entity) -> bool {
+    let usable = |entity: &clang::Entity| {
+        entity.get_accessibility() == Some(clang::Accessibility::Public)
+            && entity.get_availability() != clang::Availability::Unavailable
+    };
+    let children = class.get_children();
+    let constructors = children
+        .iter()
+        .filter(|child| child.get_kind() == clang::EntityKind::Constructor)
+        .collect::<Vec<_>>();
+    let default_constructible = constructors.is_empty()
+        || constructors
+            .iter()
+            .any(|constructor| constructor.is_default_constructor() && usable(constructor));
+    let copy_constructors = constructors
+        .iter()
+        .filter(|constructor| constructor.is_copy_constructor())
+        .collect::<Vec<_>>();
+    // Declaring a move constructor deletes the implicit copy constructor
+    let copyable = if copy_constructors.is_empty() {
+        !constructors
+            .iter()
+            .any(|constructor| constructor.is_move_constructor())
+    } else {
+        copy_constructors
+            .iter()
+            .any(|constructor| usable(constructor))
+    };
+    default_constructible && copyable
+}
+
+// Finds the declarations of the types used in the signature of a method, and whether
+// each type is only used by pointer or reference
+fn type_declarations<'tu>(method: &clang::Entity<'tu>) -> Vec<(clang::Entity<'tu>, bool)> {
+    let mut declarations = Vec::new();
+    let argument_types = method
+        .get_arguments()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|arg| arg.get_type())
+        .collect::<Vec<_>>();
+    for type_ in method.get_result_type().into_iter().chain(argument_types) {
+        collect_type_declarations(type_, false, &mut declarations);
     }
+    declarations
+}
+
+// Collects the declarations of a type and its template arguments, looking through
+// pointers and references, e.g., both `Foo` and `Bar` for `const Foo<Bar> &`
+fn collect_type_declarations<'tu>(
+    type_: clang::Type<'tu>,
+    by_reference: bool,
+    declarations: &mut Vec<(clang::Entity<'tu>, bool)>,
+) {
+    match type_.get_kind() {
+        clang::TypeKind::Pointer
+        | clang::TypeKind::LValueReference
+        | clang::TypeKind::RValueReference => {
+            if let Some(pointee) = type_.get_pointee_type() {
+                collect_type_declarations(pointee, true, declarations);
+            }
+            return;
+        }
+        clang::TypeKind::Elaborated => {
+            if let Some(elaborated) = type_.get_elaborated_type() {
+                collect_type_declarations(elaborated, by_reference, declarations);
+            }
+            return;
+        }
+        _ => {}
+    }
+    let template_arguments = type_
+        .get_template_argument_types()
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    if let Some(declaration) = type_.get_declaration() {
+        // Templates can't be forward declared without their template parameters
+        let by_reference = by_reference && template_arguments.is_empty();
+        match declarations
+            .iter_mut()
+            .find(|(other, _)| *other == declaration)
+        {
+            Some((_, only_by_reference)) => *only_by_reference &= by_reference,
+            None => declarations.push((declaration, by_reference)),
+        }
+    }
+    for argument in template_arguments {
+        collect_type_declarations(argument, false, declarations);
+    }
+}
+
+// Uses of types declared in other files than the main file and system headers
+fn type_uses(declarations: &[(clang::Entity, bool)]) -> Vec<TypeUse> {
+    let mut uses = Vec::new();
+    for (declaration, only_by_reference) in declarations
+        .iter()
+        .filter(|(declaration, _)| !declaration.is_in_main_file())
+        .filter(|(declaration, _)| !declaration.is_in_system_header())
+    {
+        if let Some(file) = declaration
+            .get_location()
+            .and_then(|location| location.get_file_location().file)
+        {
+            uses.push(TypeUse {
+                header: file.get_path(),
+                forward_declaration: only_by_reference
+                    .then(|| forward_declaration(declaration))
+                    .flatten(),
+            });
+        }
+    }
+    uses
+}
+
+// Forward declaration of a class or struct declared in a namespace, e.g.,
+// `namespace foo { class Bar; }`. Nested classes can't be forward declared.
+fn forward_declaration(declaration: &clang::Entity) -> Option<String> {
+    let keyword = match declaration.get_kind() {
+        clang::EntityKind::ClassDecl => "class",
+        clang::EntityKind::StructDecl => "struct",
+        _ => return None,
+    };
+    let mut code = format!("{keyword} {};", declaration.get_name()?);
+    let mut parent = declaration.get_semantic_parent();
+    while let Some(entity) = parent {
+        match entity.get_kind() {
+            clang::EntityKind::TranslationUnit => break,
+            clang::EntityKind::Namespace => {
+                code = format!("namespace {} {{ {code} }}", entity.get_name()?);
+            }
+            _ => return None,
+        }
+        parent = entity.get_semantic_parent();
+    }
+    Some(code)
+}
+
+// Finds the standard attributes of a method which are meaningful for callers of a mock.
+// Only attributes exposed by libclang can be found, and arguments of attributes are lost.
+fn standard_attributes(method: &clang::Entity) -> Vec<String> {
+    let mut attributes = Vec::new();
+    if method
+        .get_children()
+        .iter()
+        .any(|child| child.get_kind() == clang::EntityKind::WarnUnusedResultAttr)
+    {
+        attributes.push("nodiscard".to_string());
+    }
+    if method.get_availability() == clang::Availability::Deprecated {
+        attributes.push("deprecated".to_string());
+    }
+    attributes
+}
+
+impl MethodToMock {
+    // Rewrites the types in the signature to their fully qualified spellings
+    pub(crate) fn qualify_types(&mut self) {
+        self.qualify_types_matching(|_| true);
+    }
+
+    // Rewrites the types nested in a class, given its qualified name, to their fully
+    // qualified spellings, to refer to them outside of the class
+    pub(crate) fn qualify_types_nested_in(&mut self, class_name: &str) {
+        let prefix = format!("{class_name}::");
+        self.qualify_types_matching(|qualified_name| qualified_name.starts_with(&prefix));
+    }
+
+    fn qualify_types_matching(&mut self, filter: impl Fn(&str) -> bool) {
+        for qualified_name in self
+            .qualified_type_names
+            .iter()
+            .filter(|qualified_name| filter(qualified_name))
+        {
+            self.result_type = qualify::qualify_type_name(&self.result_type, qualified_name);
+            for argument in &mut self.arguments {
+                argument.type_name =
+                    qualify::qualify_type_name(&argument.type_name, qualified_name);
+            }
+        }
+    }
+
+    pub(crate) fn info<'a>(&'a self, class_name: &'a str) -> crate::MethodInfo<'a> {
+        let mut signature = format!(
+            "{} ({})",
+            self.result_type,
+            self.arguments
+                .iter()
+                .map(|arg| arg.type_name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if self.is_const {
+            signature.push_str(" const");
+        }
+        if let Some(ref_qualifier) = &self.ref_qualifier {
+            signature.push(' ');
+            signature.push_str(ref_qualifier);
+        }
+        if self.is_noexcept {
+            signature.push_str(" noexcept");
+        }
+        crate::MethodInfo {
+            class_name,
+            name: &self.name,
+            signature,
+            is_const: self.is_const,
+            is_virtual: self.is_virtual,
+            is_noexcept: self.is_noexcept,
+            ref_qualifier: self.ref_qualifier.as_deref(),
+        }
+    }
+}
+
+// Checks if a method uses the stdcall calling convention, either by its type or by being
+// declared with the COM macros. The macros are checked since the calling convention is
+// ignored by Clang for targets without stdcall, e.g., x86-64.
+fn uses_stdcall(method: &clang::Entity) -> bool {
+    method.get_type().and_then(|t| t.get_calling_convention())
+        == Some(clang::CallingConvention::Stdcall)
+        || method.get_range().is_some_and(|range| {
+            range
+                .tokenize()
+                .iter()
+                .any(|token| STDCALL_MACROS.contains(&token.get_spelling().as_str()))
+        })
+}
+
+// Methods declared by Qt's Q_OBJECT macro, which are implemented by moc and should never
+// be mocked
+const QT_META_OBJECT_METHODS: [&str; 3] = ["metaObject", "qt_metacast", "qt_metacall"];
+
+// Finds the methods of a class that should be mocked
+fn mockable_methods<'a>(
+    class: &clang::Entity<'a>,
+    methods_to_mock: crate::MethodsToMockStrategy,
+    qt_macros: bool,
+) -> Vec<clang::Entity<'a>> {
+    methods_with_skip_reasons(class, methods_to_mock, qt_macros)
+        .into_iter()
+        .filter(|(_, reason)| reason.is_none())
+        .map(|(method, _)| method)
+        .collect()
+}
+
+// Finds the methods of a class that should not be mocked, with the reasons
+fn skipped_methods(
+    class: &clang::Entity,
+    methods_to_mock: crate::MethodsToMockStrategy,
+    qt_macros: bool,
+) -> Vec<(String, String)> {
+    methods_with_skip_reasons(class, methods_to_mock, qt_macros)
+        .into_iter()
+        .filter_map(|(method, reason)| {
+            Some((
+                method.get_name().expect("Method should have a name"),
+                reason?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+// Pairs the methods of a class with the reasons for not mocking them, if any. Methods in
+// Qt signal sections are skipped since signals are implemented by moc rather than by the
+// class, like the meta-object methods when Qt macros are predefined.
+fn methods_with_skip_reasons<'a>(
+    class: &clang::Entity<'a>,
+    methods_to_mock: crate::MethodsToMockStrategy,
+    qt_macros: bool,
+) -> Vec<(clang::Entity<'a>, Option<&'static str>)> {
+    let mut in_signal_section = false;
+    class
+        .get_children()
+        .into_iter()
+        .filter_map(|child| {
+            if child.get_kind() == clang::EntityKind::AccessSpecifier {
+                in_signal_section = is_qt_signal_section(&child);
+            }
+            if child.get_kind() != clang::EntityKind::Method {
+                return None;
+            }
+            let reason = if in_signal_section {
+                Some("Qt signal")
+            } else if qt_macros && is_qt_meta_object_method(&child) {
+                Some("implemented by moc")
+            } else {
+                methods_to_mock.skip_reason(&child)
+            };
+            Some((child, reason))
+        })
+        .collect()
+}
+
+fn is_qt_meta_object_method(method: &clang::Entity) -> bool {
+    method
+        .get_name()
+        .is_some_and(|name| QT_META_OBJECT_METHODS.contains(&name.as_str()))
+}
+
+// Checks if an access specifier starts a Qt signal section, i.e., if it is annotated with
+// "qt_signal" like Qt does when `QT_ANNOTATE_ACCESS_SPECIFIER` is defined
+fn is_qt_signal_section(access_specifier: &clang::Entity) -> bool {
+    access_specifier.get_children().iter().any(|child| {
+        child.get_kind() == clang::EntityKind::AnnotateAttr
+            && child.get_name().as_deref() == Some("qt_signal")
+    })
 }
 
 struct AstTraverser<'a> {
     root: clang::Entity<'a>,
     methods_to_mock: crate::MethodsToMockStrategy,
+    include_structs: bool,
+    qt_macros: bool,
 
     classes: Vec<ClassToMock>,
     namespace_stack: Vec<clang::Entity<'a>>,
@@ -100,10 +807,14 @@ impl<'a> AstTraverser<'a> {
     pub fn new(
         root: &'a clang::TranslationUnit<'a>,
         methods_to_mock: crate::MethodsToMockStrategy,
+        include_structs: bool,
+        qt_macros: bool,
     ) -> Self {
         Self {
             root: root.get_entity(),
             methods_to_mock,
+            include_structs,
+            qt_macros,
             classes: Vec::new(),
             namespace_stack: Vec::new(),
         }
@@ -116,14 +827,15 @@ impl<'a> AstTraverser<'a> {
 
     fn traverse_recursive(&mut self, entity: clang::Entity<'a>) {
         match entity.get_kind() {
-            clang::EntityKind::ClassDecl => {
-                if entity.is_definition() && self.should_mock_class(&entity) {
-                    self.classes.push(ClassToMock::from_entity(
-                        &entity,
-                        &self.namespace_stack,
-                        self.methods_to_mock,
-                    ));
-                }
+            clang::EntityKind::ClassDecl | clang::EntityKind::StructDecl
+                if entity.is_definition() && self.should_mock_class(&entity) =>
+            {
+                self.classes.push(ClassToMock::from_entity(
+                    &entity,
+                    &self.namespace_stack,
+                    self.methods_to_mock,
+                    self.qt_macros,
+                ));
             }
 
             clang::EntityKind::Namespace => {
@@ -145,18 +857,90 @@ impl<'a> AstTraverser<'a> {
     }
 
     fn should_mock_class(&self, class: &clang::Entity) -> bool {
-        class.get_children().iter().any(|child| {
-            child.get_kind() == clang::EntityKind::Method && self.methods_to_mock.should_mock(child)
-        })
+        (self.include_structs || class.get_kind() == clang::EntityKind::ClassDecl)
+            && !mockable_methods(class, self.methods_to_mock, self.qt_macros).is_empty()
     }
 }
 
 impl crate::MethodsToMockStrategy {
-    fn should_mock(self, method: &clang::Entity) -> bool {
+    // Reason for not mocking a method with the strategy, or None if it should be mocked
+    fn skip_reason(self, method: &clang::Entity) -> Option<&'static str> {
         match self {
-            crate::MethodsToMockStrategy::All => !method.is_static_method(),
-            crate::MethodsToMockStrategy::AllVirtual => method.is_virtual_method(),
-            crate::MethodsToMockStrategy::OnlyPureVirtual => method.is_pure_virtual_method(),
+            crate::MethodsToMockStrategy::All => method.is_static_method().then_some("static"),
+            crate::MethodsToMockStrategy::AllVirtual => {
+                (!method.is_virtual_method()).then_some("not virtual")
+            }
+            crate::MethodsToMockStrategy::OnlyPureVirtual => {
+                (!method.is_pure_virtual_method()).then_some("not pure virtual")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn declaration(type_name: &str, name: &str) -> String {
+        Argument {
+            type_name: type_name.to_string(),
+            name: Some(name.to_string()),
         }
+        .declaration(name)
+    }
+
+    #[test]
+    fn name_is_appended_to_simple_types() {
+        assert_eq!(declaration("int", "value"), "int value");
+        assert_eq!(
+            declaration("const std::string &", "text"),
+            "const std::string & text"
+        );
+        assert_eq!(
+            declaration("std::function<void (int)>", "callback"),
+            "std::function<void (int)> callback"
+        );
+    }
+
+    #[test]
+    fn name_is_placed_inside_function_pointers_and_arrays() {
+        assert_eq!(declaration("void (*)(int)", "cb"), "void (*cb)(int)");
+        assert_eq!(
+            declaration("void (&)(int, int)", "cb"),
+            "void (&cb)(int, int)"
+        );
+        assert_eq!(
+            declaration("void (*const)(int)", "cb"),
+            "void (*const cb)(int)"
+        );
+        assert_eq!(declaration("int [8]", "values"), "int values[8]");
+        assert_eq!(declaration("int (&)[8]", "values"), "int (&values)[8]");
+    }
+
+    #[test]
+    fn name_is_placed_correctly_in_types_with_non_ascii_identifiers() {
+        assert_eq!(declaration("Größe", "värde"), "Größe värde");
+        assert_eq!(
+            declaration("void (*)(Größe)", "återanrop"),
+            "void (*återanrop)(Größe)"
+        );
+        assert_eq!(declaration("Größe [8]", "värden"), "Größe värden[8]");
+    }
+
+    #[test]
+    fn name_is_placed_correctly_for_pointers_to_members() {
+        assert_eq!(declaration("int Foo::*", "member"), "int Foo::* member");
+        assert_eq!(
+            declaration("void (Foo::*)(int) const", "method"),
+            "void (Foo::*method)(int) const"
+        );
+    }
+
+    #[test]
+    fn name_is_placed_in_innermost_declarator_of_nested_function_types() {
+        assert_eq!(
+            declaration("void (*(*)(int))(double)", "factory"),
+            "void (*(*factory)(int))(double)"
+        );
     }
 }
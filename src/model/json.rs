@@ -0,0 +1,56 @@
+// JSON representation of the model, for other tools to consume the parse results
+use super::{ClassToMock, MethodToMock};
+use serde_json::{Value, json};
+use std::path::PathBuf;
+
+impl ClassToMock {
+    pub(crate) fn to_json(&self) -> Value {
+        let (file, line) = location_json(&self.declaration_location);
+        json!({
+            "name": self.name,
+            "qualified_name": self.qualified_name(),
+            "namespaces": self.namespaces,
+            "file": file,
+            "line": line,
+            "methods": self.methods.iter().map(MethodToMock::to_json).collect::<Vec<_>>(),
+            "skipped_methods": self
+                .skipped_methods
+                .iter()
+                .map(|(name, reason)| json!({ "name": name, "reason": reason }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl MethodToMock {
+    fn to_json(&self) -> Value {
+        let (file, line) = location_json(&self.declaration_location);
+        json!({
+            "name": self.name,
+            "result_type": self.result_type,
+            "arguments": self
+                .arguments
+                .iter()
+                .map(|arg| json!({ "type_name": arg.type_name, "name": arg.name }))
+                .collect::<Vec<_>>(),
+            "is_const": self.is_const,
+            "is_virtual": self.is_virtual,
+            "is_pure_virtual": self.is_pure_virtual,
+            "is_noexcept": self.is_noexcept,
+            "is_deprecated": self.is_deprecated,
+            "ref_qualifier": self.ref_qualifier,
+            "calltype": self.calltype,
+            "access": self.access.to_string(),
+            "attributes": self.attributes,
+            "file": file,
+            "line": line,
+        })
+    }
+}
+
+fn location_json(location: &Option<(PathBuf, u32)>) -> (Value, Value) {
+    match location {
+        Some((file, line)) => (json!(file.to_string_lossy()), json!(line)),
+        None => (Value::Null, Value::Null),
+    }
+}
@@ -0,0 +1,47 @@
+// Public, owned representation of the model, for library users to analyze the parse
+// results
+use super::{ClassToMock, MethodToMock};
+use crate::{ParsedArgument, ParsedClass, ParsedMethod};
+
+impl ClassToMock {
+    pub(crate) fn to_parsed(&self) -> ParsedClass {
+        let (file, line) = self.declaration_location.clone().unzip();
+        ParsedClass {
+            name: self.name.clone(),
+            namespaces: self.namespaces.clone(),
+            file,
+            line: line.unwrap_or(0),
+            methods: self.methods.iter().map(MethodToMock::to_parsed).collect(),
+            skipped_methods: self.skipped_methods.clone(),
+        }
+    }
+}
+
+impl MethodToMock {
+    fn to_parsed(&self) -> ParsedMethod {
+        let (file, line) = self.declaration_location.clone().unzip();
+        ParsedMethod {
+            name: self.name.clone(),
+            result_type: self.result_type.clone(),
+            arguments: self
+                .arguments
+                .iter()
+                .map(|arg| ParsedArgument {
+                    type_name: arg.type_name.clone(),
+                    name: arg.name.clone(),
+                })
+                .collect(),
+            is_const: self.is_const,
+            is_virtual: self.is_virtual,
+            is_pure_virtual: self.is_pure_virtual,
+            is_noexcept: self.is_noexcept,
+            is_deprecated: self.is_deprecated,
+            ref_qualifier: self.ref_qualifier.clone(),
+            calltype: self.calltype.clone(),
+            access: self.access.to_string(),
+            attributes: self.attributes.clone(),
+            file,
+            line: line.unwrap_or(0),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+// Qualification of type names, to make mocks independent of `using namespace`
+// directives and unqualified names in mocked headers
+
+// Fully qualified name of a type declaration, e.g., `foo::Bar`. Anonymous namespaces and
+// reserved namespaces, typically inline namespaces of standard libraries like
+// `std::__1`, are left out, since they are not needed to refer to the type.
+pub(crate) fn qualified_name(declaration: &clang::Entity) -> Option<String> {
+    let mut names = vec![declaration.get_name()?];
+    let mut parent = declaration.get_semantic_parent();
+    while let Some(entity) = parent {
+        match (entity.get_kind(), entity.get_name()) {
+            (clang::EntityKind::TranslationUnit, _) => break,
+            (clang::EntityKind::Namespace, None) => {}
+            (clang::EntityKind::Namespace, Some(name))
+                if name.is_empty() || name.starts_with("__") => {}
+            (_, name) => names.push(name?),
+        }
+        parent = entity.get_semantic_parent();
+    }
+    names.reverse();
+    Some(names.join("::"))
+}
+
+// Qualifies the occurrences of a name in a type with the fully qualified name, e.g.,
+// `const Bar &` becomes `const foo::Bar &` for `foo::Bar`. Partially qualified names are
+// completed, e.g., `inner::Bar` for `foo::inner::Bar`, while names qualified with other
+// namespaces are kept.
+pub(crate) fn qualify_type_name(type_name: &str, qualified_name: &str) -> String {
+    let name = qualified_name.rsplit("::").next().unwrap_or(qualified_name);
+    let mut result = String::new();
+    let mut copied = 0;
+    for (start, end) in identifier_ranges(type_name) {
+        if &type_name[start..end] != name {
+            continue;
+        }
+        let mut qualified_start = start;
+        while type_name[..qualified_start].ends_with("::") {
+            let before = &type_name[..qualified_start - 2];
+            let qualifier_length = before.len() - before.trim_end_matches(is_identifier_char).len();
+            if qualifier_length == 0 {
+                break;
+            }
+            qualified_start -= 2 + qualifier_length;
+        }
+        let spelled = &type_name[qualified_start..end];
+        if qualified_start >= copied
+            && (qualified_name == spelled || qualified_name.ends_with(&format!("::{spelled}")))
+        {
+            result.push_str(&type_name[copied..qualified_start]);
+            result.push_str(qualified_name);
+            copied = end;
+        }
+    }
+    result.push_str(&type_name[copied..]);
+    result
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// Byte ranges of the identifiers in a text
+fn identifier_ranges(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (start, is_identifier_char(c)) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                ranges.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        ranges.push((s, text.len()));
+    }
+    ranges
+        .into_iter()
+        .filter(|(s, _)| !text[*s..].starts_with(|c: char| c.is_ascii_digit()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unqualified_names_are_qualified() {
+        assert_eq!(
+            qualify_type_name("const Bar &", "foo::Bar"),
+            "const foo::Bar &"
+        );
+        assert_eq!(
+            qualify_type_name("std::vector<Bar>", "foo::Bar"),
+            "std::vector<foo::Bar>"
+        );
+        assert_eq!(
+            qualify_type_name("Map<Bar, Bar> *", "foo::Bar"),
+            "Map<foo::Bar, foo::Bar> *"
+        );
+    }
+
+    #[test]
+    fn partially_qualified_names_are_completed() {
+        assert_eq!(
+            qualify_type_name("inner::Bar", "foo::inner::Bar"),
+            "foo::inner::Bar"
+        );
+        assert_eq!(qualify_type_name("::foo::Bar", "foo::Bar"), "::foo::Bar");
+    }
+
+    #[test]
+    fn other_names_are_kept() {
+        assert_eq!(qualify_type_name("other::Bar", "foo::Bar"), "other::Bar");
+        assert_eq!(qualify_type_name("BarBaz", "foo::Bar"), "BarBaz");
+        assert_eq!(qualify_type_name("int [8]", "foo::Bar"), "int [8]");
+    }
+}
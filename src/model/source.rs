@@ -0,0 +1,121 @@
+// Extraction of types as spelled in the source code, rather than as presented by Clang
+// after expanding macros
+
+// Extracts the type of an argument as spelled in the source, to keep macros used in the
+// type, e.g., `DWORD`. Returns `None` if the source spelling doesn't differ from the type
+// presented by Clang, or if the type can't be extracted, e.g., when the whole argument is
+// the result of a macro expansion.
+pub(crate) fn extract_argument_type_from_source(argument: &clang::Entity) -> Option<String> {
+    let display_name = argument.get_type()?.get_display_name();
+    let tokens = source_tokens(argument)?;
+
+    // Skip default value and the name of the argument, if any. The name is not necessarily
+    // last, e.g., for function pointers, `void (*callback)(int)`, and arrays,
+    // `int values[8]`.
+    let end = tokens
+        .iter()
+        .position(|token| token.get_spelling() == "=")
+        .unwrap_or(tokens.len());
+    let mut tokens = tokens.into_iter().take(end).collect::<Vec<_>>();
+    if let Some(name) = argument.get_name().filter(|name| !name.is_empty()) {
+        let name_index = tokens
+            .iter()
+            .rposition(|token| token.get_spelling() == name)?;
+        tokens.remove(name_index);
+    }
+    if tokens.is_empty() {
+        return None;
+    }
+
+    let display_words = identifier_words(&display_name);
+    if tokens
+        .iter()
+        .filter(|token| token.get_kind() == clang::token::TokenKind::Identifier)
+        .all(|token| display_words.contains(&token.get_spelling().as_str()))
+    {
+        return None;
+    }
+
+    Some(join_type_tokens(
+        &tokens
+            .iter()
+            .map(|token| token.get_spelling())
+            .collect::<Vec<_>>(),
+    ))
+}
+
+// Tokens of an entity as written in the source. Locations inside macro expansions are
+// mapped to the locations where the macros are expanded, so macro invocations are
+// included rather than the tokens from the macro definitions.
+fn source_tokens<'tu>(entity: &clang::Entity<'tu>) -> Option<Vec<clang::token::Token<'tu>>> {
+    let range = entity.get_range()?;
+    let start = range.get_start().get_expansion_location();
+    let end = range.get_end().get_expansion_location();
+    let file = start.file?;
+    if end.file? != file || end.offset < start.offset {
+        return None;
+    }
+    let range = clang::source::SourceRange::new(
+        file.get_offset_location(start.offset),
+        file.get_offset_location(end.offset),
+    );
+    Some(range.tokenize())
+}
+
+fn identifier_words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+// Joins tokens of a type with spaces in the same style as Clang presents types, e.g.,
+// `const std::map<int, int> &`
+fn join_type_tokens(tokens: &[String]) -> String {
+    let mut text = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 {
+            let previous = tokens[i - 1].as_str();
+            let no_space = matches!(token.as_str(), "::" | "<" | ">" | ">>" | "," | ")" | "]")
+                || matches!(previous, "::" | "<" | "(" | "[")
+                || (matches!(token.as_str(), "(" | "[") && matches!(previous, ")" | "]"));
+            if !no_space {
+                text.push(' ');
+            }
+        }
+        text.push_str(token);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join(tokens: &[&str]) -> String {
+        join_type_tokens(&tokens.iter().map(|t| t.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn identifier_words_handle_non_ascii_identifiers() {
+        assert_eq!(
+            identifier_words("const Größe<Ünit> &"),
+            vec!["const", "Größe", "Ünit"]
+        );
+    }
+
+    #[test]
+    fn tokens_are_joined_in_clang_style() {
+        assert_eq!(join(&["const", "DWORD", "&"]), "const DWORD &");
+        assert_eq!(join(&["const", "char", "*"]), "const char *");
+        assert_eq!(
+            join(&["std", "::", "map", "<", "int", ",", "MY_INT", ">"]),
+            "std::map<int, MY_INT>"
+        );
+        assert_eq!(
+            join(&["void", "(", "*", ")", "(", "DWORD", ")"]),
+            "void (*)(DWORD)"
+        );
+        assert_eq!(join(&["DWORD", "[", "8", "]"]), "DWORD [8]");
+        assert_eq!(join(&["int", "(", "&", ")", "[", "8", "]"]), "int (&)[8]");
+    }
+}
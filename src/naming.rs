@@ -5,21 +5,41 @@ use crate::MockHeader;
 /// This function generates a mock name by stripping common prefixes or suffixes like
 /// "Interface", "Ifc", or "I" from the class name and prepending "Mock" to it.
 pub fn default_name_mock(class_name: &str) -> String {
+    format!("Mock{}", strip_interface_affixes(class_name))
+}
+
+/// Default function to generate fake names.
+///
+/// This function generates a fake name like `default_name_mock()`, but prepends "Fake"
+/// rather than "Mock".
+pub fn default_name_fake(class_name: &str) -> String {
+    format!("Fake{}", strip_interface_affixes(class_name))
+}
+
+/// Default function to generate names of test fixtures.
+///
+/// This function generates a fixture name by stripping interface affixes like
+/// `default_name_mock()`, and appending "Test".
+pub fn default_name_fixture(class_name: &str) -> String {
+    format!("{}Test", strip_interface_affixes(class_name))
+}
+
+fn strip_interface_affixes(class_name: &str) -> &str {
     if class_name.ends_with("Interface") {
-        format!("Mock{}", class_name.strip_suffix("Interface").unwrap())
+        class_name.strip_suffix("Interface").unwrap()
     } else if class_name.ends_with("Ifc") {
-        format!("Mock{}", class_name.strip_suffix("Ifc").unwrap())
+        class_name.strip_suffix("Ifc").unwrap()
     } else if class_name.starts_with("Interface") {
-        format!("Mock{}", class_name.strip_prefix("Interface").unwrap())
+        class_name.strip_prefix("Interface").unwrap()
     } else if class_name.starts_with("Ifc") {
-        format!("Mock{}", class_name.strip_prefix("Ifc").unwrap())
+        class_name.strip_prefix("Ifc").unwrap()
     } else if class_name.starts_with("I")
         && class_name.len() > 1
         && class_name.chars().nth(1).unwrap().is_uppercase()
     {
-        format!("Mock{}", class_name.strip_prefix("I").unwrap())
+        class_name.strip_prefix("I").unwrap()
     } else {
-        format!("Mock{class_name}")
+        class_name
     }
 }
 
@@ -129,16 +149,20 @@ mod tests {
         assert_eq!(default_name_mock("I"), "MockI");
     }
 
+    #[test]
+    fn test_default_name_fake() {
+        assert_eq!(default_name_fake("MyTypeInterface"), "FakeMyType");
+        assert_eq!(default_name_fake("IMyType"), "FakeMyType");
+        assert_eq!(default_name_fake("MyType"), "FakeMyType");
+    }
+
     #[test]
     fn default_name_output_file_uses_mock_name_when_only_one_mock() {
         let info = MockHeader {
-            mocks: vec![Mock {
-                source_file: Some(std::path::PathBuf::from("source.h")),
-                parent_name: "ISomething".to_string(),
-                name: "MockSomething".to_string(),
-                code: String::new(),
-            }],
+            mocks: vec![Mock::test_mock("MockSomething", Some("source.h"))],
             code: String::new(),
+            mock_spans: Vec::new(),
+            includes_span: None,
         };
 
         assert_eq!(default_name_output_file(&info), "MockSomething.h");
@@ -147,13 +171,10 @@ mod tests {
     #[test]
     fn default_name_output_file_uses_extension_from_source_file() {
         let info = MockHeader {
-            mocks: vec![Mock {
-                source_file: Some(std::path::PathBuf::from("source.hpp")),
-                parent_name: "ISomething".to_string(),
-                name: "MockSomething".to_string(),
-                code: String::new(),
-            }],
+            mocks: vec![Mock::test_mock("MockSomething", Some("source.hpp"))],
             code: String::new(),
+            mock_spans: Vec::new(),
+            includes_span: None,
         };
 
         assert_eq!(default_name_output_file(&info), "MockSomething.hpp");
@@ -163,20 +184,12 @@ mod tests {
     fn default_name_output_file_uses_source_file_with_suffix_when_several_mocks() {
         let info = MockHeader {
             mocks: vec![
-                Mock {
-                    source_file: Some(std::path::PathBuf::from("source.hpp")),
-                    parent_name: "ISomething".to_string(),
-                    name: "MockSomething".to_string(),
-                    code: String::new(),
-                },
-                Mock {
-                    source_file: Some(std::path::PathBuf::from("source.hpp")),
-                    parent_name: "IOther".to_string(),
-                    name: "MockOther".to_string(),
-                    code: String::new(),
-                },
+                Mock::test_mock("MockSomething", Some("source.hpp")),
+                Mock::test_mock("MockOther", Some("source.hpp")),
             ],
             code: String::new(),
+            mock_spans: Vec::new(),
+            includes_span: None,
         };
 
         assert_eq!(default_name_output_file(&info), "source_mocks.hpp");
@@ -186,20 +199,12 @@ mod tests {
     fn default_name_output_file_falls_back_to_mock_h_if_different_source_files() {
         let info = MockHeader {
             mocks: vec![
-                Mock {
-                    source_file: Some(std::path::PathBuf::from("ISomething.h")),
-                    parent_name: "ISomething".to_string(),
-                    name: "MockSomething".to_string(),
-                    code: String::new(),
-                },
-                Mock {
-                    source_file: Some(std::path::PathBuf::from("IOther.h")),
-                    parent_name: "IOther".to_string(),
-                    name: "MockOther".to_string(),
-                    code: String::new(),
-                },
+                Mock::test_mock("MockSomething", Some("ISomething.h")),
+                Mock::test_mock("MockOther", Some("IOther.h")),
             ],
             code: String::new(),
+            mock_spans: Vec::new(),
+            includes_span: None,
         };
 
         assert_eq!(default_name_output_file(&info), "mocks.h");
@@ -209,20 +214,12 @@ mod tests {
     fn default_name_output_file_falls_back_to_mock_h_when_no_source_file() {
         let info = MockHeader {
             mocks: vec![
-                Mock {
-                    source_file: None,
-                    parent_name: "ISomething".to_string(),
-                    name: "MockSomething".to_string(),
-                    code: String::new(),
-                },
-                Mock {
-                    source_file: None,
-                    parent_name: "IOther".to_string(),
-                    name: "MockOther".to_string(),
-                    code: String::new(),
-                },
+                Mock::test_mock("MockSomething", None),
+                Mock::test_mock("MockOther", None),
             ],
             code: String::new(),
+            mock_spans: Vec::new(),
+            includes_span: None,
         };
 
         assert_eq!(default_name_output_file(&info), "mocks.h");
@@ -233,6 +230,8 @@ mod tests {
         let info = MockHeader {
             mocks: vec![],
             code: String::new(),
+            mock_spans: Vec::new(),
+            includes_span: None,
         };
 
         assert_eq!(default_name_output_file(&info), "mocks.h");
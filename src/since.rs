@@ -0,0 +1,172 @@
+// Selection of the header files changed since a git ref, or including changed headers, to
+// only regenerate the mocks that may have changed
+
+use anyhow::Context;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+// Include directive, with the opening delimiter and the name of the included header
+static INCLUDE_REGEX: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r#"^[ \t]*#[ \t]*include[ \t]*([<"])([^>"]+)[>"]"#)
+        .expect("Include regex should be valid")
+});
+
+// Files changed in the working tree compared to a git ref, including untracked files, as
+// canonical paths
+pub(crate) fn changed_files(git_ref: &str) -> anyhow::Result<Vec<PathBuf>> {
+    let top_level = PathBuf::from(git(&["rev-parse", "--show-toplevel"])?.trim());
+    let changed = git(&["diff", "--name-only", git_ref, "--"])?;
+    let untracked = git(&["ls-files", "--others", "--exclude-standard", "--full-name"])?;
+    Ok(changed
+        .lines()
+        .chain(untracked.lines())
+        .filter(|line| !line.is_empty())
+        .map(|line| canonical(&top_level.join(line)))
+        .collect())
+}
+
+// Finds the header files that are changed, or include changed headers directly or
+// indirectly. Includes are found without parsing, so conditional includes are always
+// followed. The includes of each visited file and the files known to be affected or not
+// are kept, so headers shared by several inputs are only scanned once.
+pub(crate) struct AffectedFiles {
+    // Changed files and the files found to include them
+    affected: HashSet<PathBuf>,
+    include_dirs: Vec<PathBuf>,
+    includes: HashMap<PathBuf, Vec<PathBuf>>,
+    unaffected: HashSet<PathBuf>,
+}
+
+impl AffectedFiles {
+    pub(crate) fn new(changed_files: Vec<PathBuf>, include_dirs: Vec<PathBuf>) -> Self {
+        Self {
+            affected: changed_files.into_iter().collect(),
+            include_dirs,
+            includes: HashMap::new(),
+            unaffected: HashSet::new(),
+        }
+    }
+
+    // Whether a header file, or a header it includes directly or indirectly, is changed
+    pub(crate) fn is_affected(&mut self, file: &Path) -> bool {
+        let file = canonical(file);
+        let mut visited = HashSet::new();
+        let mut pending = vec![file.clone()];
+        while let Some(pending_file) = pending.pop() {
+            if self.affected.contains(&pending_file) {
+                self.affected.insert(file);
+                return true;
+            }
+            if self.unaffected.contains(&pending_file) || !visited.insert(pending_file.clone()) {
+                continue;
+            }
+            pending.extend(self.included_files(&pending_file).iter().cloned());
+        }
+        // All files included by the visited files were visited without finding changes
+        self.unaffected.extend(visited);
+        false
+    }
+
+    // Paths of the headers included by a file, that can be found
+    fn included_files(&mut self, file: &Path) -> &[PathBuf] {
+        if !self.includes.contains_key(file) {
+            let content = std::fs::read(file).unwrap_or_default();
+            let included = includes(&String::from_utf8_lossy(&content))
+                .into_iter()
+                .filter_map(|(name, quoted)| {
+                    resolve_include(file, &name, quoted, &self.include_dirs)
+                })
+                .collect();
+            self.includes.insert(file.to_path_buf(), included);
+        }
+        &self.includes[file]
+    }
+}
+
+fn git(arguments: &[&str]) -> anyhow::Result<String> {
+    let output = std::process::Command::new("git")
+        .args(arguments)
+        .output()
+        .context("Could not run git")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            arguments.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+// Names of the headers included in a file, and whether they are included with quotes
+fn includes(content: &str) -> Vec<(String, bool)> {
+    content
+        .lines()
+        .filter_map(|line| INCLUDE_REGEX.captures(line))
+        .map(|captures| (captures[2].to_string(), &captures[1] == "\""))
+        .collect()
+}
+
+// Path of an included header, searched for relative to the including file if included
+// with quotes, and then in the include directories
+fn resolve_include(
+    file: &Path,
+    name: &str,
+    quoted: bool,
+    include_dirs: &[PathBuf],
+) -> Option<PathBuf> {
+    let relative_dir = file.parent().filter(|_| quoted);
+    relative_dir
+        .into_iter()
+        .chain(include_dirs.iter().map(PathBuf::as_path))
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+        .map(|path| canonical(&path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn includes_are_found_with_quotes_or_angle_brackets() {
+        assert_eq!(
+            includes(
+                "#include \"first.h\"\n  #  include <dir/second.h>\n// #include \"third.h\"\n"
+            ),
+            vec![
+                ("first.h".to_string(), true),
+                ("dir/second.h".to_string(), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn headers_including_changed_headers_indirectly_are_affected() {
+        let dir = tempfile::tempdir().unwrap();
+        let include_dir = dir.path().join("include");
+        std::fs::create_dir(&include_dir).unwrap();
+        let write = |path: &Path, content: &str| {
+            std::fs::write(path, content).unwrap();
+            canonical(path)
+        };
+        let changed = write(&include_dir.join("changed.h"), "struct Changed {};\n");
+        let middle = write(&dir.path().join("middle.h"), "#include <changed.h>\n");
+        let top = write(&dir.path().join("top.h"), "#include \"middle.h\"\n");
+        let other = write(&dir.path().join("other.h"), "#include \"unchanged.h\"\n");
+        write(&dir.path().join("unchanged.h"), "#include \"other.h\"\n");
+
+        let mut affected = AffectedFiles::new(vec![changed], vec![include_dir]);
+        assert!(affected.is_affected(&top));
+        assert!(affected.is_affected(&middle));
+        assert!(!affected.is_affected(&other));
+        assert!(!affected.is_affected(&other));
+    }
+}
@@ -0,0 +1,59 @@
+// Locations of the parts of a generated mock header, from the ranges of lines recorded
+// when generating the header. Transforms of the complete code, e.g., line endings and
+// byte order mark, don't change the number of lines, so the byte ranges are taken from
+// the final code.
+use crate::Span;
+use std::ops::Range;
+
+// Span of a range of lines, counted from 0, including the line ending of the last line
+pub(crate) fn lines_span(code: &str, lines: Range<usize>) -> Span {
+    Span {
+        bytes: line_start(code, lines.start)..line_start(code, lines.end),
+        lines: lines.start + 1..lines.end + 1,
+    }
+}
+
+// Byte offset of the start of a line, counted from 0, or the length of the code for lines
+// after the last line
+fn line_start(code: &str, line: usize) -> usize {
+    if line == 0 {
+        return 0;
+    }
+    code.match_indices('\n')
+        .nth(line - 1)
+        .map_or(code.len(), |(index, _)| index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_cover_complete_lines() {
+        let code = "#pragma once\n\n#include \"a.h\"\n#include <gmock/gmock.h>\n\n\
+                    class MockA {\n};\n\nclass MockB {};\n";
+        let span = lines_span(code, 5..7);
+        assert_eq!(
+            span,
+            Span {
+                bytes: 55..72,
+                lines: 6..8
+            }
+        );
+        assert_eq!(&code[span.bytes], "class MockA {\n};\n");
+        assert_eq!(lines_span(code, 8..9).bytes, 73..89);
+        assert_eq!(lines_span(code, 2..4).bytes, 14..54);
+    }
+
+    #[test]
+    fn spans_cover_crlf_line_endings() {
+        let code = "#pragma once\r\n\r\nclass MockA {\r\n};\r\n";
+        assert_eq!(
+            lines_span(code, 2..4),
+            Span {
+                bytes: 16..35,
+                lines: 3..5
+            }
+        );
+    }
+}
@@ -0,0 +1,117 @@
+// Table of the time spent in each phase of creating mocks, printed with --stats
+
+use mocksmith::{Phase, Timing};
+use std::{path::PathBuf, time::Duration};
+
+// Time spent in each phase for a file
+#[derive(Clone, Copy, Default)]
+struct Row {
+    parse: Duration,
+    traversal: Duration,
+    generation: Duration,
+    io: Duration,
+}
+
+impl Row {
+    fn add(&mut self, other: &Row) {
+        self.parse += other.parse;
+        self.traversal += other.traversal;
+        self.generation += other.generation;
+        self.io += other.io;
+    }
+
+    fn total(&self) -> Duration {
+        self.parse + self.traversal + self.generation + self.io
+    }
+}
+
+// Formats the timings per file, in the order the files were handled, and in total. I/O
+// not done for a specific file is only included in the total.
+pub(crate) fn stats_table(
+    timings: &[Timing],
+    io_timings: &[(Option<PathBuf>, Duration)],
+) -> String {
+    let mut rows: Vec<(String, Row)> = Vec::new();
+    for timing in timings {
+        let name = timing
+            .file
+            .as_ref()
+            .map_or("<stdin>".to_string(), |file| file.display().to_string());
+        let row = row(&mut rows, name);
+        match timing.phase {
+            Phase::Parse => row.parse += timing.duration,
+            Phase::Traversal => row.traversal += timing.duration,
+            Phase::Generation => row.generation += timing.duration,
+            // Phases added to the library later have no column
+            _ => {}
+        }
+    }
+    let mut other_io = Duration::ZERO;
+    for (file, duration) in io_timings {
+        match file {
+            Some(file) => row(&mut rows, file.display().to_string()).io += *duration,
+            None => other_io += *duration,
+        }
+    }
+
+    let mut total = Row {
+        io: other_io,
+        ..Row::default()
+    };
+    rows.iter().for_each(|(_, row)| total.add(row));
+    rows.push(("total".to_string(), total));
+
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let mut table = format!(
+        "{:<width$} {:>10} {:>10} {:>10} {:>10} {:>10}\n",
+        "file", "parse", "traversal", "generation", "I/O", "total"
+    );
+    for (name, row) in &rows {
+        table.push_str(&format!(
+            "{name:<width$} {:>10} {:>10} {:>10} {:>10} {:>10}\n",
+            milliseconds(row.parse),
+            milliseconds(row.traversal),
+            milliseconds(row.generation),
+            milliseconds(row.io),
+            milliseconds(row.total())
+        ));
+    }
+    table
+}
+
+// Row of the file with the given name, added last if not present
+fn row(rows: &mut Vec<(String, Row)>, name: String) -> &mut Row {
+    let index = match rows.iter().position(|(row_name, _)| *row_name == name) {
+        Some(index) => index,
+        None => {
+            rows.push((name, Row::default()));
+            rows.len() - 1
+        }
+    };
+    &mut rows[index].1
+}
+
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timings_are_summed_per_file_and_in_total() {
+        let ms = Duration::from_millis;
+        let io_timings = [
+            (Some(PathBuf::from("a.h")), ms(4)),
+            (Some(PathBuf::from("a.h")), ms(1)),
+            (None, ms(10)),
+        ];
+        assert_eq!(
+            stats_table(&[], &io_timings),
+            "file       parse  traversal generation        I/O      total\n\
+             a.h       0.0 ms     0.0 ms     0.0 ms     5.0 ms     5.0 ms\n\
+             total     0.0 ms     0.0 ms     0.0 ms    15.0 ms    15.0 ms\n"
+        );
+    }
+}
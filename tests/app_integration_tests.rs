@@ -2,6 +2,7 @@ mod assertions;
 #[allow(dead_code)]
 mod helpers;
 mod paths;
+#[allow(clippy::collapsible_if)]
 mod program_under_test;
 
 use helpers::{
@@ -83,6 +84,565 @@ fn input_from_file_produces_complete_header_when_output_to_dir() {
     );
 }
 
+#[test]
+fn excluded_header_files_are_skipped() {
+    let input_dir = temp_dir();
+    let source_file = input_dir.path().join("something.h");
+    std::fs::write(&source_file, some_class("ISomething")).unwrap();
+    let excluded_file = input_dir.path().join("other_generated.h");
+    std::fs::write(&excluded_file, some_class("IOther")).unwrap();
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            "--exclude=*_generated.h"
+        ])
+        .source_file(&source_file)
+        .source_file(&excluded_file)
+        .run()
+        .wait()
+        .success()
+    );
+    assert!(output_dir.path().join("MockSomething.h").is_file());
+    assert!(!output_dir.path().join("MockOther.h").exists());
+}
+
+#[test]
+fn depfile_lists_mocked_and_included_headers() {
+    let input_dir = temp_dir();
+    let types_file = input_dir.path().join("types.h");
+    std::fs::write(&types_file, "struct Bar {};\n").unwrap();
+    let source_file = input_dir.path().join("something.h");
+    std::fs::write(
+        &source_file,
+        format!("#include \"types.h\"\n{}", some_class("ISomething")),
+    )
+    .unwrap();
+    let output_dir = temp_dir();
+    let output_file = output_dir.path().join("mocks.h");
+    let depfile = output_dir.path().join("mocks.d");
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-file={}", output_file.to_string_lossy()),
+            &format!("--depfile={}", depfile.to_string_lossy())
+        ])
+        .source_file(&source_file)
+        .run()
+        .wait()
+        .success()
+    );
+    let depfile = std::fs::read_to_string(depfile).expect("Depfile not found");
+    assert_eq!(
+        depfile,
+        lines!(
+            format!("{}: \\", output_file.to_string_lossy()),
+            format!("  {} \\", source_file.to_string_lossy()),
+            format!("  {}", types_file.to_string_lossy())
+        )
+    );
+}
+
+#[test]
+fn classes_are_listed_as_json() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let mut mocksmith = Mocksmith::new_with_options(&["--list-classes=json"])
+        .source_file(source_file.path())
+        .run();
+    let output = mocksmith.read_stdout().unwrap();
+    assert!(mocksmith.wait().success());
+    let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(
+        output,
+        serde_json::json!({
+            "classes": [{
+                "qualified_name": "ISomething",
+                "file": source_file.path().to_string_lossy(),
+                "line": 1,
+                "methods": 1,
+                "virtual_methods": 1,
+                "pure_virtual_methods": 1,
+            }]
+        })
+    );
+}
+
+#[test]
+fn output_files_are_multiplexed_to_stdout() {
+    let source_file1 = temp_file_from(&some_class("ISomething"));
+    let source_file2 = temp_file_from(&some_class("IOther"));
+    let temp_dir = temp_dir();
+    let output_dir = temp_dir.path().join("mocks");
+
+    let mut mocksmith = Mocksmith::new_with_options(&[
+        &format!("--output-dir={}", output_dir.to_string_lossy()),
+        "--stdout-multiplex",
+    ])
+    .source_file(source_file1.path())
+    .source_file(source_file2.path())
+    .run();
+    let output = mocksmith.read_stdout().unwrap();
+    assert!(mocksmith.wait().success());
+    let markers = output
+        .lines()
+        .filter(|line| line.starts_with("--- file: "))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        markers,
+        vec!["--- file: MockSomething.h", "--- file: MockOther.h"]
+    );
+    assert!(output.contains("class MockOther : public IOther"));
+    assert!(!output_dir.exists());
+}
+
+#[test]
+fn stdin_with_name_can_be_written_to_output_file() {
+    let input_dir = temp_dir();
+    std::fs::write(input_dir.path().join("types.h"), "struct Bar {};\n").unwrap();
+    let source_file = input_dir.path().join("something.h");
+    let output_dir = temp_dir();
+    let output_file = output_dir.path().join("mocks.h");
+
+    let mut mocksmith = Mocksmith::new_with_options(&[
+        &format!("--stdin-name={}", source_file.to_string_lossy()),
+        &format!("--output-file={}", output_file.to_string_lossy()),
+    ])
+    .run()
+    .stdin(&format!(
+        "#include \"types.h\"\n{}",
+        some_class("ISomething")
+    ));
+    assert!(mocksmith.wait().success());
+    let header = std::fs::read_to_string(&output_file).expect("Mock file not found");
+    assert_matches!(
+        header,
+        &header_pattern(
+            &[source_file.as_path()],
+            &[some_mock("ISomething", "MockSomething")]
+        )
+    );
+}
+
+#[test]
+fn source_tree_is_mirrored_in_output_dir() {
+    let source_root = temp_dir();
+    let source_dir = source_root.path().join("net");
+    std::fs::create_dir(&source_dir).unwrap();
+    let source_file = source_dir.join("isocket.h");
+    std::fs::write(&source_file, some_class("ISocket")).unwrap();
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            "--mirror-tree",
+            &format!("--source-root={}", source_root.path().to_string_lossy())
+        ])
+        .source_file(&source_file)
+        .run()
+        .wait()
+        .success()
+    );
+    assert!(output_dir.path().join("net").join("MockSocket.h").is_file());
+}
+
+#[test]
+fn mocks_are_updated_in_place_between_markers() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+    let output_file = output_dir.path().join("test.cpp");
+    std::fs::write(
+        &output_file,
+        lines!(
+            "#include \"something.h\"",
+            "// mocksmith:begin",
+            "old mock",
+            "// mocksmith:end",
+            "TEST(Something, Works) {}"
+        ),
+    )
+    .unwrap();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-file={}", output_file.to_string_lossy()),
+            "--in-place"
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+    assert_eq!(
+        std::fs::read_to_string(&output_file).unwrap(),
+        format!(
+            "{}{}{}",
+            lines!("#include \"something.h\"", "// mocksmith:begin"),
+            some_mock("ISomething", "MockSomething"),
+            lines!("// mocksmith:end", "TEST(Something, Works) {}")
+        )
+    );
+}
+
+#[test]
+fn output_file_dash_prints_header_to_stdout() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+
+    let mut mocksmith = Mocksmith::new_with_options(&["--output-file=-", "--verbose"])
+        .source_file(source_file.path())
+        .run();
+    let stdout = mocksmith.read_stdout().unwrap();
+    let stderr = mocksmith.read_stderr().unwrap();
+    assert!(mocksmith.wait().success());
+    assert!(stdout.starts_with("// Automatically generated by Mocksmith"));
+    assert!(stdout.contains("#pragma once"));
+    assert!(stdout.contains("class MockSomething : public ISomething"));
+    assert!(!stdout.contains("Clang"));
+    assert!(!stderr.is_empty());
+    assert!(!std::path::Path::new("-").exists());
+}
+
+#[test]
+fn header_files_are_read_from_list() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let listed_file = temp_file_from(&some_class("IOther"));
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            "--files-from=-"
+        ])
+        .source_file(source_file.path())
+        .run()
+        .stdin(&lines!(listed_file.path().to_string_lossy(), ""))
+        .wait()
+        .success()
+    );
+    assert!(output_dir.path().join("MockSomething.h").exists());
+    assert!(output_dir.path().join("MockOther.h").exists());
+}
+
+#[test]
+fn header_files_are_read_from_null_separated_list() {
+    let source_dir = temp_dir();
+    let first_file = source_dir.path().join("first header.h");
+    let second_file = source_dir.path().join(" second header.h");
+    std::fs::write(&first_file, some_class("IFirst")).unwrap();
+    std::fs::write(&second_file, some_class("ISecond")).unwrap();
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            "--files-from=-",
+            "--null"
+        ])
+        .run()
+        .stdin(&format!(
+            "{}\0{}\0",
+            first_file.to_string_lossy(),
+            second_file.to_string_lossy()
+        ))
+        .wait()
+        .success()
+    );
+    assert!(output_dir.path().join("MockFirst.h").exists());
+    assert!(output_dir.path().join("MockSecond.h").exists());
+}
+
+#[test]
+fn only_headers_changed_since_git_ref_are_mocked() {
+    let repo_dir = temp_dir();
+    let git = |arguments: &[&str]| {
+        assert!(
+            std::process::Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(arguments)
+                .current_dir(repo_dir.path())
+                .output()
+                .unwrap()
+                .status
+                .success()
+        );
+    };
+    std::fs::write(repo_dir.path().join("types.h"), "typedef int Value;\n").unwrap();
+    std::fs::write(
+        repo_dir.path().join("first.h"),
+        format!("#include \"types.h\"\n{}", some_class("IFirst")),
+    )
+    .unwrap();
+    std::fs::write(repo_dir.path().join("second.h"), some_class("ISecond")).unwrap();
+    std::fs::write(repo_dir.path().join("third.h"), some_class("IThird")).unwrap();
+    git(&["init", "--quiet"]);
+    git(&["add", "."]);
+    git(&["commit", "--quiet", "-m", "Headers"]);
+    std::fs::write(repo_dir.path().join("types.h"), "typedef long Value;\n").unwrap();
+    std::fs::write(repo_dir.path().join("fourth.h"), some_class("IFourth")).unwrap();
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            "--since=HEAD",
+            "first.h",
+            "second.h",
+            "third.h",
+            "fourth.h",
+        ])
+        .current_dir(repo_dir.path())
+        .run()
+        .wait()
+        .success()
+    );
+    assert!(output_dir.path().join("MockFirst.h").exists());
+    assert!(!output_dir.path().join("MockSecond.h").exists());
+    assert!(!output_dir.path().join("MockThird.h").exists());
+    assert!(output_dir.path().join("MockFourth.h").exists());
+}
+
+// The post-processing command is run by sh, other shells are used on Windows
+#[cfg(unix)]
+#[test]
+fn generated_files_are_post_processed() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            "--post-process=echo \"// $MOCKSMITH_OUTPUT_FILE\"; cat"
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+    let output_file = output_dir.path().join("MockSomething.h");
+    let header = std::fs::read_to_string(&output_file).expect("Mock file not found");
+    assert!(header.starts_with(&format!("// {}\n", output_file.to_string_lossy())));
+    assert!(header.contains("class MockSomething"));
+}
+
+#[test]
+fn classes_to_mock_can_be_selected_interactively() {
+    let source_file = temp_file_from(&format!(
+        "{}\n{}\n{}",
+        some_class("IFirst"),
+        some_class("ISecond"),
+        some_class("IThird")
+    ));
+    let mut mocksmith = Mocksmith::new_with_options(&["--interactive"])
+        .source_file(source_file.path())
+        .run()
+        .stdin("2-3\n");
+    let stdout = mocksmith.read_stdout().unwrap();
+    assert!(mocksmith.wait().success());
+    assert!(!stdout.contains("class MockFirst"));
+    assert!(stdout.contains("class MockSecond"));
+    assert!(stdout.contains("class MockThird"));
+}
+
+#[test]
+fn interactive_selection_keeps_class_filter_and_namespaces() {
+    let source_file = temp_file_from(&format!(
+        "namespace a {{ {} }}\nnamespace b {{ {} }}\n{}",
+        some_class("IFoo"),
+        some_class("IFoo"),
+        some_class("Other")
+    ));
+    let mut mocksmith = Mocksmith::new_with_options(&["--interactive", "--class-filter=I.*"])
+        .source_file(source_file.path())
+        .run()
+        .stdin("2\n");
+    let stdout = mocksmith.read_stdout().unwrap();
+    assert!(mocksmith.wait().success());
+    assert!(stdout.contains("namespace b"));
+    assert!(!stdout.contains("namespace a"));
+    assert!(!stdout.contains("MockOther"));
+}
+
+#[test]
+fn changes_to_output_files_are_printed_as_diff() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+    let output_file = output_dir.path().join("mocks.h");
+    std::fs::write(&output_file, "old mock\n").unwrap();
+
+    let mut mocksmith = Mocksmith::new_with_options(&[
+        &format!("--output-file={}", output_file.to_string_lossy()),
+        "--diff",
+    ])
+    .source_file(source_file.path())
+    .run();
+    let stdout = mocksmith.read_stdout().unwrap();
+    assert!(mocksmith.wait().success());
+    assert!(stdout.starts_with(&format!(
+        "--- a/{0}\n+++ b/{0}\n@@ -1,1 ",
+        output_file.to_string_lossy()
+    )));
+    assert!(stdout.contains("\n-old mock\n"));
+    assert!(stdout.contains("\n+class MockSomething : public ISomething\n"));
+    assert_eq!(std::fs::read_to_string(&output_file).unwrap(), "old mock\n");
+}
+
+#[test]
+fn stale_generated_files_are_pruned() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let removed_file = temp_file_from(&some_class("IRemoved"));
+    let output_dir = temp_dir();
+    let run = |source_files: &[&std::path::Path]| {
+        let mut mocksmith = Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            "--manifest",
+            "--prune",
+        ]);
+        for file in source_files {
+            mocksmith = mocksmith.source_file(file);
+        }
+        mocksmith.run().wait().success()
+    };
+
+    assert!(run(&[source_file.path(), removed_file.path()]));
+    let stale_file = output_dir.path().join("MockRemoved.h");
+    assert!(stale_file.exists());
+    // Generated by another run writing to the same directory
+    let foreign_file = output_dir.path().join("MockOther.h");
+    std::fs::write(
+        &foreign_file,
+        lines!(
+            "// Automatically generated by Mocksmith (https://github.com/jordfras/mocksmith)",
+            "class MockOther {};"
+        ),
+    )
+    .unwrap();
+    let user_file = output_dir.path().join("helpers.h");
+    std::fs::write(&user_file, "// Hand-written\n").unwrap();
+
+    assert!(run(&[source_file.path()]));
+    assert!(output_dir.path().join("MockSomething.h").exists());
+    assert!(!stale_file.exists());
+    assert!(foreign_file.exists());
+    assert!(user_file.exists());
+}
+
+#[test]
+fn empty_output_fails_when_configured() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let empty_file = temp_file_from("class NotVirtual {};");
+    let run = |options: &[&str], files: &[&std::path::Path]| {
+        let mut mocksmith = Mocksmith::new_with_options(options);
+        for file in files {
+            mocksmith = mocksmith.source_file(file);
+        }
+        mocksmith.run().wait().success()
+    };
+
+    assert!(run(&[], &[empty_file.path()]));
+    assert!(!run(&["--fail-on-empty"], &[empty_file.path()]));
+    assert!(run(
+        &["--fail-on-empty"],
+        &[source_file.path(), empty_file.path()]
+    ));
+    assert!(!run(
+        &["--fail-on-empty=each"],
+        &[source_file.path(), empty_file.path()]
+    ));
+}
+
+#[test]
+fn other_files_are_mocked_when_keeping_going_after_errors() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let broken_file = temp_file_from("class IBroken { virtual Unknown fun() = 0; };");
+    let output_dir = temp_dir();
+
+    let mut mocksmith = Mocksmith::new_with_options(&[
+        &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+        "--keep-going",
+    ])
+    .source_file(broken_file.path())
+    .source_file(source_file.path())
+    .run();
+    let stderr = mocksmith.read_stderr().unwrap();
+    assert!(!mocksmith.wait().success());
+    assert!(stderr.contains("unknown type name 'Unknown'"));
+    assert!(stderr.contains(&format!(
+        "Could not create mocks from 1 of 2 header files:\n  {}",
+        broken_file.path().to_string_lossy()
+    )));
+    assert!(output_dir.path().join("MockSomething.h").exists());
+}
+
+#[test]
+fn out_of_date_output_file_fails_check_with_exit_code() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+    let output_file = output_dir.path().join("mocks.h");
+    let run = || {
+        Mocksmith::new_with_options(&[
+            &format!("--output-file={}", output_file.to_string_lossy()),
+            "--check",
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .code()
+    };
+
+    assert_eq!(run(), Some(5));
+    assert!(!output_file.exists());
+    assert!(
+        Mocksmith::new_with_options(&[&format!("--output-file={}", output_file.to_string_lossy())])
+            .source_file(source_file.path())
+            .run()
+            .wait()
+            .success()
+    );
+    assert_eq!(run(), Some(0));
+}
+
+#[test]
+fn exit_codes_tell_failures_apart() {
+    let run = |options: &[&str], source_file: &std::path::Path| {
+        Mocksmith::new_with_options(options)
+            .source_file(source_file)
+            .run()
+            .wait()
+            .code()
+    };
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let broken_file = temp_file_from("class IBroken { virtual Unknown fun() = 0; };");
+    let empty_file = temp_file_from("");
+
+    assert_eq!(run(&["--class-filter=("], source_file.path()), Some(2));
+    assert_eq!(run(&[], broken_file.path()), Some(3));
+    assert_eq!(run(&["--fail-on-empty"], empty_file.path()), Some(4));
+    assert_eq!(
+        run(&[], std::path::Path::new("non_existing_file.h")),
+        Some(6)
+    );
+}
+
+#[test]
+fn timings_are_printed_per_file_with_stats() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+
+    let mut mocksmith = Mocksmith::new_with_options(&[
+        &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+        "--stats",
+    ])
+    .source_file(source_file.path())
+    .run();
+    let stderr = mocksmith.read_stderr().unwrap();
+    assert!(mocksmith.wait().success());
+    assert!(stderr.starts_with("file"));
+    assert!(stderr.contains("generation"));
+    assert!(stderr.contains(&*source_file.path().to_string_lossy()));
+    assert!(stderr.lines().last().unwrap().starts_with("total"));
+}
+
 #[test]
 fn output_dir_is_created_if_it_does_not_exist() {
     let source_file = temp_file_from(&some_class("ISomething"));
@@ -253,7 +813,9 @@ fn no_files_are_written_to_dir_if_failing_to_mock_one_source_file() {
     .source_file(source_file2.path())
     .run();
     let stderr = mocksmith.read_stderr().unwrap();
-    assert!(stderr.contains("Parse error"));
+    let file_name = source_file2.path().file_name().unwrap().to_string_lossy();
+    assert!(stderr.contains(&format!("{file_name}:1:")));
+    assert!(stderr.contains(": error: "));
     assert!(!mocksmith.wait().success());
 
     assert_eq!(output_dir.path().read_dir().unwrap().count(), 0);
@@ -377,14 +939,275 @@ fn pragma_added_when_allowing_overriding_deprecated() {
 }
 
 #[test]
-fn cpp_standard_affects_parsing() {
-    let source_file = temp_file_from(&lines!("int x = 100'000;"));
+fn gcc_pragma_added_when_allowing_overriding_deprecated() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output = temp_file();
 
-    let mut mocksmith = Mocksmith::new_with_options(&["--std=c++11"])
+    assert!(
+        Mocksmith::new_with_options(&[
+            "--gcc-allow-deprecated",
+            &format!("--output-file={}", output.path().to_string_lossy())
+        ])
         .source_file(source_file.path())
-        .run();
-    let stderr = mocksmith.read_stderr().unwrap();
-    assert!(stderr.contains("Parse error"));
+        .run()
+        .wait()
+        .success()
+    );
+
+    let header = std::fs::read_to_string(output.path()).expect("Mock file not found");
+    assert_matches!(
+        header,
+        &regex_quote(&lines!(
+            "#ifdef __GNUC__",
+            "#  pragma GCC diagnostic push",
+            "#  pragma GCC diagnostic ignored \"-Wdeprecated-declarations\"",
+            "#endif",
+            "",
+            &some_mock("ISomething", "MockSomething"),
+            "#ifdef __GNUC__",
+            "#  pragma GCC diagnostic pop",
+            "#endif"
+        ))
+    );
+}
+
+#[test]
+fn warning_suppression_pragmas_added_when_configured() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output = temp_file();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            "--suppress-warnings",
+            &format!("--output-file={}", output.path().to_string_lossy())
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+
+    let header = std::fs::read_to_string(output.path()).expect("Mock file not found");
+    assert_matches!(
+        header,
+        &regex_quote(&lines!(
+            "#if defined(__clang__)",
+            "#  pragma clang diagnostic push",
+            "#  pragma clang diagnostic ignored \"-Wunknown-warning-option\"",
+            "#  pragma clang diagnostic ignored \"-Wsuggest-override\"",
+            "#  pragma clang diagnostic ignored \"-Wweak-vtables\"",
+            "#  pragma clang diagnostic ignored \"-Winconsistent-missing-destructor-override\"",
+            "#elif defined(__GNUC__)",
+            "#  pragma GCC diagnostic push",
+            "#  pragma GCC diagnostic ignored \"-Wsuggest-override\"",
+            "#endif",
+            "",
+            &some_mock("ISomething", "MockSomething"),
+            "#if defined(__clang__)",
+            "#  pragma clang diagnostic pop",
+            "#elif defined(__GNUC__)",
+            "#  pragma GCC diagnostic pop",
+            "#endif"
+        ))
+    );
+}
+
+#[test]
+fn nolint_comments_added_when_configured() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output = temp_file();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            "--nolint=modernize-*,cert-*",
+            &format!("--output-file={}", output.path().to_string_lossy())
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+
+    let header = std::fs::read_to_string(output.path()).expect("Mock file not found");
+    assert_matches!(
+        &header,
+        &regex_quote(&lines!(
+            "#pragma once",
+            "",
+            "// NOLINTBEGIN(modernize-*,cert-*)",
+            "#include \""
+        ))
+    );
+    assert_matches!(
+        header,
+        &regex_quote(&lines!(
+            "#include <gmock/gmock.h>",
+            "",
+            &some_mock("ISomething", "MockSomething"),
+            "// NOLINTEND(modernize-*,cert-*)",
+            ""
+        ))
+    );
+}
+
+#[test]
+fn code_is_guarded_by_macro_when_configured() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output = temp_file();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            "--guard-macro=UNIT_TEST",
+            &format!("--output-file={}", output.path().to_string_lossy())
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+
+    let header = std::fs::read_to_string(output.path()).expect("Mock file not found");
+    assert_matches!(
+        &header,
+        &regex_quote(&lines!(
+            "#pragma once",
+            "",
+            "#if defined(UNIT_TEST)",
+            "",
+            "#include"
+        ))
+    );
+    assert_matches!(
+        &header,
+        &regex_quote(&lines!(
+            &some_mock("ISomething", "MockSomething"),
+            "#endif // defined(UNIT_TEST)",
+            ""
+        ))
+    );
+}
+
+#[test]
+fn module_interface_unit_generated_when_configured() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            "--module=myproject.mocks",
+            &format!("--output-dir={}", output_dir.path().to_string_lossy())
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+
+    let module = std::fs::read_to_string(output_dir.path().join("MockSomething.cppm"))
+        .expect("Module file not found");
+    assert_matches!(
+        &module,
+        &regex_quote(&lines!("", "module;", "", "#include"))
+    );
+    assert_matches!(
+        &module,
+        &regex_quote(&lines!(
+            "#include <gmock/gmock.h>",
+            "",
+            "export module myproject.mocks;",
+            "",
+            "export {",
+            "",
+            &some_mock("ISomething", "MockSomething"),
+            "}"
+        ))
+    );
+}
+
+#[test]
+fn gmock_imported_in_module_when_configured() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            "--module=myproject.mocks",
+            "--import-gmock",
+            &format!("--output-dir={}", output_dir.path().to_string_lossy())
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+
+    let module = std::fs::read_to_string(output_dir.path().join("MockSomething.cppm"))
+        .expect("Module file not found");
+    assert!(!module.contains("#include <gmock/gmock.h>"));
+    assert_matches!(
+        &module,
+        &regex_quote(&lines!(
+            "export module myproject.mocks;",
+            "",
+            "import <gmock/gmock.h>;",
+            "",
+            "export {"
+        ))
+    );
+}
+
+#[test]
+fn module_conflicts_with_asserting_not_abstract() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+
+    assert!(
+        !Mocksmith::new_with_options(&[
+            "--module=myproject.mocks",
+            "--assert-not-abstract",
+            &format!("--output-dir={}", output_dir.path().to_string_lossy())
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+}
+
+#[test]
+fn pragma_added_when_mocking_deprecated_methods() {
+    let source_file = temp_file_from(&lines!(
+        "class ISomething {",
+        "public:",
+        "  [[deprecated]] virtual void fun() = 0;",
+        "};"
+    ));
+
+    let mut mocksmith = Mocksmith::new().source_file(source_file.path()).run();
+    let header = mocksmith.read_stdout().unwrap();
+    assert!(header.contains("#  pragma warning(disable : 4996)"));
+    assert!(mocksmith.wait().success());
+
+    let mut mocksmith = Mocksmith::new_with_options(&["--skip-deprecated"])
+        .source_file(source_file.path())
+        .run();
+    let header = mocksmith.read_stdout().unwrap();
+    assert!(!header.contains("pragma warning"));
+    assert!(!header.contains("class"));
+    assert!(mocksmith.wait().success());
+}
+
+#[test]
+fn cpp_standard_affects_parsing() {
+    let source_file = temp_file_from(&lines!("int x = 100'000;"));
+
+    let mut mocksmith = Mocksmith::new_with_options(&["--std=c++11"])
+        .source_file(source_file.path())
+        .run();
+    let stderr = mocksmith.read_stderr().unwrap();
+    let file_name = source_file.path().file_name().unwrap().to_string_lossy();
+    assert!(stderr.contains(&format!("{file_name}:1:")));
+    assert!(stderr.contains(": error: "));
     assert!(!mocksmith.wait().success());
 
     let mut mocksmith = Mocksmith::new_with_options(&["--std=c++14"])
@@ -512,6 +1335,253 @@ fn class_filter_option_affects_which_classes_are_mocked() {
     assert!(mocksmith.wait().success());
 }
 
+#[test]
+fn class_filters_can_be_repeated_and_combined_with_excludes() {
+    let source_file = temp_file_from(&format!(
+        "{}{}{}",
+        some_class("IFoo"),
+        some_class("IBar"),
+        some_class("IBaz")
+    ));
+
+    let mut mocksmith = Mocksmith::new_with_options(&[
+        "--class-filter=Foo",
+        "--class-filter=Ba",
+        "--class-exclude=Baz",
+    ])
+    .source_file(source_file.path())
+    .run();
+    let output = mocksmith.read_stdout().unwrap();
+    assert!(mocksmith.wait().success());
+    assert!(output.contains("class MockFoo : public IFoo"));
+    assert!(output.contains("class MockBar : public IBar"));
+    assert!(!output.contains("IBaz"));
+}
+
+#[test]
+fn method_filter_options_affect_which_methods_are_mocked() {
+    let source_file = temp_file_from(&lines!(
+        "class IFoo {",
+        "public:",
+        "  virtual void foo() = 0;",
+        "  virtual void dumpState() = 0;",
+        "  virtual void dumpAll() = 0;",
+        "  virtual IFoo * clone() = 0;",
+        "};"
+    ));
+
+    let mut mocksmith = Mocksmith::new_with_options(&[
+        "--method-filter=^(foo|dump.*)$",
+        "--method-exclude=^dumpAll$",
+    ])
+    .source_file(source_file.path())
+    .run();
+    assert!(mocksmith.read_stdout().unwrap().contains(&lines!(
+        "class MockFoo : public IFoo",
+        "{",
+        "public:",
+        "  MOCK_METHOD(void, foo, (), (override));",
+        "  MOCK_METHOD(void, dumpState, (), (override));",
+        "};"
+    )));
+    assert!(mocksmith.wait().success());
+}
+
+#[test]
+fn verbose_version_reports_libclang_and_clang_arguments() {
+    let mut mocksmith =
+        Mocksmith::new_with_options(&["--version-verbose", "--std=c++20", "-I", "some/include"])
+            .run();
+    let output = mocksmith.read_stdout().unwrap();
+    assert!(mocksmith.wait().success());
+    assert!(output.starts_with(&format!("mocksmith {}\n", env!("CARGO_PKG_VERSION"))));
+    assert!(output.contains("\nlibclang: "));
+    assert!(output.contains("\nLIBCLANG_PATH: "));
+    let arguments = output
+        .lines()
+        .find_map(|line| line.strip_prefix("Clang arguments: "))
+        .expect("Clang arguments should be printed");
+    assert!(arguments.contains("-std=c++20"));
+    assert!(arguments.contains("-Isome/include"));
+}
+
+#[test]
+fn include_paths_from_environment_are_used_when_configured() {
+    let clang_arguments = |options: &[&str]| {
+        let mut mocksmith = Mocksmith::new_with_options(options)
+            .env("CPATH", "some/cpath")
+            .env("CPLUS_INCLUDE_PATH", "some/system/include")
+            .run();
+        let output = mocksmith.read_stdout().unwrap();
+        assert!(mocksmith.wait().success());
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix("Clang arguments: "))
+            .expect("Clang arguments should be printed")
+            .to_string()
+    };
+
+    let arguments = clang_arguments(&["--version-verbose"]);
+    assert!(!arguments.contains("some/cpath"));
+    assert!(!arguments.contains("some/system/include"));
+
+    let arguments = clang_arguments(&["--version-verbose", "--use-env-includes"]);
+    assert!(arguments.contains("-Isome/cpath"));
+    assert!(arguments.contains("-isystemsome/system/include"));
+}
+
+#[test]
+fn system_includes_are_not_detected_by_default() {
+    let mut mocksmith = Mocksmith::new_with_options(&["--version-verbose"]).run();
+    let output = mocksmith.read_stdout().unwrap();
+    assert!(mocksmith.wait().success());
+    let arguments = output
+        .lines()
+        .find_map(|line| line.strip_prefix("Clang arguments: "))
+        .expect("Clang arguments should be printed");
+    assert!(!arguments.contains("-isystem"));
+}
+
+#[test]
+fn detected_system_includes_are_not_used_with_sysroot_or_target() {
+    let sysroot = temp_dir();
+    let clang_arguments = |options: &[&str]| {
+        let mut mocksmith = Mocksmith::new_with_options(options).run();
+        let output = mocksmith.read_stdout().unwrap();
+        assert!(mocksmith.wait().success());
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix("Clang arguments: "))
+            .expect("Clang arguments should be printed")
+            .to_string()
+    };
+
+    let sysroot_option = format!("--sysroot={}", sysroot.path().to_string_lossy());
+    let arguments = clang_arguments(&[
+        "--version-verbose",
+        "--detect-system-includes",
+        &sysroot_option,
+    ]);
+    assert!(arguments.contains(&sysroot_option));
+    assert!(!arguments.contains("-isystem"));
+    assert!(!arguments.contains("-isysroot"));
+
+    let arguments = clang_arguments(&[
+        "--version-verbose",
+        "--detect-system-includes",
+        "--target=aarch64-linux-gnu",
+        "--isystem=some/system/include",
+    ]);
+    assert!(arguments.contains("--target=aarch64-linux-gnu"));
+    assert!(arguments.contains("-isystemsome/system/include"));
+    assert_eq!(arguments.matches("-isystem").count(), 1);
+    assert!(!arguments.contains("-isysroot"));
+}
+
+#[test]
+fn system_include_dirs_are_not_used_to_include_source_header() {
+    let system_dir = temp_dir();
+    std::fs::write(
+        system_dir.path().join("library.h"),
+        "typedef int Value;\n#warning library warning\n",
+    )
+    .unwrap();
+    let source_file = temp_file_from(&lines!(
+        "#include <library.h>",
+        "class IFoo {",
+        "public:",
+        "  virtual Value foo() = 0;",
+        "};"
+    ));
+    let source_dir = source_file.path().parent().unwrap();
+
+    let mut mocksmith = Mocksmith::new_with_options(&[
+        &format!("--isystem={}", source_dir.to_string_lossy()),
+        &format!("--isystem={}", system_dir.path().to_string_lossy()),
+    ])
+    .source_file(source_file.path())
+    .run();
+    let stdout = mocksmith.read_stdout().unwrap();
+    let stderr = mocksmith.read_stderr().unwrap();
+    assert!(mocksmith.wait().success());
+    assert!(stdout.contains("MOCK_METHOD(Value, foo, (), (override));"));
+    assert!(!stdout.contains(&format!(
+        "#include \"{}\"",
+        source_file.path().file_name().unwrap().to_string_lossy()
+    )));
+    assert!(!stderr.contains("library warning"));
+}
+
+#[test]
+fn sysroot_and_target_are_passed_to_parser() {
+    let sysroot = temp_dir();
+    let mut mocksmith = Mocksmith::new_with_options(&[
+        "--version-verbose",
+        &format!("--sysroot={}", sysroot.path().to_string_lossy()),
+        "--target=aarch64-linux-gnu",
+    ])
+    .run();
+    let output = mocksmith.read_stdout().unwrap();
+    assert!(mocksmith.wait().success());
+    let arguments = output
+        .lines()
+        .find_map(|line| line.strip_prefix("Clang arguments: "))
+        .expect("Clang arguments should be printed");
+    assert!(arguments.contains(&format!("--sysroot={}", sysroot.path().to_string_lossy())));
+    assert!(arguments.contains("--target=aarch64-linux-gnu"));
+}
+
+#[test]
+fn force_included_headers_are_passed_to_parser() {
+    let prefix_header = temp_file_from("#define SOMETHING\n");
+    let source_file = temp_file_from(&lines!(
+        "#ifdef SOMETHING",
+        "class IFoo {",
+        "public:",
+        "  virtual void foo() = 0;",
+        "};",
+        "#endif"
+    ));
+
+    let mut mocksmith = Mocksmith::new_with_options(&[&format!(
+        "--force-include={}",
+        prefix_header.path().to_string_lossy()
+    )])
+    .source_file(source_file.path())
+    .run();
+    assert!(
+        mocksmith
+            .read_stdout()
+            .unwrap()
+            .contains("class MockFoo : public IFoo")
+    );
+    assert!(mocksmith.wait().success());
+}
+
+#[test]
+fn macros_are_defined_and_undefined_when_parsing() {
+    let source_file = temp_file_from(&lines!(
+        "#if defined(FIRST) && !defined(SECOND)",
+        "class IFoo {",
+        "public:",
+        "  virtual void foo() = 0;",
+        "};",
+        "#endif"
+    ));
+
+    let mut mocksmith =
+        Mocksmith::new_with_options(&["-DFIRST", "--define=SECOND", "-U", "SECOND"])
+            .source_file(source_file.path())
+            .run();
+    assert!(
+        mocksmith
+            .read_stdout()
+            .unwrap()
+            .contains("class MockFoo : public IFoo")
+    );
+    assert!(mocksmith.wait().success());
+}
+
 #[test]
 fn additional_clang_args_are_passed_to_parser() {
     let source_file = temp_file_from(&lines!(
@@ -539,3 +1609,382 @@ fn additional_clang_args_are_passed_to_parser() {
     )));
     assert!(mocksmith.wait().success());
 }
+
+#[test]
+fn include_guards_replace_pragma_once_when_configured() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+
+    let mut mocksmith = Mocksmith::new_with_options(&[
+        "--include-guard-style=ifndef",
+        "--include-guard-pattern=MYPROJECT_{FILE}_MOCK_H_",
+    ])
+    .source_file(source_file.path())
+    .run();
+    let stdout = mocksmith.read_stdout().unwrap();
+    assert_matches!(
+        &stdout,
+        "(?s)^// Automatically generated[^\n]*\n\
+         #ifndef MYPROJECT_[A-Z0-9_]+_MOCK_H_\n#define MYPROJECT_[A-Z0-9_]+_MOCK_H_\n\
+         .*class MockSomething.*\n#endif // MYPROJECT_[A-Z0-9_]+_MOCK_H_\n$"
+    );
+    assert!(!stdout.contains("#pragma once"));
+    assert!(mocksmith.wait().success());
+}
+
+#[test]
+fn source_file_is_written_next_to_header_when_splitting() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            "--split-source"
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+    let header = std::fs::read_to_string(output_dir.path().join("MockSomething.h"))
+        .expect("Mock file not found");
+    assert!(header.contains("  MockSomething();\n  ~MockSomething();\n"));
+    let source = std::fs::read_to_string(output_dir.path().join("MockSomething.cpp"))
+        .expect("Mock source file not found");
+    assert_eq!(
+        source,
+        lines!(
+            "// Automatically generated by Mocksmith (https://github.com/jordfras/mocksmith)",
+            "#include \"MockSomething.h\"",
+            "",
+            "MockSomething::MockSomething() {}",
+            "MockSomething::~MockSomething() {}"
+        )
+    );
+}
+
+#[test]
+fn fixture_skeletons_are_generated_but_not_overwritten() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+    let run = || {
+        assert!(
+            Mocksmith::new_with_options(&[
+                &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+                "--emit-fixture"
+            ])
+            .source_file(source_file.path())
+            .run()
+            .wait()
+            .success()
+        );
+    };
+
+    run();
+    let fixture_file = output_dir.path().join("SomethingTest.cpp");
+    let fixture = std::fs::read_to_string(&fixture_file).expect("Fixture file not found");
+    assert_eq!(
+        fixture,
+        lines!(
+            "// Automatically generated by Mocksmith (https://github.com/jordfras/mocksmith)",
+            "#include \"MockSomething.h\"",
+            "",
+            "#include <gtest/gtest.h>",
+            "",
+            "class SomethingTest : public ::testing::Test",
+            "{",
+            "protected:",
+            "  void SetUp() override",
+            "  {",
+            "  }",
+            "",
+            "  void TearDown() override",
+            "  {",
+            "  }",
+            "",
+            "  MockSomething mock;",
+            "};",
+            "",
+            "TEST_F(SomethingTest, FirstTest)",
+            "{",
+            "}"
+        )
+    );
+
+    std::fs::write(&fixture_file, "// My tests\n").unwrap();
+    run();
+    assert_eq!(
+        std::fs::read_to_string(&fixture_file).unwrap(),
+        "// My tests\n"
+    );
+}
+
+#[test]
+fn cheat_sheets_are_written_for_each_mock() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            "--emit-cheat-sheet"
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+    let cheat_sheet = std::fs::read_to_string(output_dir.path().join("MockSomething.md"))
+        .expect("Cheat sheet not found");
+    assert_eq!(
+        cheat_sheet,
+        lines!(
+            "# MockSomething",
+            "",
+            "Expectations for the methods of `ISomething` mocked by `MockSomething mock`.",
+            "",
+            "```cpp",
+            "using ::testing::_;",
+            "",
+            "EXPECT_CALL(mock, fun());",
+            "```"
+        )
+    );
+}
+
+#[test]
+fn documentation_written_to_docs_dir() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+    let docs_dir = output_dir.path().join("docs");
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            &format!("--emit-docs={}", docs_dir.to_string_lossy())
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+    let documentation = std::fs::read_to_string(docs_dir.join("MockSomething.md"))
+        .expect("Documentation not found");
+    assert_eq!(
+        documentation,
+        lines!(
+            format!("# Mocks of `{}`", source_file.path().to_string_lossy()),
+            "",
+            format!(
+                "Mocks are written to `{}`.",
+                output_dir.path().join("MockSomething.h").to_string_lossy()
+            ),
+            "",
+            "## ISomething",
+            "",
+            "Mocked by `MockSomething` in the global namespace.",
+            "",
+            "Mocked methods:",
+            "",
+            "- `fun`: `void ()`"
+        )
+    );
+}
+
+#[test]
+fn manifest_lists_generated_files_and_mocks() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            "--manifest"
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+    let manifest = std::fs::read_to_string(output_dir.path().join("mocksmith_manifest.json"))
+        .expect("Manifest not found");
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+    let header = &manifest["headers"][0];
+    assert_eq!(
+        header["source_file"],
+        source_file.path().to_string_lossy().as_ref()
+    );
+    assert_eq!(
+        header["output_file"],
+        output_dir
+            .path()
+            .join("MockSomething.h")
+            .to_string_lossy()
+            .as_ref()
+    );
+    assert_eq!(header["mocks"][0]["class_name"], "ISomething");
+    assert_eq!(header["mocks"][0]["mock_name"], "MockSomething");
+    assert!(header["mock_source_file"].is_null());
+    assert!(header["mocks"][0]["fixture_file"].is_null());
+}
+
+#[test]
+fn manifest_lists_split_sources_and_fixtures() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            "--manifest",
+            "--split-source",
+            "--emit-fixture"
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+    let manifest = std::fs::read_to_string(output_dir.path().join("mocksmith_manifest.json"))
+        .expect("Manifest not found");
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+    let header = &manifest["headers"][0];
+    let mock_source_file = output_dir.path().join("MockSomething.cpp");
+    assert!(mock_source_file.is_file());
+    assert_eq!(
+        header["mock_source_file"],
+        mock_source_file.to_string_lossy().as_ref()
+    );
+    let fixture_file = output_dir.path().join("SomethingTest.cpp");
+    assert!(fixture_file.is_file());
+    assert_eq!(
+        header["mocks"][0]["fixture_file"],
+        fixture_file.to_string_lossy().as_ref()
+    );
+}
+
+#[test]
+fn signature_checks_written_next_to_mock_header() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            "--signature-checks"
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+    let checks = std::fs::read_to_string(output_dir.path().join("MockSomething_checks.h"))
+        .expect("Checks file not found");
+    assert!(checks.contains(
+        "static_assert(std::is_same<decltype(&ISomething::fun), void (ISomething::*)()>::value, \
+         \"Signature of ISomething::fun has changed\");"
+    ));
+}
+
+#[test]
+fn mocks_written_to_separate_files_when_splitting_per_class() {
+    let source_file = temp_file_from(&format!(
+        "{}{}",
+        some_class("ISomething"),
+        some_class("IOther")
+    ));
+    let output_dir = temp_dir();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--output-dir={}", output_dir.path().to_string_lossy()),
+            "--split-per-class"
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+    let something = std::fs::read_to_string(output_dir.path().join("MockSomething.h"))
+        .expect("Mock file not found");
+    assert_matches!(
+        &something,
+        &regex_quote(&some_mock("ISomething", "MockSomething"))
+    );
+    assert!(!something.contains("MockOther"));
+    let other = std::fs::read_to_string(output_dir.path().join("MockOther.h"))
+        .expect("Mock file not found");
+    assert_matches!(&other, &regex_quote(&some_mock("IOther", "MockOther")));
+    assert!(!other.contains("MockSomething"));
+}
+
+#[test]
+fn license_header_inserted_when_configured() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let license_file = temp_file_from("Copyright Acme\nAll rights reserved");
+    let output = temp_file();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            &format!("--license-file={}", license_file.path().to_string_lossy()),
+            &format!("--output-file={}", output.path().to_string_lossy())
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+
+    let header = std::fs::read_to_string(output.path()).expect("Mock file not found");
+    assert!(header.starts_with(&format!(
+        "{}// Automatically generated by Mocksmith",
+        lines!("// Copyright Acme", "// All rights reserved")
+    )));
+}
+
+#[test]
+fn line_endings_and_bom_applied_when_configured() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output = temp_file();
+
+    assert!(
+        Mocksmith::new_with_options(&[
+            "--line-endings=crlf",
+            "--bom",
+            &format!("--output-file={}", output.path().to_string_lossy())
+        ])
+        .source_file(source_file.path())
+        .run()
+        .wait()
+        .success()
+    );
+
+    let header = std::fs::read_to_string(output.path()).expect("Mock file not found");
+    assert!(header.starts_with("\u{feff}// Automatically generated"));
+    assert!(header.ends_with("};\r\n"));
+    assert!(!header.replace("\r\n", "").contains('\n'));
+}
+
+#[test]
+fn line_endings_and_bom_applied_to_stdout() {
+    let mut mocksmith = Mocksmith::new_with_options(&["--line-endings=crlf", "--bom"])
+        .run()
+        .stdin(&some_class("ISomething"));
+    assert_ok!(mocksmith.expect_stdout(&format!(
+        "\u{feff}{}",
+        some_mock("ISomething", "MockSomething").replace('\n', "\r\n")
+    )));
+}
+
+#[test]
+fn banner_replaces_default_comment_when_configured() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+
+    let mut mocksmith =
+        Mocksmith::new_with_options(&["--banner-text=Mocks of {CLASSES}, do not edit"])
+            .source_file(source_file.path())
+            .run();
+    let stdout = mocksmith.read_stdout().unwrap();
+    assert!(stdout.starts_with("// Mocks of ISomething, do not edit\n#pragma once\n"));
+    assert!(mocksmith.wait().success());
+}
@@ -1,6 +1,7 @@
 #[allow(dead_code)]
 mod helpers;
 mod paths;
+#[allow(clippy::collapsible_if)]
 mod program_under_test;
 
 use helpers::{some_class, temp_dir, temp_file, temp_file_from};
@@ -131,3 +132,46 @@ fn cant_specify_nonexistent_input_file() {
     assert!(stderr.contains("Input header file does not exist or is not a file"));
     assert!(!mocksmith.wait().success());
 }
+
+#[test]
+fn cant_diff_check_or_write_depfile_when_output_to_stdout() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let depfile = temp_file();
+    for (option, message) in [
+        (
+            "--diff".to_string(),
+            "can't be diffed when writing to stdout",
+        ),
+        (
+            "--check".to_string(),
+            "can't be checked when writing to stdout",
+        ),
+        (
+            format!("--depfile={}", depfile.path().to_string_lossy()),
+            "dependency file can't be written when writing to stdout",
+        ),
+    ] {
+        let mut mocksmith = Mocksmith::new_with_options(&["--output-file=-", &option])
+            .source_file(source_file.path())
+            .run();
+        let stderr = mocksmith.read_stderr().unwrap();
+        assert!(stderr.contains(message), "{stderr}");
+        assert!(!mocksmith.wait().success());
+    }
+}
+
+#[test]
+fn manifest_cant_be_written_when_output_to_file() {
+    let source_file = temp_file_from(&some_class("ISomething"));
+    let output = temp_file();
+
+    let mut mocksmith = Mocksmith::new_with_options(&[
+        &format!("--output-file={}", output.path().to_string_lossy()),
+        "--manifest",
+    ])
+    .source_file(source_file.path())
+    .run();
+    let stderr = mocksmith.read_stderr().unwrap();
+    assert!(stderr.contains("'--output-file <OUTPUT_FILE>' cannot be used with '--manifest'"));
+    assert!(!mocksmith.wait().success());
+}
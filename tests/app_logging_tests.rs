@@ -2,6 +2,7 @@ mod assertions;
 #[allow(dead_code)]
 mod helpers;
 mod paths;
+#[allow(clippy::collapsible_if)]
 mod program_under_test;
 
 use helpers::{some_class, temp_file_from};
@@ -52,3 +53,108 @@ fn logging_to_stdout_when_writing_to_file() {
     assert!(stdout.contains("warning"));
     assert!(mocksmith.wait().success());
 }
+
+#[test]
+fn parse_errors_are_reported_like_compiler_errors() {
+    let source_file = temp_file_from("class ISomething { virtual Unknown fun() = 0; };");
+    let mut mocksmith = Mocksmith::new().source_file(source_file.path()).run();
+    let stderr = mocksmith.read_stderr().unwrap();
+    assert!(stderr.starts_with(&format!(
+        "{}:1:28: error: unknown type name 'Unknown'",
+        source_file.path().to_string_lossy()
+    )));
+    // Not colored when not writing to a terminal
+    assert!(!stderr.contains('\x1b'));
+    assert!(!mocksmith.wait().success());
+}
+
+#[test]
+fn diagnostics_are_written_as_json() {
+    let source_file = temp_file_from("class ISomething { virtual Unknown fun() = 0; };");
+    let mut mocksmith = Mocksmith::new_with_options(&["--diagnostics=json"])
+        .source_file(source_file.path())
+        .run();
+    let stderr = mocksmith.read_stderr().unwrap();
+    assert!(!mocksmith.wait().success());
+    let diagnostics: serde_json::Value = serde_json::from_str(&stderr).unwrap();
+    assert_eq!(
+        diagnostics,
+        serde_json::json!([{
+            "severity": "error",
+            "message": "unknown type name 'Unknown'",
+            "file": source_file.path().to_string_lossy(),
+            "line": 1,
+            "column": 28,
+            "source": "clang"
+        }])
+    );
+}
+
+#[test]
+fn warning_is_logged_for_ambiguous_base_class() {
+    let source_file = temp_file_from(&format!(
+        "{}\n{}",
+        lines!(
+            "class Base { public: virtual ~Base() = default; };",
+            "class A : public Base {};",
+            "class B : public Base {};"
+        ),
+        some_class("ISomething : public A, public B")
+    ));
+    let mut mocksmith = Mocksmith::new().source_file(source_file.path()).run();
+    let stderr = mocksmith.read_stderr().unwrap();
+    assert!(stderr.contains("Class ISomething inherits Base more than once"));
+    assert!(
+        mocksmith
+            .read_stdout()
+            .unwrap()
+            .contains("class MockSomething")
+    );
+    assert!(mocksmith.wait().success());
+}
+
+#[test]
+fn warning_is_logged_for_method_returning_reference() {
+    let source_file = temp_file_from(&lines!(
+        "class ISomething {",
+        "public:",
+        "  virtual ~ISomething() = default;",
+        "  virtual int& get() = 0;",
+        "};"
+    ));
+    let mut mocksmith = Mocksmith::new().source_file(source_file.path()).run();
+    let stderr = mocksmith.read_stderr().unwrap();
+    assert!(stderr.contains("Method ISomething::get returns a reference"));
+    assert!(mocksmith.wait().success());
+
+    let mut mocksmith = Mocksmith::new_with_options(&["--reference-default-actions"])
+        .source_file(source_file.path())
+        .run();
+    assert!(mocksmith.read_stderr().unwrap().is_empty());
+    assert!(
+        mocksmith
+            .read_stdout()
+            .unwrap()
+            .contains("static int dummy{};")
+    );
+    assert!(mocksmith.wait().success());
+}
+
+#[test]
+fn warning_is_logged_for_pure_virtual_method_not_mocked() {
+    let source_file = temp_file_from(&lines!(
+        "class ISomething {",
+        "public:",
+        "  virtual void fun() = 0;",
+        "  [[deprecated]] virtual void old_fun() = 0;",
+        "};"
+    ));
+    let mut mocksmith = Mocksmith::new_with_options(&["--skip-deprecated"])
+        .source_file(source_file.path())
+        .run();
+    let stderr = mocksmith.read_stderr().unwrap();
+    assert!(stderr.contains(
+        "warning: Pure virtual method ISomething::old_fun is not mocked, so the mock is abstract"
+    ));
+    assert!(mocksmith.wait().success());
+}
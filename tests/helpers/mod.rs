@@ -31,6 +31,31 @@ pub fn temp_file_from(content: &str) -> tempfile::NamedTempFile {
     file
 }
 
+// Checks the syntax of C++ code with the compiler in `CXX`, or `c++`. Returns `None` if
+// there is no compiler or if gmock is not found by it, e.g., via `CPLUS_INCLUDE_PATH`.
+pub fn compiles_with_gmock(code: &str) -> Option<bool> {
+    let compiler = std::env::var("CXX").unwrap_or_else(|_| "c++".to_string());
+    let compiles = |code: &str| -> Option<bool> {
+        let dir = temp_dir();
+        let file = dir.path().join("code.cpp");
+        std::fs::write(&file, code).expect("Should be able to write to file");
+        let output = std::process::Command::new(&compiler)
+            .args(["-std=c++17", "-fsyntax-only"])
+            .arg(&file)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+        }
+        Some(output.status.success())
+    };
+    if !compiles("#include <gmock/gmock.h>\n")? {
+        eprintln!("Skipping compilation, gmock not found by {compiler}");
+        return None;
+    }
+    compiles(code)
+}
+
 // Creates class to mock, when not really interested in the actual content.
 pub fn some_class(name: &str) -> String {
     lines!(
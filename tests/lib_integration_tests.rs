@@ -2,8 +2,12 @@ mod assertions;
 #[allow(dead_code)]
 mod helpers;
 
-use helpers::{temp_dir, temp_file_from};
-use mocksmith::{Mocksmith, MocksmithError};
+use helpers::{compiles_with_gmock, temp_dir, temp_file_from};
+use mocksmith::{
+    ClassInfo, DiagnosticSource, FactoryPointer, HelperAllocation, Language, MockStyle, Mocksmith,
+    MocksmithBuilder, MocksmithError, MocksmithHandle, Phase, Severity,
+};
+use std::path::{Path, PathBuf};
 
 #[test]
 fn simple_pure_virtual_method_can_be_mocked() {
@@ -539,3 +543,2340 @@ fn class_filter_avoids_mocking_unwanted_class() {
         )
     );
 }
+
+#[test]
+fn qualified_class_filter_selects_classes_in_namespaces() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .qualified_class_filter_fun(|class_name| class_name == "b::IFoo");
+    let cpp_classes = "
+          namespace a { class IFoo { public: virtual void bar() = 0; }; }
+          namespace b { class IFoo { public: virtual void baz() = 0; }; }";
+    let mocks = mocksmith
+        .create_mocks_from_string(cpp_classes)
+        .expect("Mocks should be generated");
+    assert_eq!(mocks.len(), 1);
+    assert!(mocks[0].code.contains("baz"));
+}
+
+#[test]
+fn qt_signals_are_not_mocked_when_using_qt_macros() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .qt_macros(true)
+        .methods_to_mock(mocksmith::MethodsToMockStrategy::All);
+    let cpp_class = "
+          class QObject {
+          public:
+            virtual ~QObject() = default;
+          };
+          class Foo : public QObject {
+            Q_OBJECT
+          public:
+            virtual void bar() Q_DECL_NOEXCEPT = 0;
+          signals:
+            void changed();
+          public slots:
+            void fizz();
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (), (noexcept, override));",
+            "  MOCK_METHOD(void, fizz, (), ());",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn methods_named_like_qt_meta_object_methods_are_mocked_without_qt_macros() {
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual int qt_metacall(int id) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(int, qt_metacall, (int id), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn com_interfaces_are_mocked_with_calltype_and_iunknown_methods() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .com_interfaces(true);
+    let cpp_class = "
+          MIDL_INTERFACE(\"12345678-1234-1234-1234-123456789012\")
+          IFoo : public IUnknown {
+          public:
+            virtual HRESULT STDMETHODCALLTYPE Bar(int x) = 0;
+            STDMETHOD_(ULONG, Fizz)(THIS_ int y) PURE;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public IFoo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(HRESULT, QueryInterface, (REFIID riid, void ** ppvObject), (Calltype(STDMETHODCALLTYPE), override));",
+            "  MOCK_METHOD(ULONG, AddRef, (), (Calltype(STDMETHODCALLTYPE), override));",
+            "  MOCK_METHOD(ULONG, Release, (), (Calltype(STDMETHODCALLTYPE), override));",
+            "  MOCK_METHOD(HRESULT, Bar, (int x), (Calltype(STDMETHODCALLTYPE), override));",
+            "  MOCK_METHOD(ULONG, Fizz, (int y), (Calltype(STDMETHODCALLTYPE), override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn com_interfaces_inheriting_iunknown_indirectly_get_iunknown_methods() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .com_interfaces(true)
+        .class_filter_fun(|name| name == "IFoo");
+    let cpp_class = "
+          struct IBase : public IUnknown {
+            virtual HRESULT STDMETHODCALLTYPE Fizz() = 0;
+          };
+          struct IFoo : public IBase {
+            virtual HRESULT STDMETHODCALLTYPE Bar() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public IFoo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(HRESULT, QueryInterface, (REFIID riid, void ** ppvObject), (Calltype(STDMETHODCALLTYPE), override));",
+            "  MOCK_METHOD(ULONG, AddRef, (), (Calltype(STDMETHODCALLTYPE), override));",
+            "  MOCK_METHOD(ULONG, Release, (), (Calltype(STDMETHODCALLTYPE), override));",
+            "  MOCK_METHOD(HRESULT, Bar, (), (Calltype(STDMETHODCALLTYPE), override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn calltype_is_only_added_for_com_interfaces() {
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let cpp_class = "
+          #define STDMETHODCALLTYPE
+          class Foo {
+          public:
+            virtual int STDMETHODCALLTYPE bar() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(int, bar, (), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn using_declarations_for_mocked_methods_are_kept() {
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let cpp_class = "
+          class Base {
+          public:
+            virtual ~Base() = default;
+            void bar(const char* text) {}
+            void fizz(const char* text) {}
+          };
+          class Foo : public Base {
+          public:
+            using Base::bar;
+            using Base::fizz;
+            virtual void bar(int x) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  using Foo::bar;",
+            "  MOCK_METHOD(void, bar, (int x), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn inherited_constructors_are_forwarded() {
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let cpp_class = "
+          class Base {
+          public:
+            explicit Base(int x) {}
+            virtual ~Base() = default;
+          };
+          class Foo : public Base {
+          public:
+            using Base::Base;
+            virtual void bar() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  using Foo::Foo;",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn deprecated_methods_can_be_skipped() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .skip_deprecated_methods(true);
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            [[deprecated]] virtual void bar() = 0;
+            virtual void fizz() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, fizz, (), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn standard_attributes_are_kept_when_configured() {
+    let mut mocksmith = Mocksmith::new_when_available().unwrap();
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            [[nodiscard]] virtual int bar() = 0;
+            [[deprecated(\"Use bar\")]] [[nodiscard]] virtual int fizz() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(int, bar, (), (override));",
+            "  MOCK_METHOD(int, fizz, (), (override));",
+            "};"
+        )
+    );
+
+    mocksmith = mocksmith.keep_method_attributes(true);
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(int, DoBar, (), ());",
+            "  [[nodiscard]] int bar() override",
+            "  {",
+            "    return DoBar();",
+            "  }",
+            "  MOCK_METHOD(int, DoFizz, (), ());",
+            "  [[nodiscard, deprecated]] int fizz() override",
+            "  {",
+            "    return DoFizz();",
+            "  }",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn mocks_with_standard_attributes_compile() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .keep_method_attributes(true);
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            [[nodiscard]] virtual int bar(int value) const = 0;
+            [[deprecated]] virtual void fizz(int &&value) = 0;
+          };";
+    let mocks = mocksmith.create_mocks_from_string(cpp_class).unwrap();
+    let code = format!(
+        "{cpp_class}\n#include <gmock/gmock.h>\n{}",
+        mocks
+            .iter()
+            .map(|mock| mock.code.as_str())
+            .collect::<String>()
+    );
+    if let Some(compiled) = compiles_with_gmock(&code) {
+        assert!(compiled, "Generated code doesn't compile:\n{code}");
+    }
+}
+
+#[test]
+fn method_filter_avoids_mocking_unwanted_methods() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .method_filter_fun(|method| {
+            assert_eq!(method.class_name, "Foo");
+            method.name != "debugDump" && method.signature != "void (int) const"
+        });
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar(int x) = 0;
+            virtual void bar(int x) const = 0;
+            virtual void debugDump() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (int x), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn using_declarations_from_several_bases_are_deduplicated() {
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let cpp_class = "
+          class Base {
+          public:
+            virtual ~Base() = default;
+          };
+          class A : public virtual Base {
+          public:
+            void bar(const char* text) {}
+          };
+          class B : public virtual Base {
+          public:
+            void bar(double value) {}
+          };
+          class Foo : public A, public B {
+          public:
+            using A::bar;
+            using B::bar;
+            virtual void bar(int x) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  using Foo::bar;",
+            "  MOCK_METHOD(void, bar, (int x), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn macros_in_argument_types_are_kept() {
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let cpp_class = "
+          #define DWORD unsigned long
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar(DWORD value, const DWORD* ptr, DWORD) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (DWORD value, const DWORD * ptr, DWORD), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn const_overload_helpers_are_generated_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .const_overload_helpers(true);
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual int& get(int index) = 0;
+            virtual const int& get(int index) const = 0;
+            virtual void set(int value) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(int &, get, (int index), (override));",
+            "  MOCK_METHOD(const int &, get, (int index), (const, override));",
+            "  MOCK_METHOD(void, set, (int value), (override));",
+            "",
+            "  // Makes the non-const get() call the const overload by default, so that",
+            "  // expectations only need to be set on the const overload",
+            "  void DelegateGetToConst()",
+            "  {",
+            "    ON_CALL(*this, get(::testing::A<int>()))",
+            "      .WillByDefault([this](int arg0) -> int & {",
+            "        return const_cast<int &>(static_cast<const MockFoo *>(this)->get(arg0));",
+            "      });",
+            "  }",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn function_pointer_and_array_arguments_get_names_inside_types() {
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let cpp_class = "
+          #define DWORD unsigned long
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void on(void (*cb)(int)) = 0;
+            virtual void off(void (*cb)(int, int), int (&values)[8]) = 0;
+            virtual void set(DWORD values[8]) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, on, (void (*cb)(int)), (override));",
+            "  MOCK_METHOD(void, off, ((void (*cb)(int, int)), int (&values)[8]), (override));",
+            "  MOCK_METHOD(void, set, (DWORD values[8]), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn move_only_helpers_are_generated_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .move_only_helpers(true);
+    let cpp_class = "
+          namespace std {
+            template<typename T> class unique_ptr {};
+          }
+          class Buzz {};
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual bool share(std::unique_ptr<Buzz> buzz, int ts) = 0;
+            virtual std::unique_ptr<Buzz> make(int size) const = 0;
+            virtual void bar() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(bool, DoShare, (Buzz * buzz, int ts), ());",
+            "  bool share(std::unique_ptr<Buzz> buzz, int ts) override",
+            "  {",
+            "    return DoShare(buzz.get(), ts);",
+            "  }",
+            "  MOCK_METHOD(Buzz *, DoMake, (int size), (const));",
+            "  std::unique_ptr<Buzz> make(int size) const override",
+            "  {",
+            "    return std::unique_ptr<Buzz>(DoMake(size));",
+            "  }",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn reference_default_actions_are_added_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .reference_default_actions(true);
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual const int& get(int index) const = 0;
+            virtual void set(int value) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MockFoo()",
+            "  {",
+            "    ON_CALL(::testing::Const(*this), get(::testing::A<int>()))",
+            "      .WillByDefault([](auto &&...) -> const int & {",
+            "        static const int dummy{};",
+            "        return dummy;",
+            "      });",
+            "  }",
+            "  MOCK_METHOD(const int &, get, (int index), (const, override));",
+            "  MOCK_METHOD(void, set, (int value), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn reference_default_actions_are_added_for_const_and_non_const_overloads() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .reference_default_actions(true);
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual const int *& get() = 0;
+            virtual const int *& get() const = 0;
+          };";
+    let mocks = mocksmith.create_mocks_from_string(cpp_class);
+    assert_mocks!(
+        mocks.as_ref(),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MockFoo()",
+            "  {",
+            "    ON_CALL(*this, get())",
+            "      .WillByDefault([](auto &&...) -> const int *& {",
+            "        static const int * dummy{};",
+            "        return dummy;",
+            "      });",
+            "    ON_CALL(::testing::Const(*this), get())",
+            "      .WillByDefault([](auto &&...) -> const int *& {",
+            "        static const int * dummy{};",
+            "        return dummy;",
+            "      });",
+            "  }",
+            "  MOCK_METHOD(const int *&, get, (), (override));",
+            "  MOCK_METHOD(const int *&, get, (), (const, override));",
+            "};"
+        )
+    );
+    let code = format!(
+        "{cpp_class}\n#include <gmock/gmock.h>\n{}",
+        mocks.unwrap()[0].code
+    );
+    if let Some(compiled) = compiles_with_gmock(&code) {
+        assert!(compiled, "Generated code doesn't compile:\n{code}");
+    }
+}
+
+#[test]
+fn value_default_actions_are_added_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .value_default_actions(true);
+    let cpp_class = "
+          enum class Color { Red };
+          struct Point { int x; };
+          struct NoDefault { NoDefault(int); };
+          struct MoveOnly { MoveOnly() = default; MoveOnly(MoveOnly &&) = default; };
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void run() = 0;
+            virtual int count() const = 0;
+            virtual bool ok() = 0;
+            virtual Color color() = 0;
+            virtual Point * find(int id) = 0;
+            virtual Point point() = 0;
+            virtual NoDefault noDefault() = 0;
+            virtual MoveOnly moveOnly() = 0;
+          };";
+    let mocks = mocksmith.create_mocks_from_string(cpp_class);
+    assert_mocks!(
+        mocks.as_ref(),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MockFoo()",
+            "  {",
+            "    ON_CALL(::testing::Const(*this), count())",
+            "      .WillByDefault(::testing::Return(0));",
+            "    ON_CALL(*this, ok())",
+            "      .WillByDefault(::testing::Return(false));",
+            "    ON_CALL(*this, color())",
+            "      .WillByDefault(::testing::Return(Color{}));",
+            "    ON_CALL(*this, find(::testing::A<int>()))",
+            "      .WillByDefault(::testing::Return(nullptr));",
+            "    ON_CALL(*this, point())",
+            "      .WillByDefault(::testing::Return(Point{}));",
+            "  }",
+            "  MOCK_METHOD(void, run, (), (override));",
+            "  MOCK_METHOD(int, count, (), (const, override));",
+            "  MOCK_METHOD(bool, ok, (), (override));",
+            "  MOCK_METHOD(Color, color, (), (override));",
+            "  MOCK_METHOD(Point *, find, (int id), (override));",
+            "  MOCK_METHOD(Point, point, (), (override));",
+            "  MOCK_METHOD(NoDefault, noDefault, (), (override));",
+            "  MOCK_METHOD(MoveOnly, moveOnly, (), (override));",
+            "};"
+        )
+    );
+    let code = format!(
+        "{cpp_class}\n#include <gmock/gmock.h>\n{}",
+        mocks.unwrap()[0].code
+    );
+    if let Some(compiled) = compiles_with_gmock(&code) {
+        assert!(compiled, "Generated code doesn't compile:\n{code}");
+    }
+}
+
+#[test]
+fn line_directives_are_added_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .line_directives(true);
+    let cpp_class = "class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar() = 0;
+
+            virtual int fizz(int value) const = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  #line 4 \"mocksmith_dummy_input_file.h\"",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "  #line 7 \"MockFoo.h\"",
+            "  #line 6 \"mocksmith_dummy_input_file.h\"",
+            "  MOCK_METHOD(int, fizz, (int value), (const, override));",
+            "  #line 10 \"MockFoo.h\"",
+            "};"
+        )
+    );
+
+    // In mock headers, the numbering of the header is restored
+    let source_file = temp_file_from(cpp_class);
+    let mocksmith = mocksmith.line_directives_file_fun(|_| "mocks.h".to_string());
+    let header = mocksmith
+        .create_mock_header_for_files(&[source_file.path()])
+        .unwrap();
+    let restored = header
+        .code
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let line_number = line
+                .trim()
+                .strip_prefix("#line ")?
+                .strip_suffix(" \"mocks.h\"")?;
+            Some((index, line_number.parse::<usize>().unwrap()))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(restored.len(), 2);
+    for (index, line_number) in restored {
+        assert_eq!(line_number, index + 2);
+    }
+}
+
+#[test]
+fn signature_checks_are_generated_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .signature_checks(true);
+    let cpp_class = "
+          namespace foo {
+          struct Bar {};
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar(const Bar & bar) const noexcept = 0;
+            virtual int get(int a) = 0;
+            virtual int get(int a) const = 0;
+            virtual void (*callback())(int) = 0;
+            struct Result {};
+            virtual Result result(const Result & previous) = 0;
+          protected:
+            virtual void hidden() = 0;
+          };
+          }";
+    let mocks = mocksmith.create_mocks_from_string(cpp_class).unwrap();
+    let checks = mocks[0].signature_checks.as_deref().unwrap();
+    assert_eq!(
+        checks,
+        lines!(
+            "namespace foo {",
+            "static_assert(std::is_same<decltype(&Foo::bar), void (Foo::*)(const Bar &) const \
+             noexcept>::value, \"Signature of foo::Foo::bar has changed\");",
+            "static_assert(static_cast<int (Foo::*)(int)>(&Foo::get) != nullptr, \
+             \"Signature of foo::Foo::get has changed\");",
+            "static_assert(static_cast<int (Foo::*)(int) const>(&Foo::get) != nullptr, \
+             \"Signature of foo::Foo::get has changed\");",
+            "static_assert(std::is_same<decltype(&Foo::callback), auto (Foo::*)() -> \
+             void (*)(int)>::value, \"Signature of foo::Foo::callback has changed\");",
+            "static_assert(std::is_same<decltype(&Foo::result), foo::Foo::Result (Foo::*)(const \
+             foo::Foo::Result &)>::value, \"Signature of foo::Foo::result has changed\");",
+            "}"
+        )
+    );
+    let code = format!("{cpp_class}\n#include <type_traits>\n{checks}");
+    if let Some(compiled) = compiles_with_gmock(&code) {
+        assert!(compiled, "Generated code doesn't compile:\n{code}");
+    }
+}
+
+#[test]
+fn mock_macro_can_be_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .mock_macro("MY_MOCK_METHOD".to_string());
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar(int value) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MY_MOCK_METHOD(void, bar, (int value), (override));",
+            "};"
+        )
+    );
+    let mocksmith = mocksmith.mock_macro("MY-MOCK".to_string());
+    assert_eq!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        Err(MocksmithError::InvalidIdentifier("MY-MOCK".to_string()))
+    );
+}
+
+#[test]
+fn destructors_are_mocked_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .mock_destructor(true);
+    let cpp_class = "
+          class Base {
+          public:
+            virtual ~Base() = default;
+          };
+          class Foo : public Base {
+          public:
+            virtual void bar() = 0;
+          };
+          class NonVirtual {
+          public:
+            virtual void bar() = 0;
+          };";
+    let mocks = mocksmith.create_mocks_from_string(cpp_class);
+    assert_mocks!(
+        mocks.as_ref(),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  ~MockFoo() override { Die(); }",
+            "  MOCK_METHOD(void, Die, ());",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};"
+        ),
+        lines!(
+            "class MockNonVirtual : public NonVirtual",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};"
+        )
+    );
+    let code = format!(
+        "{cpp_class}\n#include <gmock/gmock.h>\n{}",
+        mocks.unwrap()[0].code
+    );
+    if let Some(compiled) = compiles_with_gmock(&code) {
+        assert!(compiled, "Generated code doesn't compile:\n{code}");
+    }
+}
+
+#[test]
+fn types_with_commas_are_aliased_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .comma_type_aliases(true);
+    let cpp_class = "
+          #include <map>
+          class Foo {
+          public:
+            virtual std::map<int, int> bar(const std::map<int, int> & m, int i) = 0;
+            virtual std::map<int, int> bar(std::map<int, int> m) = 0;
+          };";
+    let mocks = mocksmith.create_mocks_from_string(cpp_class);
+    assert_mocks!(
+        mocks.as_ref(),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  using MockFoo_bar_return = std::map<int, int>;",
+            "  using MockFoo_bar_arg0 = const std::map<int, int> &;",
+            "  MOCK_METHOD(MockFoo_bar_return, bar, (MockFoo_bar_arg0 m, int i), (override));",
+            "  using MockFoo_bar_return_2 = std::map<int, int>;",
+            "  using MockFoo_bar_arg0_2 = std::map<int, int>;",
+            "  MOCK_METHOD(MockFoo_bar_return_2, bar, (MockFoo_bar_arg0_2 m), (override));",
+            "};"
+        )
+    );
+    let code = format!(
+        "{cpp_class}\n#include <gmock/gmock.h>\n{}",
+        mocks.unwrap()[0].code
+    );
+    if let Some(compiled) = compiles_with_gmock(&code) {
+        assert!(compiled, "Generated code doesn't compile:\n{code}");
+    }
+}
+
+#[test]
+fn cheat_sheets_select_overloads_with_typed_matchers() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .emit_cheat_sheet(true);
+    let mocks = mocksmith
+        .create_mocks_from_string(
+            "namespace ns {
+             class Foo {
+             public:
+               virtual void bar(int a, const char * b) = 0;
+               virtual int baz(int a) = 0;
+               virtual int baz(int a) const = 0;
+             };
+             }",
+        )
+        .unwrap();
+    assert_eq!(
+        mocksmith.create_cheat_sheet_for_mock(&mocks[0]).unwrap(),
+        lines!(
+            "# MockFoo",
+            "",
+            "Expectations for the methods of `ns::Foo` mocked by `MockFoo mock`.",
+            "",
+            "```cpp",
+            "using ::testing::_;",
+            "",
+            "EXPECT_CALL(mock, bar(_, _));",
+            "EXPECT_CALL(mock, baz(::testing::A<int>()));",
+            "EXPECT_CALL(::testing::Const(mock), baz(::testing::A<int>()));",
+            "```"
+        )
+    );
+}
+
+#[test]
+fn documentation_lists_mocked_and_skipped_methods() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .emit_documentation(true)
+        .skip_deprecated_methods(true)
+        .methods_to_mock(mocksmith::MethodsToMockStrategy::AllVirtual)
+        .method_filter_fun(|method| method.name != "excluded");
+    let source_file = temp_file_from(
+        "namespace ns {
+             class Foo {
+             public:
+               virtual int bar(int a) const = 0;
+               void helper();
+               [[deprecated]] virtual void old() = 0;
+               virtual void excluded() = 0;
+             };
+             }",
+    );
+    let header = mocksmith
+        .create_mock_header_for_files(&[source_file.path()])
+        .unwrap();
+    assert_eq!(
+        mocksmith
+            .create_documentation_for_header(&header, Path::new("mocks/MockFoo.h"))
+            .unwrap(),
+        lines!(
+            format!("# Mocks of `{}`", source_file.path().to_string_lossy()),
+            "",
+            "Mocks are written to `mocks/MockFoo.h`.",
+            "",
+            "## ns::Foo",
+            "",
+            "Mocked by `MockFoo` in namespace `ns`.",
+            "",
+            "Mocked methods:",
+            "",
+            "- `bar`: `int (int) const`",
+            "",
+            "Skipped methods:",
+            "",
+            "- `helper`: not virtual",
+            "- `old`: deprecated",
+            "- `excluded`: excluded by method filter"
+        )
+    );
+}
+
+#[test]
+fn classes_are_listed_with_method_counts() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .class_filter_fun(|name| name != "Filtered");
+    let source_file = temp_file_from(
+        "namespace ns {
+         class Foo {
+         public:
+           virtual void a() = 0;
+           virtual void b();
+           void c();
+           static void d();
+         };
+         }
+         class Filtered {
+         public:
+           virtual void a() = 0;
+         };
+         class Plain {
+         public:
+           void a();
+         };",
+    );
+    let classes = mocksmith.list_classes_in_file(source_file.path()).unwrap();
+    let summary = |class: &ClassInfo| {
+        (
+            class.qualified_name.clone(),
+            class.file.clone(),
+            class.line,
+            class.methods,
+            class.virtual_methods,
+            class.pure_virtual_methods,
+        )
+    };
+    let file = Some(source_file.path().to_path_buf());
+    assert_eq!(
+        classes.iter().map(summary).collect::<Vec<_>>(),
+        vec![
+            ("ns::Foo".to_string(), file.clone(), 2, 3, 2, 1),
+            ("Plain".to_string(), file, 14, 1, 0, 0)
+        ]
+    );
+}
+
+#[test]
+fn model_is_emitted_as_json() {
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let source_file = temp_file_from(
+        "namespace ns {
+         class Foo {
+         public:
+           virtual int bar(const char * name) const noexcept = 0;
+           void helper();
+         };
+         }",
+    );
+    let model = mocksmith
+        .create_model_for_files(&[source_file.path()])
+        .unwrap();
+    let model: serde_json::Value = serde_json::from_str(&model).unwrap();
+    let file = source_file.path().to_string_lossy();
+    assert_eq!(
+        model,
+        serde_json::json!({
+            "classes": [{
+                "name": "Foo",
+                "qualified_name": "ns::Foo",
+                "namespaces": ["ns"],
+                "file": file,
+                "line": 2,
+                "methods": [{
+                    "name": "bar",
+                    "result_type": "int",
+                    "arguments": [{ "type_name": "const char *", "name": "name" }],
+                    "is_const": true,
+                    "is_virtual": true,
+                    "is_pure_virtual": true,
+                    "is_noexcept": true,
+                    "is_deprecated": false,
+                    "ref_qualifier": null,
+                    "calltype": null,
+                    "access": "public",
+                    "attributes": [],
+                    "file": file,
+                    "line": 4,
+                }],
+                "skipped_methods": [{ "name": "helper", "reason": "not virtual" }],
+            }]
+        })
+    );
+}
+
+#[test]
+fn parse_errors_in_string_parsed_as_file_report_the_file() {
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let result = mocksmith.create_mocks_from_string_as_file(
+        "class Foo {\npublic:\n  virtual Unknown bar() = 0;\n};",
+        Path::new("some/dir/foo.h"),
+    );
+    assert!(matches!(
+        result,
+        Err(MocksmithError::ParseError { file: Some(file), line: 3, .. })
+            if file == Path::new("some/dir/foo.h")
+    ));
+}
+
+#[test]
+fn class_exclude_is_applied_after_class_filter() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .class_filter_fun(|class_name| class_name.starts_with("I"))
+        .class_exclude_fun(|class_name| class_name.starts_with("IInternal"));
+    let mocks = mocksmith
+        .create_mocks_from_string(
+            "class IFoo { public: virtual void foo() = 0; };
+             class IInternalBar { public: virtual void bar() = 0; };
+             class Baz { public: virtual void baz() = 0; };",
+        )
+        .unwrap();
+    assert_eq!(
+        mocks
+            .iter()
+            .map(|mock| mock.parent_name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["IFoo"]
+    );
+}
+
+#[test]
+fn content_of_kept_regions_is_preserved_when_regenerating_mocks() {
+    let mocksmith = Mocksmith::new_when_available().unwrap().keep_regions(true);
+    let mocks = mocksmith.create_mocks_from_string(
+        "class Foo {
+         public:
+           virtual void bar() = 0;
+         };",
+    );
+    assert_mocks!(
+        mocks.as_ref(),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  // mocksmith:keep(MockFoo)",
+            "  // mocksmith:endkeep",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};"
+        )
+    );
+
+    let existing = lines!(
+        "class MockFoo : public Foo",
+        "{",
+        "public:",
+        "  // mocksmith:keep(MockFoo)",
+        "  int calls = 0;",
+        "  // mocksmith:endkeep",
+        "  MOCK_METHOD(void, baz, (), (override));",
+        "};"
+    );
+    assert_eq!(
+        mocksmith.preserve_kept_regions(&existing, &mocks.unwrap()[0].code),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  // mocksmith:keep(MockFoo)",
+            "  int calls = 0;",
+            "  // mocksmith:endkeep",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn force_included_headers_are_parsed_before_source_headers() {
+    let prefix_header = temp_file_from("#define EXPORT\ntypedef int Value;\n");
+    let cpp_class = "class EXPORT Foo { public: virtual Value bar() = 0; };";
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    assert!(mocksmith.create_mocks_from_string(cpp_class).is_err());
+
+    let mocksmith = mocksmith.force_includes(vec![prefix_header.path().to_path_buf()]);
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(Value, bar, (), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn macros_are_defined_and_undefined_when_parsing() {
+    let cpp_class = "
+          #if defined(FIRST) && !defined(SECOND)
+          class Foo { public: virtual VALUE bar() = 0; };
+          #endif";
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .define_macros(vec![
+            "FIRST".to_string(),
+            "SECOND".to_string(),
+            "VALUE=int".to_string(),
+        ])
+        .undefine_macros(vec!["SECOND".to_string()]);
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(int, bar, (), (override));",
+            "};"
+        )
+    );
+
+    let mocksmith = mocksmith.define_macros(vec!["NOT-A-MACRO=1".to_string()]);
+    assert_eq!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        Err(MocksmithError::InvalidParserOption(
+            "Invalid macro definition: NOT-A-MACRO=1".to_string()
+        ))
+    );
+}
+
+#[test]
+fn invalid_sysroot_and_target_are_errors() {
+    let cpp_class = "class Foo { public: virtual void bar() = 0; };";
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .sysroot(Some(PathBuf::from("non/existing/sysroot")));
+    assert_eq!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        Err(MocksmithError::InvalidParserOption(
+            "System root is not a directory: non/existing/sysroot".to_string()
+        ))
+    );
+    let mocksmith = mocksmith
+        .sysroot(None)
+        .target(Some("not a triple".to_string()));
+    assert_eq!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        Err(MocksmithError::InvalidParserOption(
+            "Invalid target triple: not a triple".to_string()
+        ))
+    );
+}
+
+#[test]
+fn diagnostics_are_reported_to_diagnostic_function() {
+    let diagnostics = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let reported = diagnostics.clone();
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .skip_deprecated_methods(true)
+        .diagnostic_fun(move |diagnostic| reported.borrow_mut().push(diagnostic.clone()));
+    let mocks = mocksmith.create_mocks_from_string(
+        "class Foo {
+         public:
+           [[deprecated]] virtual void bar() = 0;
+         };",
+    );
+    assert!(mocks.is_ok());
+    {
+        let diagnostics = diagnostics.borrow();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(
+            diagnostics[0].message,
+            "Pure virtual method Foo::bar is not mocked, so the mock is abstract"
+        );
+        assert_eq!(diagnostics[0].source, DiagnosticSource::Mocksmith);
+    }
+
+    let mocks = mocksmith.create_mocks_from_string("class Foo { virtual Unknown bar() = 0; };");
+    assert!(mocks.is_err());
+    let diagnostics = diagnostics.borrow();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[1].severity, Severity::Error);
+    assert_eq!(diagnostics[1].message, "unknown type name 'Unknown'");
+    assert_eq!(diagnostics[1].source, DiagnosticSource::Clang);
+}
+
+#[test]
+fn timings_are_collected_per_phase_when_enabled() {
+    let source_file = temp_file_from("class Foo { virtual void bar() = 0; };");
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    assert!(mocksmith.create_mocks_for_file(source_file.path()).is_ok());
+    assert!(mocksmith.take_timings().is_empty());
+
+    let mocksmith = mocksmith.collect_timings(true);
+    assert!(mocksmith.create_mocks_for_file(source_file.path()).is_ok());
+    let timings = mocksmith.take_timings();
+    assert_eq!(
+        timings
+            .iter()
+            .map(|timing| timing.phase)
+            .collect::<Vec<_>>(),
+        vec![Phase::Parse, Phase::Traversal, Phase::Generation]
+    );
+    assert!(
+        timings
+            .iter()
+            .all(|timing| timing.file.as_deref() == Some(source_file.path()))
+    );
+    assert!(mocksmith.take_timings().is_empty());
+}
+
+#[test]
+fn builder_configures_each_instance_built() {
+    let cpp_classes = "
+          class IFoo { public: virtual void foo() = 0; };
+          class Bar { public: virtual void bar() = 0; };";
+    let builder = MocksmithBuilder::new()
+        .class_filter_fun(|class_name| class_name.starts_with('I'))
+        .mock_name_fun(|class_name| format!("{class_name}Fake"))
+        .configure(|mocksmith| mocksmith.sort_output(true));
+
+    for _ in 0..2 {
+        let mocks = builder
+            .run(|mocksmith| mocksmith.create_mocks_from_string(cpp_classes))
+            .unwrap();
+        assert_eq!(mocks.len(), 1);
+        assert_eq!(mocks[0].name, "IFooFake");
+    }
+    let mocksmith = builder.build_when_available().unwrap();
+    let mocks = mocksmith.create_mocks_from_string(cpp_classes).unwrap();
+    assert_eq!(mocks[0].name, "IFooFake");
+}
+
+#[test]
+fn parsed_classes_describe_methods_to_mock() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .method_filter_fun(|method| method.name != "skipped");
+    let classes = mocksmith
+        .parse_string(
+            "namespace foo {
+             class Bar {
+             public:
+               virtual int baz(const char *name, bool) const noexcept = 0;
+               virtual void skipped() = 0;
+             };
+             }",
+        )
+        .unwrap();
+    assert_eq!(classes.len(), 1);
+    assert_eq!(classes[0].qualified_name(), "foo::Bar");
+    assert_eq!(classes[0].line, 2);
+    assert_eq!(
+        classes[0].skipped_methods,
+        vec![(
+            "skipped".to_string(),
+            "excluded by method filter".to_string()
+        )]
+    );
+    let method = &classes[0].methods[0];
+    assert_eq!(method.name, "baz");
+    assert_eq!(method.result_type, "int");
+    assert!(method.is_const && method.is_pure_virtual && method.is_noexcept);
+    assert_eq!(method.access, "public");
+    assert_eq!(method.line, 4);
+    assert_eq!(
+        method
+            .arguments
+            .iter()
+            .map(|arg| (&arg.type_name, &arg.name))
+            .collect::<Vec<_>>(),
+        vec![
+            (&"const char *".to_string(), &Some("name".to_string())),
+            (&"bool".to_string(), &None)
+        ]
+    );
+}
+
+#[test]
+fn mock_headers_are_passed_to_function_one_file_at_a_time() {
+    let first_file = temp_file_from("class IFirst { virtual void foo() = 0; };");
+    let broken_file = temp_file_from("class IBroken { virtual Unknown foo() = 0; };");
+    let second_file = temp_file_from("class ISecond { virtual void bar() = 0; };");
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+
+    let mut results = Vec::new();
+    mocksmith.create_mocks_for_files_with(
+        &[first_file.path(), broken_file.path(), second_file.path()],
+        |header| {
+            results.push(header.map(|header| header.mocks[0].name.clone()));
+        },
+    );
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0], Ok("MockFirst".to_string()));
+    assert!(matches!(results[1], Err(MocksmithError::ParseError { .. })));
+    assert_eq!(results[2], Ok("MockSecond".to_string()));
+}
+
+#[test]
+fn handle_forwards_requests_from_several_threads() {
+    let handle =
+        MocksmithHandle::spawn(MocksmithBuilder::new().mock_name_fun(|name| format!("{name}Fake")))
+            .unwrap();
+    let threads = ["IFirst", "ISecond"].map(|class_name| {
+        let handle = handle.clone();
+        std::thread::spawn(move || {
+            handle.run(move |mocksmith| {
+                mocksmith.create_mocks_from_string(&format!(
+                    "class {class_name} {{ virtual void foo() = 0; }};"
+                ))
+            })
+        })
+    });
+    let names = threads.map(|thread| thread.join().unwrap().unwrap()[0].name.clone());
+    assert_eq!(names, ["IFirstFake", "ISecondFake"]);
+}
+
+#[test]
+fn all_diagnostics_are_collected_when_parsing_fails() {
+    let source_file = temp_file_from(
+        "class Foo {
+         public:
+           virtual First bar() = 0;
+           virtual Second baz() = 0;
+         };",
+    );
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .collect_diagnostics(true);
+    assert!(mocksmith.create_mocks_for_file(source_file.path()).is_err());
+    let diagnostics = mocksmith.take_diagnostics();
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|diagnostic| (
+                diagnostic.severity,
+                diagnostic.message.as_str(),
+                diagnostic.line
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            (Severity::Error, "unknown type name 'First'", Some(3)),
+            (Severity::Error, "unknown type name 'Second'", Some(4)),
+        ]
+    );
+    assert!(mocksmith.take_diagnostics().is_empty());
+}
+
+#[test]
+fn spans_locate_mocks_and_includes_in_mock_header() {
+    let source_file = temp_file_from(
+        "class IFoo {
+         public:
+           virtual void foo() = 0;
+         };
+         class IBar {
+         public:
+           virtual void bar() = 0;
+         };",
+    );
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let header = mocksmith
+        .create_mock_header_for_files(&[source_file.path()])
+        .unwrap();
+    assert_eq!(header.mock_spans.len(), 2);
+    for (mock, span) in header.mocks.iter().zip(&header.mock_spans) {
+        let span = span.as_ref().unwrap();
+        assert_eq!(
+            header.code[span.bytes.clone()].trim_end(),
+            mock.code.trim_end()
+        );
+        assert_eq!(
+            header.code.lines().nth(span.lines.start - 1).unwrap(),
+            mock.code.lines().next().unwrap()
+        );
+    }
+    let includes_span = header.includes_span.unwrap();
+    assert!(header.code[includes_span.bytes].contains("#include <gmock/gmock.h>"));
+    assert!(includes_span.lines.end <= header.mock_spans[0].as_ref().unwrap().lines.start);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn mock_header_and_parsed_model_can_be_serialized_and_deserialized() {
+    let source_file = temp_file_from(
+        "class IFoo {
+         public:
+           virtual int foo(const char* name) const = 0;
+         };",
+    );
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let header = mocksmith
+        .create_mock_header_for_files(&[source_file.path()])
+        .unwrap();
+    let json = serde_json::to_string(&header).unwrap();
+    assert_eq!(
+        serde_json::from_str::<mocksmith::MockHeader>(&json).unwrap(),
+        header
+    );
+
+    let classes = mocksmith.parse_file(source_file.path()).unwrap();
+    let json = serde_json::to_string(&classes).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Vec<mocksmith::ParsedClass>>(&json).unwrap(),
+        classes
+    );
+}
+
+#[test]
+fn virtual_files_are_found_when_included() {
+    let include_dir = temp_dir();
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .include_path(include_dir.path())
+        .virtual_file(
+            include_dir.path().join("types.h"),
+            "struct Result { int value; };",
+        )
+        .virtual_file(
+            "base.h",
+            "class IBase { public: virtual ~IBase() = default; };",
+        );
+    let cpp_class = "
+          #include \"base.h\"
+          #include <types.h>
+          class IFoo : public IBase {
+          public:
+            virtual Result foo() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public IFoo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(Result, foo, (), (override));",
+            "};"
+        )
+    );
+    assert!(!include_dir.path().join("types.h").exists());
+}
+
+#[test]
+fn pointer_to_member_and_nested_function_arguments_are_declared_correctly() {
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let cpp_class = "
+          class Bar { public: int value; void run(int) const; };
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void member(int Bar::* field) = 0;
+            virtual void method(void (Bar::*fun)(int) const) = 0;
+            virtual void nested(void (*(*factory)(int))(double)) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, member, (int Bar::* field), (override));",
+            "  MOCK_METHOD(void, method, (void (Bar::*fun)(int) const), (override));",
+            "  MOCK_METHOD(void, nested, (void (*(*factory)(int))(double)), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn cpp_classes_are_mocked_in_objective_cpp_headers() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .language(Language::ObjectiveCpp);
+    let cpp_class = "
+          @class NSString;
+          @interface Greeter
+          - (void)greet:(NSString*)name;
+          @end
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar(Greeter* greeter) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (Greeter * greeter), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn argument_types_are_extracted_from_non_ascii_sources() {
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let cpp_class = "
+          // Räksmörgås, 日本語 and emojis 🦀 shift byte offsets from character offsets
+          #define DWORD unsigned long
+          struct Größe {};
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            /* Kommentar med åäö */ virtual void bar(DWORD värde, const char* text = \"åäö\") = 0;
+            virtual void fizz(Größe größe, DWORD (*återanrop)(DWORD)) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (DWORD värde, const char * text), (override));",
+            "  MOCK_METHOD(void, fizz, (Größe größe, DWORD (*återanrop)(DWORD)), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn nice_and_strict_mock_aliases_are_added_when_configured() {
+    let mocksmith = Mocksmith::new_when_available().unwrap().mock_aliases(true);
+    let cpp_class = "
+          namespace foo {
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar() = 0;
+          };
+          }";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "namespace foo {",
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};",
+            "using NiceMockFoo = ::testing::NiceMock<MockFoo>;",
+            "using StrictMockFoo = ::testing::StrictMock<MockFoo>;",
+            "}"
+        )
+    );
+}
+
+#[test]
+fn constructors_and_destructors_are_defined_in_source_when_splitting() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .split_source_file(true);
+    let cpp_class = "
+          namespace foo {
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar() = 0;
+          };
+          }";
+    let mocks = mocksmith.create_mocks_from_string(cpp_class).unwrap();
+    assert_eq!(
+        mocks[0].code,
+        lines!(
+            "namespace foo {",
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MockFoo();",
+            "  ~MockFoo();",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};",
+            "}"
+        )
+    );
+    assert_eq!(
+        mocks[0].definitions.as_deref(),
+        Some(
+            lines!(
+                "namespace foo {",
+                "MockFoo::MockFoo() {}",
+                "MockFoo::~MockFoo() {}",
+                "}"
+            )
+            .as_str()
+        )
+    );
+}
+
+#[test]
+fn fakes_are_generated_with_fake_style() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .style(MockStyle::Fake);
+    let cpp_class = "
+          class IFoo {
+          public:
+            virtual ~IFoo() = default;
+            virtual void bar(int value) = 0;
+            virtual int fizz(const char* text) const noexcept = 0;
+            virtual const int& buzz() = 0;
+            virtual const int*& pointer() const & = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class FakeFoo : public IFoo",
+            "{",
+            "public:",
+            "  void bar(int) override {}",
+            "  int fizz(const char *) const noexcept override { return {}; }",
+            "  const int & buzz() override { static const int dummy{}; return dummy; }",
+            "  const int *& pointer() const & override { static const int * dummy{}; return dummy; }",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn fakeit_mocks_are_generated_with_fakeit_style() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .style(MockStyle::FakeIt);
+    let cpp_class = "
+          class IFoo {
+          public:
+            virtual ~IFoo() = default;
+            virtual void bar(int value) = 0;
+            virtual int& get(int index) = 0;
+            virtual const int& get(int index) const = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public fakeit::Mock<IFoo>",
+            "{",
+            "public:",
+            "  MockFoo()",
+            "  {",
+            "    Fake(Method(*this, bar));",
+            "    Fake(OverloadedMethod(*this, get, int &(int)));",
+            "    Fake(ConstOverloadedMethod(*this, get, const int &(int)));",
+            "  }",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn template_controls_mock_layout() {
+    let template = "
+{% macro mock(class) %}
+struct {{ class.name }} : {{ class.class_name }} {
+{% for method in class.methods %}
+    {{ method.mock_method }}
+{% endfor %}
+};
+{% endmacro %}"
+        .to_string();
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .template(Some(template));
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar(int value) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "struct MockFoo : Foo {",
+            "    MOCK_METHOD(void, bar, (int value), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn license_is_kept_and_banner_available_with_template() {
+    let dir = temp_dir();
+    let header = dir.path().join("ifoo.h");
+    std::fs::write(
+        &header,
+        "class IFoo { public: virtual ~IFoo() = default; virtual void bar() = 0; };",
+    )
+    .unwrap();
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .include_path(dir.path())
+        .license(Some("Copyright Acme".to_string()))
+        .banner(Some("Mocks of {CLASSES}".to_string()))
+        .template(Some(
+            "{{ banner }}\n{% for mock in mocks %}{{ mock.code }}{% endfor %}".to_string(),
+        ));
+    let mock_header = mocksmith.create_mock_header_for_files(&[&header]).unwrap();
+    assert!(mock_header.code.starts_with(&lines!(
+        "// Copyright Acme",
+        "// Mocks of IFoo",
+        "class MockFoo : public IFoo"
+    )));
+}
+
+#[test]
+fn template_controls_method_layout_and_header_sees_classes() {
+    let dir = temp_dir();
+    let header = dir.path().join("ifoo.h");
+    std::fs::write(
+        &header,
+        "class IFoo { public: virtual ~IFoo() = default; virtual void bar() = 0; };",
+    )
+    .unwrap();
+    let template = "
+{% for mock in mocks %}
+// {{ mock.class.methods | map(attribute='name') | join(', ') }}
+{{ mock.code }}
+{%- endfor %}
+{% macro method(method) %}
+// Mock of {{ method.name }}
+{{ method.mock_method }}
+{% endmacro %}"
+        .to_string();
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .include_path(dir.path())
+        .template(Some(template));
+    let mock_header = mocksmith.create_mock_header_for_files(&[&header]).unwrap();
+    assert!(mock_header.code.contains(&lines!(
+        "// bar",
+        "class MockFoo : public IFoo",
+        "{",
+        "public:",
+        "  // Mock of bar",
+        "  MOCK_METHOD(void, bar, (), (override));",
+        "};"
+    )));
+}
+
+#[test]
+fn invalid_template_is_reported() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .template(Some("{% for %}".to_string()));
+    let result = mocksmith.create_mocks_from_string("class Foo { virtual void bar() = 0; };");
+    assert!(matches!(result, Err(MocksmithError::TemplateError(_))));
+}
+
+#[test]
+fn modules_with_guard_macro_abstract_assertions_or_forward_declarations_are_rejected() {
+    let cpp_class = "class Foo { virtual void bar() = 0; };";
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .module_name(Some("mocks".to_string()))
+        .guard_macro(Some("UNIT_TEST".to_string()));
+    assert!(matches!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        Err(MocksmithError::IncompatibleOptions(_))
+    ));
+    let mocksmith = mocksmith.guard_macro(None).assert_not_abstract(true);
+    assert!(matches!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        Err(MocksmithError::IncompatibleOptions(_))
+    ));
+    let mocksmith = mocksmith
+        .assert_not_abstract(false)
+        .forward_declare_types(true);
+    assert!(matches!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        Err(MocksmithError::IncompatibleOptions(_))
+    ));
+}
+
+#[test]
+fn include_style_of_mocked_header_can_be_configured() {
+    let dir = temp_dir();
+    std::fs::create_dir(dir.path().join("src")).unwrap();
+    let header = dir.path().join("src").join("ifoo.h");
+    std::fs::write(
+        &header,
+        "class IFoo { public: virtual ~IFoo() = default; virtual void bar() = 0; };",
+    )
+    .unwrap();
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .include_path(dir.path())
+        .include_with_angle_brackets(true)
+        .strip_include_prefix(Some("src".to_string()))
+        .include_prefix(Some("mylib".to_string()));
+    let mock_header = mocksmith.create_mock_header_for_files(&[&header]).unwrap();
+    assert!(mock_header.code.contains("\n#include <mylib/ifoo.h>\n"));
+}
+
+#[test]
+fn overloads_not_mocked_are_kept_visible() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .method_filter_fun(|method| !method.signature.contains("double"));
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar(int value) = 0;
+            virtual void bar(double value) {}
+            template<typename T> void bar(T* values) {}
+            virtual void fizz(int value) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  using Foo::bar;",
+            "  MOCK_METHOD(void, bar, (int value), (override));",
+            "  MOCK_METHOD(void, fizz, (int value), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn overloads_are_not_kept_visible_when_any_overload_is_private() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .method_filter_fun(|method| !method.signature.contains("double"));
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar(int value) = 0;
+            virtual void bar(double value) {}
+          private:
+            void bar(const char * value) {}
+          };";
+    let code = format!(
+        "{cpp_class}\n{}",
+        mocksmith.create_mocks_from_string(cpp_class).unwrap()[0].code
+    );
+    assert!(!code.contains("using Foo::bar;"));
+    if let Some(compiled) = compiles_with_gmock(&code) {
+        assert!(compiled, "Generated code doesn't compile:\n{code}");
+    }
+}
+
+#[test]
+fn long_mock_methods_are_wrapped_at_column_limit() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .column_limit(Some(50));
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar(int a) = 0;
+            virtual void baz(const char * name, int count) const = 0;
+            virtual void qux(const char * first_name, const char * last_name) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (int a), (override));",
+            "  MOCK_METHOD(void, baz,",
+            "              (const char * name, int count),",
+            "              (const, override));",
+            "  MOCK_METHOD(void, qux,",
+            "              (const char * first_name,",
+            "               const char * last_name),",
+            "              (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn mocks_are_put_in_extra_mock_namespace() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .mock_namespace(Some("testing::mocks".to_string()));
+    let cpp_class = "
+          namespace foo {
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar() = 0;
+          };
+          }";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "namespace foo::testing::mocks {",
+            "class MockFoo : public ::foo::Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};",
+            "}"
+        )
+    );
+}
+
+#[test]
+fn access_specifiers_are_preserved_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .preserve_access(true);
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar() = 0;
+          protected:
+            virtual void baz() = 0;
+          private:
+            virtual void qux() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "protected:",
+            "  MOCK_METHOD(void, baz, (), (override));",
+            "private:",
+            "  MOCK_METHOD(void, qux, (), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn friend_tests_are_added_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .preserve_access(true)
+        .friend_tests(vec!["FooTest.CallsBaz".to_string()]);
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+          protected:
+            virtual void baz() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  FRIEND_TEST(FooTest, CallsBaz);",
+            "protected:",
+            "  MOCK_METHOD(void, baz, (), (override));",
+            "};"
+        )
+    );
+    let source_file = temp_file_from(cpp_class);
+    let header = mocksmith
+        .create_mock_header_for_files(&[source_file.path()])
+        .unwrap();
+    assert!(header.code.contains("\n#include <gtest/gtest_prod.h>\n"));
+
+    let mocksmith = mocksmith.friend_tests(vec!["FooTest".to_string()]);
+    assert_eq!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        Err(MocksmithError::InvalidIdentifier("FooTest".to_string()))
+    );
+}
+
+#[test]
+fn factory_helpers_are_added_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .factory_helpers(Some(FactoryPointer::Unique));
+    let cpp_class = "
+          class IFoo {
+          public:
+            virtual ~IFoo() = default;
+            virtual void bar() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public IFoo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};",
+            "inline std::unique_ptr<IFoo> makeMockFoo(MockFoo *& out)",
+            "{",
+            "  auto mock = std::make_unique<MockFoo>();",
+            "  out = mock.get();",
+            "  return mock;",
+            "}"
+        )
+    );
+}
+
+#[test]
+fn interface_adapter_and_mock_are_generated_when_extracting_interface() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .extract_interface(true);
+    let cpp_class = "
+          namespace foo {
+          class Foo {
+          public:
+            void bar(int a);
+            int baz(const char *) const;
+          private:
+            void qux();
+          };
+          }";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "namespace foo {",
+            "class IFoo",
+            "{",
+            "public:",
+            "  virtual ~IFoo() = default;",
+            "  virtual void bar(int a) = 0;",
+            "  virtual int baz(const char *) const = 0;",
+            "};",
+            "}",
+            "",
+            "namespace foo {",
+            "class FooAdapter : public IFoo",
+            "{",
+            "public:",
+            "  explicit FooAdapter(Foo & adaptee) : adaptee_(adaptee) {}",
+            "  void bar(int arg0) override { adaptee_.bar(arg0); }",
+            "  int baz(const char * arg0) const override { return adaptee_.baz(arg0); }",
+            "",
+            "private:",
+            "  Foo & adaptee_;",
+            "};",
+            "}",
+            "",
+            "namespace foo {",
+            "class MockFoo : public IFoo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (int a), (override));",
+            "  MOCK_METHOD(int, baz, (const char *), (const, override));",
+            "};",
+            "}"
+        )
+    );
+}
+
+#[test]
+fn static_assert_that_mock_is_not_abstract_is_added_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .assert_not_abstract(true)
+        .method_filter_fun(|method| method.name != "baz");
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar() = 0;
+            virtual void baz() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};",
+            "static_assert(!std::is_abstract<MockFoo>::value, \
+             \"MockFoo is abstract, some pure virtual method is not mocked\");"
+        )
+    );
+}
+
+#[test]
+fn headers_declaring_argument_types_are_included_when_configured() {
+    let dir = temp_dir();
+    std::fs::create_dir(dir.path().join("types")).unwrap();
+    std::fs::write(dir.path().join("types").join("bar.h"), "struct Bar {};").unwrap();
+    std::fs::write(dir.path().join("baz.h"), "struct Baz {};").unwrap();
+    let header = dir.path().join("ifoo.h");
+    std::fs::write(
+        &header,
+        "#include \"types/bar.h\"\n\
+         #include \"baz.h\"\n\
+         class IFoo {\n\
+         public:\n\
+           virtual ~IFoo() = default;\n\
+           virtual void bar(const Bar & bar) = 0;\n\
+           virtual Baz * baz() = 0;\n\
+         };\n",
+    )
+    .unwrap();
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .include_path(dir.path())
+        .include_type_headers(true);
+    let mock_header = mocksmith.create_mock_header_for_files(&[&header]).unwrap();
+    assert!(mock_header.code.contains(&lines!(
+        "#include \"ifoo.h\"",
+        "#include \"types/bar.h\"",
+        "#include \"baz.h\"",
+        "#include <gmock/gmock.h>"
+    )));
+}
+
+#[test]
+fn types_are_fully_qualified_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .fully_qualify_types(true);
+    let cpp_class = "
+          namespace foo { namespace inner { struct Bar {}; } }
+          using namespace foo;
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual inner::Bar bar(const inner::Bar & bar) = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(foo::inner::Bar, bar, (const foo::inner::Bar & bar), (override));",
+            "};"
+        )
+    );
+}
+
+#[test]
+fn types_used_by_reference_are_forward_declared_when_configured() {
+    let dir = temp_dir();
+    std::fs::write(dir.path().join("bar.h"), "namespace bar { class Bar {}; }").unwrap();
+    std::fs::write(dir.path().join("baz.h"), "struct Baz {};").unwrap();
+    let header = dir.path().join("ifoo.h");
+    std::fs::write(
+        &header,
+        "#include \"bar.h\"\n\
+         #include \"baz.h\"\n\
+         class IFoo {\n\
+         public:\n\
+           virtual ~IFoo() = default;\n\
+           virtual void bar(const bar::Bar & bar) = 0;\n\
+           virtual Baz baz() = 0;\n\
+         };\n",
+    )
+    .unwrap();
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .include_path(dir.path())
+        .include_type_headers(true)
+        .forward_declare_types(true);
+    let mock_header = mocksmith.create_mock_header_for_files(&[&header]).unwrap();
+    assert!(mock_header.code.contains(&lines!(
+        "#include \"ifoo.h\"",
+        "#include \"baz.h\"",
+        "#include <gmock/gmock.h>",
+        "",
+        "namespace bar { class Bar; }"
+    )));
+}
+
+#[test]
+fn mocks_and_methods_are_sorted_when_configured() {
+    let mocksmith = Mocksmith::new_when_available().unwrap().sort_output(true);
+    let cpp_class = "
+          namespace foo {
+          class Zoo {
+          public:
+            virtual void zoo() = 0;
+          };
+          }
+          class Foo {
+          public:
+            virtual void qux() = 0;
+            virtual void bar(int a) = 0;
+            virtual void bar() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "  MOCK_METHOD(void, bar, (int a), (override));",
+            "  MOCK_METHOD(void, qux, (), (override));",
+            "};"
+        ),
+        lines!(
+            "namespace foo {",
+            "class MockZoo : public Zoo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, zoo, (), (override));",
+            "};",
+            "}"
+        )
+    );
+}
+
+#[test]
+fn mocked_class_is_qualified_in_mocks_in_extra_namespace() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .mock_namespace(Some("testing".to_string()));
+    let cpp_class = "
+          namespace foo {
+          class Foo {
+          public:
+            Foo(int a);
+            virtual ~Foo() = default;
+            virtual void bar() = 0;
+          };
+          }";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "namespace foo::testing {",
+            "class MockFoo : public ::foo::Foo",
+            "{",
+            "public:",
+            "  using ::foo::Foo::Foo;",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};",
+            "}"
+        )
+    );
+}
+
+#[test]
+fn mocks_in_extra_namespace_can_be_named_like_mocked_classes() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .mock_namespace(Some("testing".to_string()))
+        .mock_name_fun(|name| name.to_string());
+    let cpp_class = "
+          namespace foo {
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar() = 0;
+          };
+          }";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "namespace foo::testing {",
+            "class Foo : public ::foo::Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};",
+            "}"
+        )
+    );
+}
+
+#[test]
+fn nice_and_strict_helpers_are_added_when_configured() {
+    let mocksmith = Mocksmith::new_when_available()
+        .unwrap()
+        .nice_strict_helpers(Some(HelperAllocation::Heap));
+    let cpp_class = "
+          class Foo {
+          public:
+            virtual ~Foo() = default;
+            virtual void bar() = 0;
+          };";
+    assert_mocks!(
+        mocksmith.create_mocks_from_string(cpp_class),
+        lines!(
+            "class MockFoo : public Foo",
+            "{",
+            "public:",
+            "  MOCK_METHOD(void, bar, (), (override));",
+            "};",
+            "inline std::unique_ptr<::testing::NiceMock<MockFoo>> makeNiceMockFoo()",
+            "{",
+            "  return std::make_unique<::testing::NiceMock<MockFoo>>();",
+            "}",
+            "inline std::unique_ptr<::testing::StrictMock<MockFoo>> makeStrictMockFoo()",
+            "{",
+            "  return std::make_unique<::testing::StrictMock<MockFoo>>();",
+            "}"
+        )
+    );
+}
+
+#[test]
+fn source_hash_only_changes_when_declaration_changes() {
+    let mocksmith = Mocksmith::new_when_available().unwrap();
+    let hash = |cpp_class: &str| {
+        mocksmith.create_mocks_from_string(cpp_class).unwrap()[0]
+            .source_hash
+            .clone()
+    };
+    let original = hash("class Foo { public: virtual void bar(int value) = 0; };");
+    assert_eq!(original.len(), 64);
+    assert_eq!(
+        original,
+        hash("class Foo {\npublic:\n  // Comment\n  virtual void bar(int) = 0;\n};")
+    );
+    assert_ne!(
+        original,
+        hash("class Foo { public: virtual void bar(long value) = 0; };")
+    );
+}
@@ -56,6 +56,24 @@ impl Mocksmith {
         self
     }
 
+    /// Sets an environment variable for the mocksmith process
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        if self.process.is_some() {
+            panic!("Mocksmith is already running!");
+        }
+        self.command.env(key, value);
+        self
+    }
+
+    /// Sets the working directory of the mocksmith process
+    pub fn current_dir(mut self, path: &std::path::Path) -> Self {
+        if self.process.is_some() {
+            panic!("Mocksmith is already running!");
+        }
+        self.command.current_dir(path);
+        self
+    }
+
     /// Runs mocksmith with the provided arguments
     pub fn run(mut self) -> Self {
         if self.process.is_some() {